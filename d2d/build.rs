@@ -0,0 +1,19 @@
+//! Direct2D is only available on Windows, but the crate is also built on other
+//! hosts so that its CPU-side functionality can be unit tested on any CI
+//! runner. The `windows` crate unconditionally links `d2d1` for some helper
+//! functions which we never call, so on non-Windows targets we provide an empty
+//! stand-in archive for the linker to find.
+
+use ::std::{env, fs, path::PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    if env::var("CARGO_CFG_WINDOWS").is_ok() {
+        return;
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR set by cargo"));
+    fs::write(out_dir.join("libd2d1.a"), b"!<arch>\n").expect("failed to write stub d2d1");
+    println!("cargo:rustc-link-search=native={}", out_dir.display());
+}
@@ -1,6 +1,8 @@
 #![cfg_attr(not(feature = "stdio"), windows_subsystem = "windows")]
 
-use ::d2d::{brushes::SolidColorBrush, Color, D2DFactory, RenderTarget};
+mod scene;
+
+use ::d2d::{D2DFactory, DrawList, Palette, RenderTarget, SnapMode};
 use ::std::rc::Rc;
 use ::win32::{
     proc::ComLibraryHandle,
@@ -10,7 +12,7 @@ use ::win32::{
         window::{Theme, Window},
     },
 };
-use ::win_geom::d2::{Point2D, Rect2D, Size2D};
+use ::win_geom::d2::Size2D;
 use ::windows::Win32::UI::WindowsAndMessaging::{
     DispatchMessageW, GetMessageW, PostQuitMessage, TranslateMessage, MSG,
 };
@@ -31,34 +33,6 @@ pub fn main() {
     example.run_message_loop().unwrap();
 }
 
-/// A simple structure which holds our Direct2D device dependent resources.
-/// These are cached and re-used across drawing calls.
-struct Resources {
-    rect_stroke_brush: SolidColorBrush,
-    rect_fill_brush: SolidColorBrush,
-    grid_brush: SolidColorBrush,
-    background_color: Color,
-}
-
-impl Resources {
-    fn make(render_target: &mut RenderTarget, theme: Theme) -> Self {
-        match theme {
-            Theme::DarkMode => Self {
-                rect_stroke_brush: render_target.make_solid_color_brush(Color::cornflower_blue()),
-                rect_fill_brush: render_target.make_solid_color_brush(Color::dark_slate_gray()),
-                grid_brush: render_target.make_solid_color_brush(Color::dark_slate_gray()),
-                background_color: Color::black(),
-            },
-            Theme::LightMode => Self {
-                rect_stroke_brush: render_target.make_solid_color_brush(Color::cornflower_blue()),
-                rect_fill_brush: render_target.make_solid_color_brush(Color::light_slate_gray()),
-                grid_brush: render_target.make_solid_color_brush(Color::light_slate_gray()),
-                background_color: Color::white(),
-            },
-        }
-    }
-}
-
 /// Our example app and all state.
 pub struct ExampleApp {
     /// The main window for our native Win32 application.
@@ -68,8 +42,11 @@ pub struct ExampleApp {
     _factory: Rc<D2DFactory>,
     /// Our Direct2D render target which pains the main window's client area.
     render_target: RenderTarget,
-    /// Cached device-specific drawing resources re-used in each drawing call.
-    resources: Resources,
+    /// The colors in which the scene is drawn.
+    palette: Palette,
+    /// The scene recorded for the most recent frame, re-used between frames
+    /// to avoid re-allocating.
+    scene: DrawList,
 }
 
 impl ExampleApp {
@@ -81,94 +58,40 @@ impl ExampleApp {
             .expect("Failed to create main window");
 
         let factory = D2DFactory::new().expect("Failed to create Direct2D factory");
-        let mut render_target = factory.make_render_target(main_window.hwnd(), size);
-        let resources = Resources::make(&mut render_target, theme);
+        let render_target = factory.make_render_target(main_window.hwnd(), size);
 
         Self {
             main_window,
             _factory: factory,
             render_target,
-            resources,
+            palette: Palette::for_theme(theme),
+            scene: DrawList::new(),
         }
     }
 
-    /// Draw the main window contents. This is a simple example of Direct2D
-    /// drawing and will paint the following:
-    ///
-    /// * An 8x8 (density independent pixel) grid of alternating red, green,
-    /// blue lines * A large outline of a rectangle in the center * A smaller
-    /// filled rounded rectangle within the larger rect * A small circle within
-    /// the rounded rectangle
+    /// Draw the main window contents, as recorded by [`scene::draw`].
     fn draw(&mut self) {
+        self.scene.reset();
+        scene::draw(&mut self.scene, self.main_window.size(), &self.palette);
+
         // Drawing must always begin with a `begin_draw` call. All drawing is
         // done via the returned `Context`, and our render target is held locked
         // until the corresponding `end_draw` call.
-        let mut ctx = self.render_target.begin_draw();
-        // Erase the last contents by paining the client area white.
-        ctx.clear(self.resources.background_color);
-
-        // Cache our main window dimensions both as i32 and f32 values.
-        let dimensions = self.main_window.size().cast::<f32>();
-
-        // Draw grid with 8px squares
-        let stroke_width = 0.5;
-        for x in (0..self.main_window.size().width)
-            .step_by(8)
-            .map(|u| u as f32)
-        {
-            ctx.draw_line(
-                Point2D { x, y: 0.0 },
-                Point2D {
-                    x,
-                    y: dimensions.height,
-                },
-                stroke_width,
-                &mut self.resources.grid_brush,
-            );
-        }
-        for y in (0..self.main_window.size().height)
-            .step_by(8)
-            .map(|u| u as f32)
-        {
-            ctx.draw_line(
-                Point2D { x: 0.0, y },
-                Point2D {
-                    x: dimensions.width,
-                    y,
-                },
-                stroke_width,
-                &mut self.resources.grid_brush,
-            );
-        }
-
-        // Draw two rectangles, one inner filled rectangle and one stroked
-        // larger rectangle.
-        ctx.fill_rect(
-            Rect2D {
-                left: (dimensions.width / 2.0 - 56.0),
-                right: (dimensions.width / 2.0 + 56.0),
-                top: (dimensions.height / 2.0 - 56.0),
-                bottom: (dimensions.height / 2.0 + 56.0),
-            },
-            &mut self.resources.rect_fill_brush,
-        );
-        let stroke_width = 1.0;
-        ctx.stroke_rect(
-            Rect2D {
-                left: (dimensions.width / 2.0 - 104.0),
-                right: (dimensions.width / 2.0 + 104.0),
-                top: (dimensions.height / 2.0 - 104.0),
-                bottom: (dimensions.height / 2.0 + 104.0),
-            },
-            &mut self.resources.rect_stroke_brush,
-            stroke_width,
-        );
+        let mut ctx = self
+            .render_target
+            .begin_draw()
+            .expect("Failed to begin drawing");
+        // Keep the hairline grid crisp at any display scaling.
+        ctx.set_snap_mode(SnapMode::Positions);
+        self.scene
+            .replay(&mut ctx)
+            .expect("Failed to draw the scene");
 
         // Drawing must end with `end_draw`. This causes the batched changes to
         // be pushed to the hardware and drawn to the screen. It also releases
         // the lock on the render target which is required before any subsequent
         // draw calls.
-        ctx.end_draw();
+        ctx.end_draw().expect("Failed to draw the frame");
     }
 
     /// Pump our Win32 message loop. The inner `main_window` will handle most
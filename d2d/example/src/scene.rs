@@ -0,0 +1,94 @@
+//! The example's scene, recorded into a [`DrawList`] so that it can be
+//! replayed onto the window's render target or rendered off-screen in tests.
+
+use ::d2d::{DrawList, Palette};
+use ::win_geom::d2::{Point2D, Rect2D, Size2D};
+
+/// Records the scene for a window client area of the given size. This is a
+/// simple example of drawing and will paint the following:
+///
+/// * An 8x8 (density independent pixel) grid of lines
+/// * A large outline of a rectangle in the center
+/// * A smaller filled rectangle within the larger rect
+pub fn draw(list: &mut DrawList, size: Size2D<i32>, palette: &Palette) {
+    // Erase the last contents by painting the client area with the palette's
+    // background color.
+    list.clear(palette.background);
+
+    // Cache our main window dimensions as f32 values.
+    let dimensions = size.cast::<f32>();
+
+    // Draw grid with 8px squares
+    let stroke_width = 0.5;
+    for x in (0..size.width).step_by(8).map(|u| u as f32) {
+        list.draw_line(
+            Point2D { x, y: 0.0 },
+            Point2D {
+                x,
+                y: dimensions.height,
+            },
+            stroke_width,
+            palette.border,
+            None,
+        );
+    }
+    for y in (0..size.height).step_by(8).map(|u| u as f32) {
+        list.draw_line(
+            Point2D { x: 0.0, y },
+            Point2D {
+                x: dimensions.width,
+                y,
+            },
+            stroke_width,
+            palette.border,
+            None,
+        );
+    }
+
+    // Draw two rectangles, one inner filled rectangle and one stroked larger
+    // rectangle.
+    list.fill_rect(
+        Rect2D {
+            left: (dimensions.width / 2.0 - 56.0),
+            right: (dimensions.width / 2.0 + 56.0),
+            top: (dimensions.height / 2.0 - 56.0),
+            bottom: (dimensions.height / 2.0 + 56.0),
+        },
+        palette.surface,
+    );
+    let stroke_width = 1.0;
+    list.stroke_rect(
+        Rect2D {
+            left: (dimensions.width / 2.0 - 104.0),
+            right: (dimensions.width / 2.0 + 104.0),
+            top: (dimensions.height / 2.0 - 104.0),
+            bottom: (dimensions.height / 2.0 + 104.0),
+        },
+        palette.accent,
+        stroke_width,
+        None,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::d2d::snapshot::Snapshots;
+
+    #[test]
+    fn test_scene_matches_snapshot() {
+        let size = Size2D {
+            width: 720,
+            height: 640,
+        };
+        let snapshots = Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots"));
+
+        snapshots.assert("scene_dark", size.cast(), |list| {
+            draw(list, size, &Palette::dark())
+        });
+        snapshots.assert("scene_light", size.cast(), |list| {
+            draw(list, size, &Palette::light())
+        });
+    }
+}
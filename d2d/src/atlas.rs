@@ -0,0 +1,338 @@
+//! Packing many small images into a single texture atlas, from which they can
+//! be drawn together with a [`SpriteBatch`](crate::SpriteBatch).
+
+use crate::imaging::Image;
+use ::win_geom::d2::{Rect2D, Size2D};
+
+/// Packs rectangles into a fixed-size area, e.g. to lay out the regions of a
+/// texture atlas.
+///
+/// Rectangles are placed on horizontal shelves, each as tall as the first
+/// rectangle placed on it. A rectangle goes on the shortest shelf it fits,
+/// or else on a new shelf below the others. Packing is fastest and tightest
+/// when rectangles are inserted tallest first, as [`TextureAtlas::pack`]
+/// does.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::AtlasPacker;
+/// use ::win_geom::d2::{Rect2D, Size2D};
+///
+/// let mut packer = AtlasPacker::new(Size2D { width: 64, height: 64 });
+/// let region = packer.insert(Size2D { width: 16, height: 16 });
+///
+/// assert_eq!(region, Some(Rect2D { left: 0, top: 0, right: 16, bottom: 16 }));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtlasPacker {
+    size: Size2D<u32>,
+    padding: u32,
+    shelves: Vec<Shelf>,
+}
+
+/// A row of rectangles placed left to right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Shelf {
+    top: u32,
+    height: u32,
+    /// The left of the free space at the end of the shelf.
+    end: u32,
+}
+
+impl AtlasPacker {
+    /// Constructs a packer for an empty area of `size`.
+    pub fn new(size: Size2D<u32>) -> Self {
+        Self {
+            size,
+            padding: 0,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Sets the space left between packed rectangles, which stops linear
+    /// filtering from bleeding neighboring regions into each other.
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// The size of the area into which rectangles are packed.
+    pub fn size(&self) -> Size2D<u32> {
+        self.size
+    }
+
+    /// Places a rectangle of `size`, returning where it was placed or `None`
+    /// if there is no room left for it.
+    pub fn insert(&mut self, size: Size2D<u32>) -> Option<Rect2D<u32>> {
+        let padded = Size2D {
+            width: size.width.checked_add(self.padding)?,
+            height: size.height.checked_add(self.padding)?,
+        };
+        // Padding is only needed between rectangles, not at the edges. Sizes
+        // which overflow don't fit.
+        let fits_width = |end: u32| {
+            end.checked_add(size.width)
+                .is_some_and(|right| right <= self.size.width)
+        };
+
+        let shelf = match self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= padded.height && fits_width(shelf.end))
+            .min_by_key(|shelf| shelf.height)
+        {
+            Some(shelf) => shelf,
+            None => {
+                let top = self
+                    .shelves
+                    .last()
+                    .map_or(Some(0), |shelf| shelf.top.checked_add(shelf.height))?;
+                let fits_height = top
+                    .checked_add(size.height)
+                    .is_some_and(|bottom| bottom <= self.size.height);
+                if !fits_height || !fits_width(0) {
+                    return None;
+                }
+                self.shelves.push(Shelf {
+                    top,
+                    height: padded.height,
+                    end: 0,
+                });
+                self.shelves.last_mut().expect("shelf was just pushed")
+            }
+        };
+
+        let placed = Rect2D {
+            left: shelf.end,
+            top: shelf.top,
+            right: shelf.end.checked_add(size.width)?,
+            bottom: shelf.top.checked_add(size.height)?,
+        };
+        // Padding which overflows leaves no room on the shelf.
+        shelf.end = shelf.end.saturating_add(padded.width);
+        Some(placed)
+    }
+
+    /// Removes all placed rectangles.
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+    }
+}
+
+/// A single image combining many smaller ones, along with the region of each,
+/// so that they can all be drawn from one [`Bitmap`](crate::bitmaps::Bitmap).
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{Image, TextureAtlas};
+/// use ::win_geom::d2::Size2D;
+///
+/// let tile = Image::from_rgba(Size2D { width: 8, height: 8 }, &[255; 8 * 8 * 4])?;
+/// let icon = Image::from_rgba(Size2D { width: 4, height: 4 }, &[128; 4 * 4 * 4])?;
+///
+/// let atlas = TextureAtlas::pack(&[&tile, &icon], Size2D { width: 16, height: 16 }, 1)
+///     .expect("images fit in the atlas");
+/// assert_eq!(atlas.region(1).width(), 4);
+/// # Ok::<(), ::d2d::ImageError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextureAtlas {
+    image: Image,
+    regions: Vec<Rect2D<u32>>,
+}
+
+impl TextureAtlas {
+    /// Packs `images` into an atlas of `size`, leaving `padding` transparent
+    /// pixels between them. Returns `None` if they do not all fit.
+    ///
+    /// Empty images take up no space, and are given an empty region at the
+    /// origin.
+    pub fn pack(images: &[&Image], size: Size2D<u32>, padding: u32) -> Option<Self> {
+        let is_empty = |image: &Image| image.size().width == 0 || image.size().height == 0;
+        // Placing the tallest images first keeps shelves tightly filled.
+        let mut order = (0..images.len())
+            .filter(|&i| !is_empty(images[i]))
+            .collect::<Vec<_>>();
+        order.sort_by_key(|&i| ::std::cmp::Reverse(images[i].size().height));
+
+        let mut packer = AtlasPacker::new(size).with_padding(padding);
+        let empty = Rect2D {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        let mut regions = vec![empty; images.len()];
+        for i in order {
+            regions[i] = packer.insert(images[i].size())?;
+        }
+
+        let stride = size.width as usize * 4;
+        let mut pixels = vec![0; stride * size.height as usize];
+        for (image, region) in images.iter().zip(&regions) {
+            if is_empty(image) {
+                continue;
+            }
+            let row_len = image.stride() as usize;
+            for (y, row) in image.pixels().chunks_exact(row_len).enumerate() {
+                let start = (region.top as usize + y) * stride + region.left as usize * 4;
+                pixels[start..start + row_len].copy_from_slice(row);
+            }
+        }
+
+        let image = Image::from_premultiplied_bgra(size, pixels)
+            .expect("atlas pixels are allocated to its size");
+        Some(Self { image, regions })
+    }
+
+    /// The combined image, from which a bitmap can be made with
+    /// [`RenderTarget::make_bitmap`](crate::RenderTarget::make_bitmap).
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// The region of each packed image, in the order they were given.
+    pub fn regions(&self) -> &[Rect2D<u32>] {
+        &self.regions
+    }
+
+    /// The region of the `index`th packed image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn region(&self, index: usize) -> Rect2D<u32> {
+        self.regions[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    fn size(width: u32, height: u32) -> Size2D<u32> {
+        Size2D { width, height }
+    }
+
+    fn overlaps(a: &Rect2D<u32>, b: &Rect2D<u32>) -> bool {
+        a.left < b.right && b.left < a.right && a.top < b.bottom && b.top < a.bottom
+    }
+
+    #[test]
+    fn test_packs_without_overlap() {
+        let mut packer = AtlasPacker::new(size(64, 64)).with_padding(1);
+        let sizes = [(16, 16), (30, 8), (8, 20), (16, 16), (40, 4), (5, 5)];
+
+        let placed = sizes
+            .iter()
+            .map(|&(w, h)| packer.insert(size(w, h)).expect("rect fits"))
+            .collect::<Vec<_>>();
+
+        for (i, a) in placed.iter().enumerate() {
+            assert!(a.right <= 64 && a.bottom <= 64, "{a:?} out of bounds");
+            for b in &placed[i + 1..] {
+                assert!(!overlaps(a, b), "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_prefers_shortest_fitting_shelf() {
+        let mut packer = AtlasPacker::new(size(32, 64));
+        packer.insert(size(24, 16));
+        packer.insert(size(24, 8));
+
+        // Fits on either shelf, but wastes less space on the shorter one.
+        assert_eq!(
+            packer.insert(size(8, 8)),
+            Some(Rect2D {
+                left: 24,
+                top: 16,
+                right: 32,
+                bottom: 24
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_when_full() {
+        let mut packer = AtlasPacker::new(size(16, 16));
+
+        assert!(packer.insert(size(17, 1)).is_none());
+        assert!(packer.insert(size(16, 16)).is_some());
+        assert!(packer.insert(size(1, 1)).is_none());
+
+        packer.clear();
+        assert!(packer.insert(size(1, 1)).is_some());
+    }
+
+    #[test]
+    fn test_rejects_overflowing_sizes() {
+        let mut packer = AtlasPacker::new(size(16, 16)).with_padding(1);
+        assert!(packer.insert(size(1, 1)).is_some());
+
+        assert!(packer.insert(size(u32::MAX, 1)).is_none());
+        assert!(packer.insert(size(1, u32::MAX)).is_none());
+        assert!(packer.insert(size(u32::MAX - 1, u32::MAX - 1)).is_none());
+
+        let mut packer = AtlasPacker::new(size(16, 16)).with_padding(u32::MAX - 1);
+        assert!(packer.insert(size(1, 1)).is_some());
+        assert!(packer.insert(size(1, 1)).is_none());
+    }
+
+    #[test]
+    fn test_atlas_copies_images_into_regions() {
+        let red = Image::from_rgba(size(1, 1), &[255, 0, 0, 255]).expect("valid image");
+        let blue = Image::from_rgba(size(2, 2), &[0, 0, 255, 255].repeat(4)).expect("valid image");
+
+        let atlas = TextureAtlas::pack(&[&red, &blue], size(4, 2), 0).expect("images fit");
+
+        // The taller image is placed first.
+        assert_eq!(
+            atlas.region(1),
+            Rect2D {
+                left: 0,
+                top: 0,
+                right: 2,
+                bottom: 2
+            }
+        );
+        assert_eq!(
+            atlas.region(0),
+            Rect2D {
+                left: 2,
+                top: 0,
+                right: 3,
+                bottom: 1
+            }
+        );
+        let rgba = atlas.image().to_rgba();
+        assert_eq!(rgba[8..12], [255, 0, 0, 255]);
+        assert_eq!(rgba[16..20], [0, 0, 255, 255]);
+        assert_eq!(rgba[24..28], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_atlas_skips_empty_images() {
+        let empty = Image::from_rgba(size(0, 3), &[]).expect("valid image");
+        let red = Image::from_rgba(size(1, 1), &[255, 0, 0, 255]).expect("valid image");
+
+        let atlas = TextureAtlas::pack(&[&empty, &red, &empty], size(1, 1), 1).expect("images fit");
+
+        assert_eq!(atlas.region(0), atlas.region(2));
+        assert_eq!((atlas.region(0).width(), atlas.region(0).height()), (0, 0));
+        assert_eq!(atlas.region(1).width(), 1);
+        assert_eq!(atlas.image().to_rgba(), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_atlas_rejects_images_which_do_not_fit() {
+        let image = Image::from_rgba(size(4, 4), &[0; 64]).expect("valid image");
+
+        assert!(TextureAtlas::pack(&[&image, &image], size(6, 4), 0).is_none());
+    }
+}
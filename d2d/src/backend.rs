@@ -0,0 +1,51 @@
+//! An abstraction over the renderers which can draw a [`DrawList`].
+
+use crate::{context::Context, draw_list::DrawList, software::SoftwareRenderTarget};
+use ::std::convert::Infallible;
+
+/// A renderer which can draw a recorded [`DrawList`].
+///
+/// Code which records its drawing into a [`DrawList`] can be written once
+/// against this trait and rendered either by Direct2D, through a [`Context`],
+/// or on the CPU by a [`SoftwareRenderTarget`].
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{Backend, Color, DrawList, SoftwareRenderTarget};
+/// use ::win_geom::d2::{Rect2D, Size2D};
+///
+/// fn draw_frame<B: Backend>(backend: &mut B) -> Result<(), B::Error> {
+///     let mut list = DrawList::new();
+///     list.clear(Color::black());
+///     list.fill_rect(Rect2D { left: 1.0, top: 1.0, right: 3.0, bottom: 3.0 }, Color::white());
+///     backend.render(&list)
+/// }
+///
+/// let mut target = SoftwareRenderTarget::new(Size2D { width: 4, height: 4 });
+/// draw_frame(&mut target).unwrap();
+/// ```
+pub trait Backend {
+    /// The error returned if rendering fails.
+    type Error;
+
+    /// Renders all commands in `list`.
+    fn render(&mut self, list: &DrawList) -> Result<(), Self::Error>;
+}
+
+impl Backend for Context<'_> {
+    type Error = crate::errors::Error;
+
+    fn render(&mut self, list: &DrawList) -> Result<(), Self::Error> {
+        list.replay(self)
+    }
+}
+
+impl Backend for SoftwareRenderTarget {
+    type Error = Infallible;
+
+    fn render(&mut self, list: &DrawList) -> Result<(), Self::Error> {
+        SoftwareRenderTarget::render(self, list);
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+//! Capturing rendered frames into CPU memory, e.g. to attach screenshots to
+//! bug reports or to compare frames in automated tests.
+
+use crate::{errors::Result, imaging::Image, software::SoftwareRenderTarget};
+use ::std::convert::Infallible;
+use ::win32::invoke::check_res;
+use ::win_geom::d2::Size2D;
+use ::windows::{
+    core::Interface,
+    Win32::Graphics::Direct2D::{
+        Common::D2D1_ALPHA_MODE_IGNORE, ID2D1Bitmap, ID2D1DeviceContext, ID2D1RenderTarget,
+        D2D1_BITMAP_OPTIONS_CANNOT_DRAW, D2D1_BITMAP_OPTIONS_CPU_READ, D2D1_BITMAP_PROPERTIES,
+        D2D1_BITMAP_PROPERTIES1, D2D1_MAP_OPTIONS_READ,
+    },
+};
+
+/// A render target whose most recent frame can be captured as an [`Image`].
+///
+/// Implemented by the window [`RenderTarget`](crate::RenderTarget), offscreen
+/// [`BitmapTarget`](crate::bitmaps::BitmapTarget)s and the
+/// [`SoftwareRenderTarget`], so that code which captures frames can be tested
+/// without a window.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{Capture, Color, DrawList, SoftwareRenderTarget};
+/// use ::win_geom::d2::Size2D;
+///
+/// let mut list = DrawList::new();
+/// list.clear(Color::white());
+///
+/// let mut target = SoftwareRenderTarget::new(Size2D { width: 8, height: 8 });
+/// target.render(&list);
+///
+/// let png = target.capture().unwrap().encode_png().unwrap();
+/// assert!(!png.is_empty());
+/// ```
+pub trait Capture {
+    /// The error returned if capturing fails.
+    type Error;
+
+    /// Copies the most recently drawn frame into an [`Image`].
+    fn capture(&self) -> ::std::result::Result<Image, Self::Error>;
+}
+
+impl Capture for SoftwareRenderTarget {
+    type Error = Infallible;
+
+    fn capture(&self) -> ::std::result::Result<Image, Self::Error> {
+        Ok(self.to_image())
+    }
+}
+
+/// Copies the current contents of `device_target` into a bitmap on the
+/// device, re-using `previous` if it is the right size.
+pub(crate) fn copy_frame(
+    device_target: &ID2D1RenderTarget,
+    previous: Option<ID2D1Bitmap>,
+) -> Result<ID2D1Bitmap> {
+    let size = unsafe { device_target.GetPixelSize() };
+    let frame = match previous {
+        Some(bitmap) if unsafe { bitmap.GetPixelSize() } == size => bitmap,
+        _ => {
+            let (mut dpi_x, mut dpi_y) = (0.0, 0.0);
+            unsafe { device_target.GetDpi(&mut dpi_x, &mut dpi_y) };
+            let props = D2D1_BITMAP_PROPERTIES {
+                pixelFormat: unsafe { device_target.GetPixelFormat() },
+                dpiX: dpi_x,
+                dpiY: dpi_y,
+            };
+            check_res(
+                || unsafe { device_target.CreateBitmap(size, None, 0, &props as _) },
+                "CreateBitmap",
+            )?
+        }
+    };
+
+    check_res(
+        || unsafe { frame.CopyFromRenderTarget(None, device_target, None) },
+        "CopyFromRenderTarget",
+    )?;
+    Ok(frame)
+}
+
+/// Reads the pixels of `bitmap`, which belongs to the device of
+/// `device_target`, back into CPU memory.
+pub(crate) fn read_bitmap(
+    device_target: &ID2D1RenderTarget,
+    bitmap: &ID2D1Bitmap,
+) -> Result<Image> {
+    // Only Direct2D 1.1 bitmaps can be mapped for reading, so the pixels are
+    // first copied into one.
+    let device_context = check_res(
+        || device_target.cast::<ID2D1DeviceContext>(),
+        "QueryInterface",
+    )?;
+    let size = unsafe { bitmap.GetPixelSize() };
+    let pixel_format = unsafe { bitmap.GetPixelFormat() };
+    let props = D2D1_BITMAP_PROPERTIES1 {
+        pixelFormat: pixel_format,
+        dpiX: 96.0,
+        dpiY: 96.0,
+        bitmapOptions: D2D1_BITMAP_OPTIONS_CPU_READ | D2D1_BITMAP_OPTIONS_CANNOT_DRAW,
+        colorContext: None,
+    };
+    let staging = check_res(
+        || unsafe { device_context.CreateBitmap2(size, None, 0, &props as _) },
+        "CreateBitmap",
+    )?;
+    check_res(
+        || unsafe { staging.CopyFromBitmap(None, bitmap, None) },
+        "CopyFromBitmap",
+    )?;
+
+    let mapped = check_res(|| unsafe { staging.Map(D2D1_MAP_OPTIONS_READ) }, "Map")?;
+    let size = Size2D {
+        width: size.width,
+        height: size.height,
+    };
+    let pixels = if size.width == 0 || size.height == 0 {
+        Vec::new()
+    } else {
+        let pitch = mapped.pitch as usize;
+        let len = pitch * (size.height as usize - 1) + size.width as usize * 4;
+        let bits = unsafe { ::std::slice::from_raw_parts(mapped.bits, len) };
+        pack_rows(
+            bits,
+            pitch,
+            size,
+            pixel_format.alphaMode == D2D1_ALPHA_MODE_IGNORE,
+        )
+    };
+    check_res(|| unsafe { staging.Unmap() }, "Unmap")?;
+
+    Ok(Image::from_premultiplied_bgra(size, pixels).expect("rows are packed to the image size"))
+}
+
+/// Packs rows of BGRA pixels which start every `pitch` bytes into tightly
+/// packed rows, making each pixel fully opaque if the alpha channel is
+/// `ignored`.
+fn pack_rows(bits: &[u8], pitch: usize, size: Size2D<u32>, ignored: bool) -> Vec<u8> {
+    let row_len = size.width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_len * size.height as usize);
+    for row in bits.chunks(pitch).take(size.height as usize) {
+        pixels.extend_from_slice(&row[..row_len]);
+    }
+
+    if ignored {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel[3] = 0xFF;
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, draw_list::DrawList};
+
+    use ::pretty_assertions::assert_eq;
+    use ::win_geom::d2::Rect2D;
+
+    #[test]
+    fn test_pack_rows_drops_padding() {
+        let size = Size2D {
+            width: 1,
+            height: 2,
+        };
+        let bits = [1, 2, 3, 4, 0, 0, 0, 0, 5, 6, 7, 8];
+
+        assert_eq!(pack_rows(&bits, 8, size, false), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            pack_rows(&bits, 8, size, true),
+            [1, 2, 3, 255, 5, 6, 7, 255]
+        );
+    }
+
+    /// Captures a frame through the trait, as a test would.
+    fn capture_rgba<C: Capture>(target: &C) -> Vec<u8> {
+        match target.capture() {
+            Ok(image) => image.to_rgba(),
+            Err(_) => panic!("capture failed"),
+        }
+    }
+
+    #[test]
+    fn test_capture_software_target() {
+        let mut list = DrawList::new();
+        list.clear(Color::black());
+        list.fill_rect(
+            Rect2D {
+                left: 1.0,
+                top: 0.0,
+                right: 2.0,
+                bottom: 1.0,
+            },
+            Color::white(),
+        );
+        let mut target = SoftwareRenderTarget::new(Size2D {
+            width: 2,
+            height: 1,
+        });
+        target.render(&list);
+
+        assert_eq!(capture_rgba(&target), [0, 0, 0, 255, 255, 255, 255, 255]);
+    }
+}
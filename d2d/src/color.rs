@@ -114,6 +114,245 @@ impl Color {
         !self.is_light()
     }
 
+    /// Parses a color from a CSS-style hex string in either `#RRGGBB` or
+    /// `#RRGGBBAA` format. The leading `#` is optional. Colors without an alpha
+    /// component are fully opaque.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ::d2d::Color;
+    ///
+    /// assert_eq!(Color::from_hex("#9ACD32"), Some(Color::yellow_green()));
+    /// assert_eq!(Color::from_hex("9ACD32FF"), Some(Color::yellow_green()));
+    /// assert_eq!(Color::from_hex("#9ACD3"), None);
+    /// ```
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let val = u32::from_str_radix(hex, 16).ok()?;
+
+        match hex.len() {
+            6 => Some(Self::new_rgba(val << 8 | 0xFF)),
+            8 => Some(Self::new_rgba(val)),
+            _ => None,
+        }
+    }
+
+    /// Looks up a color by its CSS keyword, e.g. `"cornflowerblue"`, ignoring
+    /// case. The British spelling "grey" is accepted wherever a keyword
+    /// contains "gray".
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ::d2d::Color;
+    ///
+    /// assert_eq!(Color::from_name("YellowGreen"), Some(Color::yellow_green()));
+    /// assert_eq!(Color::from_name("darkgrey"), Some(Color::dark_gray()));
+    /// assert_eq!(Color::from_name("yellowish"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase().replace("grey", "gray");
+        let color = match name.as_str() {
+            "aliceblue" => Self::alice_blue(),
+            "antiquewhite" => Self::antique_white(),
+            "aqua" => Self::aqua(),
+            "aquamarine" => Self::aquamarine(),
+            "azure" => Self::azure(),
+            "beige" => Self::beige(),
+            "bisque" => Self::bisque(),
+            "black" => Self::black(),
+            "blanchedalmond" => Self::blanched_almond(),
+            "blue" => Self::blue(),
+            "blueviolet" => Self::blue_violet(),
+            "brown" => Self::brown(),
+            "burlywood" => Self::burly_wood(),
+            "cadetblue" => Self::cadet_blue(),
+            "chartreuse" => Self::chartreuse(),
+            "chocolate" => Self::chocolate(),
+            "coral" => Self::coral(),
+            "cornflowerblue" => Self::cornflower_blue(),
+            "cornsilk" => Self::cornsilk(),
+            "crimson" => Self::crimson(),
+            "cyan" => Self::cyan(),
+            "darkblue" => Self::dark_blue(),
+            "darkcyan" => Self::dark_cyan(),
+            "darkgoldenrod" => Self::dark_goldenrod(),
+            "darkgray" => Self::dark_gray(),
+            "darkgreen" => Self::dark_green(),
+            "darkkhaki" => Self::dark_khaki(),
+            "darkmagenta" => Self::dark_magenta(),
+            "darkolivegreen" => Self::dark_olive_green(),
+            "darkorange" => Self::dark_orange(),
+            "darkorchid" => Self::dark_orchid(),
+            "darkred" => Self::dark_red(),
+            "darksalmon" => Self::dark_salmon(),
+            "darkseagreen" => Self::dark_sea_green(),
+            "darkslateblue" => Self::dark_slate_blue(),
+            "darkslategray" => Self::dark_slate_gray(),
+            "darkturquoise" => Self::dark_turquoise(),
+            "darkviolet" => Self::dark_violet(),
+            "deeppink" => Self::deep_pink(),
+            "deepskyblue" => Self::deep_sky_blue(),
+            "dimgray" => Self::dim_gray(),
+            "dodgerblue" => Self::dodger_blue(),
+            "firebrick" => Self::firebrick(),
+            "floralwhite" => Self::floral_white(),
+            "forestgreen" => Self::forest_green(),
+            "fuchsia" => Self::fuchsia(),
+            "gainsboro" => Self::gainsboro(),
+            "ghostwhite" => Self::ghost_white(),
+            "gold" => Self::gold(),
+            "goldenrod" => Self::goldenrod(),
+            "gray" => Self::gray(),
+            "green" => Self::green(),
+            "greenyellow" => Self::green_yellow(),
+            "honeydew" => Self::honeydew(),
+            "hotpink" => Self::hot_pink(),
+            "indianred" => Self::indian_red(),
+            "indigo" => Self::indigo(),
+            "ivory" => Self::ivory(),
+            "khaki" => Self::khaki(),
+            "lavender" => Self::lavender(),
+            "lavenderblush" => Self::lavender_blush(),
+            "lawngreen" => Self::lawn_green(),
+            "lemonchiffon" => Self::lemon_chiffon(),
+            "lightblue" => Self::light_blue(),
+            "lightcoral" => Self::light_coral(),
+            "lightcyan" => Self::light_cyan(),
+            "lightgoldenrodyellow" => Self::light_goldenrod_yellow(),
+            "lightgray" => Self::light_gray(),
+            "lightgreen" => Self::light_green(),
+            "lightpink" => Self::light_pink(),
+            "lightsalmon" => Self::light_salmon(),
+            "lightseagreen" => Self::light_sea_green(),
+            "lightskyblue" => Self::light_sky_blue(),
+            "lightslategray" => Self::light_slate_gray(),
+            "lightsteelblue" => Self::light_steel_blue(),
+            "lightyellow" => Self::light_yellow(),
+            "lime" => Self::lime(),
+            "limegreen" => Self::lime_green(),
+            "linen" => Self::linen(),
+            "magenta" => Self::magenta(),
+            "maroon" => Self::maroon(),
+            "mediumaquamarine" => Self::medium_aquamarine(),
+            "mediumblue" => Self::medium_blue(),
+            "mediumorchid" => Self::medium_orchid(),
+            "mediumpurple" => Self::medium_purple(),
+            "mediumseagreen" => Self::medium_sea_green(),
+            "mediumslateblue" => Self::medium_slate_blue(),
+            "mediumspringgreen" => Self::medium_spring_green(),
+            "mediumturquoise" => Self::medium_turquoise(),
+            "mediumvioletred" => Self::medium_violet_red(),
+            "midnightblue" => Self::midnight_blue(),
+            "mintcream" => Self::mint_cream(),
+            "mistyrose" => Self::misty_rose(),
+            "moccasin" => Self::moccasin(),
+            "navajowhite" => Self::navajo_white(),
+            "navy" => Self::navy(),
+            "oldlace" => Self::old_lace(),
+            "olive" => Self::olive(),
+            "olivedrab" => Self::olive_drab(),
+            "orange" => Self::orange(),
+            "orangered" => Self::orange_red(),
+            "orchid" => Self::orchid(),
+            "palegoldenrod" => Self::pale_goldenrod(),
+            "palegreen" => Self::pale_green(),
+            "paleturquoise" => Self::pale_turquoise(),
+            "palevioletred" => Self::pale_violet_red(),
+            "papayawhip" => Self::papaya_whip(),
+            "peachpuff" => Self::peach_puff(),
+            "peru" => Self::peru(),
+            "pink" => Self::pink(),
+            "plum" => Self::plum(),
+            "powderblue" => Self::powder_blue(),
+            "purple" => Self::purple(),
+            "red" => Self::red(),
+            "rosybrown" => Self::rosy_brown(),
+            "royalblue" => Self::royal_blue(),
+            "saddlebrown" => Self::saddle_brown(),
+            "salmon" => Self::salmon(),
+            "sandybrown" => Self::sandy_brown(),
+            "seagreen" => Self::sea_green(),
+            "seashell" => Self::sea_shell(),
+            "sienna" => Self::sienna(),
+            "silver" => Self::silver(),
+            "skyblue" => Self::sky_blue(),
+            "slateblue" => Self::slate_blue(),
+            "slategray" => Self::slate_gray(),
+            "snow" => Self::snow(),
+            "springgreen" => Self::spring_green(),
+            "steelblue" => Self::steel_blue(),
+            "tan" => Self::tan(),
+            "teal" => Self::teal(),
+            "thistle" => Self::thistle(),
+            "tomato" => Self::tomato(),
+            "transparent" => Self::transparent(),
+            "turquoise" => Self::turquoise(),
+            "violet" => Self::violet(),
+            "wheat" => Self::wheat(),
+            "white" => Self::white(),
+            "whitesmoke" => Self::white_smoke(),
+            "yellow" => Self::yellow(),
+            "yellowgreen" => Self::yellow_green(),
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    /// Formats the color as a CSS-style `#RRGGBBAA` hex string. Components are
+    /// clamped and rounded to the nearest byte value.
+    pub fn to_hex(&self) -> String {
+        let [r, g, b, a] = self.to_bytes();
+        format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+    }
+
+    /// Returns the color as byte values (0 - 255) in `[r, g, b, a]` order.
+    /// Components are clamped and rounded to the nearest byte value.
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [
+            byte(self.red),
+            byte(self.green),
+            byte(self.blue),
+            byte(self.alpha),
+        ]
+    }
+
+    /// Returns a copy of the color with the alpha component replaced.
+    pub fn with_alpha(self, alpha: f32) -> Self {
+        Self { alpha, ..self }
+    }
+
+    /// Linearly interpolates between `self` and `other`. A `t` of 0.0 returns
+    /// `self`, and a `t` of 1.0 returns `other`. Values outside of that range
+    /// are clamped.
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Self {
+            red: mix(self.red, other.red),
+            green: mix(self.green, other.green),
+            blue: mix(self.blue, other.blue),
+            alpha: mix(self.alpha, other.alpha),
+        }
+    }
+
+    /// Mixes the color towards white by `amount` (0.0 - 1.0), preserving
+    /// alpha.
+    pub fn lighten(self, amount: f32) -> Self {
+        self.lerp(Color::white().with_alpha(self.alpha), amount)
+    }
+
+    /// Mixes the color towards black by `amount` (0.0 - 1.0), preserving
+    /// alpha.
+    pub fn darken(self, amount: f32) -> Self {
+        self.lerp(Color::black().with_alpha(self.alpha), amount)
+    }
+
     /// AliceBlue predefined color from the Microsoft UI core library.
     pub fn alice_blue() -> Color {
         Color::new_argb(0xFFF0F8FF)
@@ -681,6 +920,28 @@ impl Color {
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde {
+    use super::Color;
+    use ::serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Colors are serialized as CSS-style `#RRGGBBAA` hex strings, which are
+    /// far friendlier to hand-edit than four normalized floats.
+    impl Serialize for Color {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.to_hex())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Color {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let hex = String::deserialize(deserializer)?;
+            Color::from_hex(&hex)
+                .ok_or_else(|| D::Error::custom(format!("invalid hex color: {hex:?}")))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -733,4 +994,25 @@ mod tests {
         assert!(!Color::light_pink().is_dark());
         assert!(Color::light_pink().is_light());
     }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let color = Color::new(0x12, 0xAB, 0xEF, 0x80);
+        assert_eq!(color.to_hex(), "#12ABEF80");
+        assert_eq!(Color::from_hex(&color.to_hex()), Some(color));
+
+        assert_eq!(Color::from_hex("#FFFFFF"), Some(Color::white()));
+        assert_eq!(Color::from_hex("#GGGGGG"), None);
+        assert_eq!(Color::from_hex(""), None);
+    }
+
+    #[test]
+    fn test_lighten_and_darken() {
+        let color = Color::new_normalized(0.5, 0.5, 0.5, 0.5);
+
+        assert_eq!(color.lighten(1.0), Color::white().with_alpha(0.5));
+        assert_eq!(color.darken(1.0), Color::black().with_alpha(0.5));
+        assert_eq!(color.lighten(0.0), color);
+        assert_eq!(color.darken(0.5).red, 0.25);
+    }
 }
@@ -1,18 +1,76 @@
 //! Graphics context which is used for all concrete drawing operations within a
 //! draw cycle.
 
-use ::std::rc::Rc;
-use ::win_geom::d2::{Ellipse2D, Point2D, Rect2D, RoundedRect2D};
-use ::windows::{core::InParam, Win32::Graphics::Direct2D::ID2D1HwndRenderTarget};
+use ::std::{
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    time::Instant,
+};
+use ::win32::{invoke::check_res, window::DPI};
+use ::win_geom::d2::{Ellipse2D, Point2D, Rect2D, RoundedRect2D, Transform2D};
+use ::windows::core::Interface;
+use ::windows::Foundation::Numerics::Matrix3x2;
+use ::windows::Win32::Graphics::{
+    Direct2D::{
+        ID2D1Bitmap, ID2D1Brush, ID2D1DeviceContext, ID2D1DeviceContext3, ID2D1RenderTarget,
+        D2D1_ANTIALIAS_MODE_ALIASED, D2D1_DRAW_TEXT_OPTIONS_NONE, D2D1_LAYER_OPTIONS_NONE,
+        D2D1_LAYER_PARAMETERS, D2D1_SPRITE_OPTIONS_NONE,
+    },
+    DirectWrite::DWRITE_MEASURING_MODE_NATURAL,
+};
 
 use crate::{
-    brushes::{Brush, SolidColorBrush},
+    bitmaps::{Bitmap, InterpolationMode},
+    brushes::Paint,
+    errors::{Error, Result},
+    geometry::PathGeometry,
+    imaging::Image,
+    layer::{AntialiasMode, LayerParameters},
+    options::{PrimitiveBlend, RenderingMode},
+    snap::{PixelSnap, SnapMode},
+    sprites::SpriteBatch,
+    stats::{DrawCalls, FrameRecorder, FrameStats},
+    stroke::{stroke_style_param, StrokeStyle, StrokeStyleProperties},
+    target::Surface,
+    text::{TextFormat, TextLayout},
     Color, DeviceResource, RenderTarget,
 };
 
 /// Drawing context for performing batched operations on an underlying render
 /// target. Drawing may _only_ be performed via a `Context`.
 ///
+/// All drawing methods accept any [`Brush`](crate::brushes::Brush), including
+/// trait objects (`&mut dyn Brush`). Brushes are transparently re-created if
+/// the render target was lost since they were last used. They equally accept a
+/// plain [`Color`] or a [`BrushSpec`](crate::BrushSpec), whose brush is created
+/// once and then shared from the render target's
+/// [`ResourceCache`](crate::cache::ResourceCache). See [`Paint`].
+///
+/// All stroke methods accept an optional [`StrokeStyle`]. Passing `None` draws
+/// a solid line with flat caps and mitered joins.
+///
+/// All drawing is subject to the context's current world transform, which is
+/// managed as a stack with [`push_transform`](Self::push_transform) and
+/// [`pop_transform`](Self::pop_transform). Each frame starts with the identity
+/// transform.
+///
+/// Each frame begins with the render target's
+/// [`RenderingMode`](crate::RenderingMode), which can be overridden for part
+/// of the frame with [`scoped_rendering_mode`](Self::scoped_rendering_mode),
+/// e.g. to draw pixel-art without antialiasing.
+///
+/// Lines and rectangles may be snapped to device pixels so that they are drawn
+/// crisply, by opting in with [`set_snap_mode`](Self::set_snap_mode).
+///
+/// Text is drawn with [`draw_text`](Self::draw_text) and
+/// [`draw_text_layout`](Self::draw_text_layout), in the
+/// [`TextFormat`](crate::TextFormat)s and [`TextLayout`](crate::TextLayout)s
+/// made by a [`DWriteFactory`](crate::DWriteFactory).
+///
+/// Clips and layers are pushed with [`push_clip_rect`](Self::push_clip_rect)
+/// and [`push_layer`](Self::push_layer), which return guards that pop them
+/// again when dropped.
+///
 /// # Example
 ///
 /// ```no_run
@@ -21,95 +79,524 @@ use crate::{
 /// use ::win_geom::d2::{Size2D, Rect2D, Point2D};
 /// use ::d2d::Color;
 ///
-/// # let factory = D2DFactory::new().unwrap();
+/// # let factory = D2DFactory::new()?;
 /// # let mut render_target = factory.make_render_target(
 /// #     HWND(0),
 /// #     Size2D { width: 100, height: 100 },
 /// # );
-/// # let mut brush = render_target.make_solid_color_brush(Color::red());
+/// # let mut brush = render_target.make_solid_color_brush(Color::red())?;
 /// # let stroke_width = 1.0;
-/// let mut ctx = render_target.begin_draw();
+/// let mut ctx = render_target.begin_draw()?;
 /// ctx.clear(Color::blue());
 /// let rect = Rect2D::from_size_and_origin(
 ///     Size2D { width: 5.0, height: 5.0 },
 ///     Point2D { x: 10.0, y: 10.0 },
 /// );
-/// ctx.stroke_rect(rect, &mut brush, 1.0);
-/// ctx.end_draw();
+/// ctx.stroke_rect(rect, &mut brush, 1.0, None);
+/// ctx.fill_rect(rect, Color::green());
+/// ctx.end_draw()?;
+/// # Ok::<(), ::d2d::Error>(())
 /// ```
 pub struct Context<'t> {
     /// Exclusive reference to the [`RenderTarget`] into which this context is
     /// drawing.
     render_target: &'t mut RenderTarget,
-    /// Cached reference to a created and usable HWND hardware render target.
-    device_target: Rc<ID2D1HwndRenderTarget>,
+    /// Cached reference to a created and usable device render target, either
+    /// that of the window or of an offscreen target.
+    device_target: Rc<ID2D1RenderTarget>,
+    /// The surface being drawn into.
+    surface: Surface<'t>,
+    /// The world transforms pushed during this frame.
+    transforms: TransformStack,
+    /// The clips and layers currently pushed, innermost last.
+    scopes: Vec<Scope>,
+    /// The first error encountered while drawing, which is reported by
+    /// [`end_draw`](Self::end_draw).
+    error: Option<Error>,
+    /// Gathers the statistics of this frame.
+    frame: FrameRecorder,
+    /// How lines and rectangles are snapped to device pixels.
+    snap_mode: SnapMode,
+    /// Snaps to the device pixels of the surface being drawn into.
+    snap: PixelSnap,
+    /// How drawing is currently antialiased and blended.
+    rendering_mode: RenderingMode,
 }
 
 impl<'t> Context<'t> {
     /// Construct a new [Context] for batching draw calls for the current frame.
     pub(crate) fn new(
-        device_target: Rc<ID2D1HwndRenderTarget>,
+        device_target: Rc<ID2D1RenderTarget>,
         render_target: &'t mut RenderTarget,
+        surface: Surface<'t>,
+        frame: FrameRecorder,
     ) -> Self {
-        Self {
+        let (mut dpi_x, mut dpi_y) = (0.0, 0.0);
+        unsafe {
+            device_target.GetDpi(&mut dpi_x, &mut dpi_y);
+        }
+
+        let rendering_mode = render_target.options().rendering_mode();
+        let mut ctx = Self {
             device_target,
             render_target,
+            surface,
+            transforms: TransformStack::default(),
+            scopes: Vec::new(),
+            error: None,
+            frame,
+            snap_mode: SnapMode::Off,
+            snap: PixelSnap::new(DPI::from(dpi_x)),
+            rendering_mode,
+        };
+        // Direct2D keeps the rendering mode between frames, so each frame
+        // starts afresh.
+        ctx.apply_rendering_mode();
+        ctx
+    }
+
+    /// The render target into which this context is drawing, e.g. for
+    /// creating resources mid-frame.
+    pub(crate) fn render_target(&mut self) -> &mut RenderTarget {
+        self.render_target
+    }
+
+    /// Re-creates `resource` if the device was lost since it was created. On
+    /// failure the error is kept for [`end_draw`](Self::end_draw) to report and
+    /// `false` is returned, so that the drawing call using it can be skipped.
+    fn prepare<R: DeviceResource + ?Sized>(&mut self, resource: &mut R) -> bool {
+        match resource.recreate_if_needed(self.render_target) {
+            Ok(()) => true,
+            Err(e) => {
+                self.error.get_or_insert(e);
+                false
+            }
+        }
+    }
+
+    /// Realizes the device brush with which to `paint`. On failure the error
+    /// is kept for [`end_draw`](Self::end_draw) to report and `None` is
+    /// returned, so that the drawing call using it can be skipped.
+    fn paint(&mut self, paint: impl Paint) -> Option<ID2D1Brush> {
+        paint
+            .realize(self.render_target)
+            .map_err(|e| self.error.get_or_insert(e))
+            .ok()
+    }
+
+    /// Realizes the brush for a drawing call of the `primitive` counted in the
+    /// frame's statistics, as for [`paint`](Self::paint).
+    fn draw_call(
+        &mut self,
+        paint: impl Paint,
+        primitive: impl FnOnce(&mut DrawCalls) -> &mut usize,
+    ) -> Option<ID2D1Brush> {
+        let brush = self.paint(paint)?;
+        self.frame.use_brush(&brush);
+        *primitive(self.frame.draw_calls()) += 1;
+        Some(brush)
+    }
+
+    /// The snapper for drawing under the current world transform, if snapping
+    /// is on. Snapping is skipped unless the transform is a plain translation,
+    /// as lines and rectangles cannot otherwise be kept on device pixels.
+    fn snap(&self) -> Option<PixelSnap> {
+        let t = self.transforms.current();
+        let translation = t.m11 == 1.0 && t.m12 == 0.0 && t.m21 == 0.0 && t.m22 == 1.0;
+        (self.snap_mode != SnapMode::Off && translation)
+            .then(|| self.snap.with_offset(Point2D { x: t.dx, y: t.dy }))
+    }
+
+    /// The width with which to draw a stroke of `stroke_width` snapped by
+    /// `snap`.
+    fn snap_width(&self, snap: &PixelSnap, stroke_width: f32) -> f32 {
+        match self.snap_mode {
+            SnapMode::PositionsAndWidths => snap.stroke_width(stroke_width),
+            _ => stroke_width,
+        }
+    }
+
+    /// Snaps a `rect` outlined with `stroke_width`, if snapping is on,
+    /// returning the rectangle and width to draw.
+    fn snap_stroked(&self, rect: Rect2D<f32>, stroke_width: f32) -> (Rect2D<f32>, f32) {
+        match self.snap() {
+            Some(snap) => {
+                let stroke_width = self.snap_width(&snap, stroke_width);
+                (snap.stroked_rect(rect, stroke_width), stroke_width)
+            }
+            None => (rect, stroke_width),
+        }
+    }
+
+    /// How lines and rectangles are snapped to device pixels. Snapping is
+    /// [`Off`](SnapMode::Off) unless set with
+    /// [`set_snap_mode`](Self::set_snap_mode).
+    pub fn snap_mode(&self) -> SnapMode {
+        self.snap_mode
+    }
+
+    /// Snaps subsequent lines, rectangles and rounded rectangles to device
+    /// pixels according to `mode`, for the rest of the frame. See
+    /// [`SnapMode`].
+    pub fn set_snap_mode(&mut self, mode: SnapMode) {
+        self.snap_mode = mode;
+    }
+
+    /// How drawing is currently antialiased and blended.
+    pub fn rendering_mode(&self) -> RenderingMode {
+        self.rendering_mode
+    }
+
+    /// Changes how subsequent drawing is antialiased and blended, for the rest
+    /// of the frame. Consider
+    /// [`scoped_rendering_mode`](Self::scoped_rendering_mode), which restores
+    /// the previous mode automatically.
+    ///
+    /// A [`PrimitiveBlend`] other than
+    /// [`SourceOver`](PrimitiveBlend::SourceOver) requires Direct2D 1.1. If
+    /// it is unavailable, a warning is logged and drawing falls back to
+    /// `SourceOver`, as reported by [`rendering_mode`](Self::rendering_mode).
+    pub fn set_rendering_mode(&mut self, mode: RenderingMode) {
+        self.rendering_mode = mode;
+        self.apply_rendering_mode();
+    }
+
+    /// Changes the rendering mode for the lifetime of the returned guard,
+    /// which restores the previous mode when dropped. The guard dereferences
+    /// to the [`Context`] so that drawing can continue through it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ::windows::Win32::Foundation::HWND;
+    /// # use ::d2d::{Color, D2DFactory};
+    /// use ::d2d::{AntialiasMode, RenderingMode};
+    /// use ::win_geom::d2::{Point2D, Rect2D, Size2D};
+    ///
+    /// # let factory = D2DFactory::new()?;
+    /// # let mut render_target = factory.make_render_target(
+    /// #     HWND(0),
+    /// #     Size2D { width: 100, height: 100 },
+    /// # );
+    /// let mut ctx = render_target.begin_draw()?;
+    /// {
+    ///     let mode = RenderingMode {
+    ///         antialias: AntialiasMode::Aliased,
+    ///         ..ctx.rendering_mode()
+    ///     };
+    ///     let mut pixel_art = ctx.scoped_rendering_mode(mode);
+    ///     let pixel = Rect2D::from_size_and_origin(
+    ///         Size2D { width: 1.0, height: 1.0 },
+    ///         Point2D { x: 4.0, y: 4.0 },
+    ///     );
+    ///     pixel_art.fill_rect(pixel, Color::green());
+    /// }
+    /// ctx.end_draw()?;
+    /// # Ok::<(), ::d2d::Error>(())
+    /// ```
+    pub fn scoped_rendering_mode(&mut self, mode: RenderingMode) -> RenderingModeGuard<'_, 't> {
+        let previous = self.rendering_mode;
+        self.set_rendering_mode(mode);
+        RenderingModeGuard {
+            ctx: self,
+            previous,
+        }
+    }
+
+    /// Sends the current rendering mode to the device render target.
+    fn apply_rendering_mode(&mut self) {
+        let mode = self.rendering_mode;
+        unsafe {
+            self.device_target.SetAntialiasMode(mode.antialias.into());
+            self.device_target
+                .SetTextAntialiasMode(mode.text_antialias.into());
+        }
+
+        // Primitive blending is only offered by Direct2D 1.1 device contexts.
+        match check_res(
+            || self.device_target.cast::<ID2D1DeviceContext>(),
+            "QueryInterface",
+        ) {
+            Ok(device_context) => unsafe {
+                device_context.SetPrimitiveBlend(mode.primitive_blend.into());
+            },
+            Err(e) if mode.primitive_blend != PrimitiveBlend::SourceOver => {
+                ::tracing::warn!(
+                    error = %e,
+                    "Primitive blending requires Direct2D 1.1, falling back to SourceOver"
+                );
+                self.rendering_mode.primitive_blend = PrimitiveBlend::SourceOver;
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Labels subsequent drawing calls with a pair of application-defined
+    /// tags. If one of those calls fails, the tags are reported in
+    /// [`Error::Draw`] by [`end_draw`](Self::end_draw) to help identify it.
+    pub fn set_tags(&mut self, tag1: u64, tag2: u64) {
+        unsafe {
+            self.device_target.SetTags(tag1, tag2);
+        }
+    }
+
+    /// The current world transform, which is applied to all drawing.
+    pub fn transform(&self) -> Transform2D {
+        self.transforms.current()
+    }
+
+    /// Replaces the current world transform. The replacement only lasts until
+    /// the enclosing [`pop_transform`](Self::pop_transform), if any.
+    pub fn set_transform(&mut self, transform: Transform2D) {
+        self.transforms.set(transform);
+        self.apply_transform();
+    }
+
+    /// Saves the current world transform and then prepends `transform` to it,
+    /// so that `transform` is applied in the local coordinate space of the
+    /// current transform. For example, pushing a camera transform followed by a
+    /// sprite's rotation rotates the sprite within the camera's view.
+    ///
+    /// Every push must be balanced by a [`pop_transform`](Self::pop_transform)
+    /// before [`end_draw`](Self::end_draw). Consider
+    /// [`scoped_transform`](Self::scoped_transform), which pops automatically.
+    pub fn push_transform(&mut self, transform: Transform2D) {
+        self.transforms.push(transform);
+        self.apply_transform();
+    }
+
+    /// Restores the world transform saved by the matching
+    /// [`push_transform`](Self::push_transform).
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if there is no matching push. In release builds
+    /// an unbalanced pop resets to the identity transform.
+    pub fn pop_transform(&mut self) {
+        let balanced = self.transforms.pop();
+        debug_assert!(
+            balanced,
+            "pop_transform called without a matching push_transform"
+        );
+        self.apply_transform();
+    }
+
+    /// Pushes `transform` for the lifetime of the returned guard, which pops it
+    /// again when dropped. The guard dereferences to the [`Context`] so that
+    /// drawing can continue through it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ::windows::Win32::Foundation::HWND;
+    /// # use ::d2d::{Color, D2DFactory};
+    /// use ::win_geom::d2::{Ellipse2D, Point2D, Size2D, Transform2D};
+    ///
+    /// # let factory = D2DFactory::new()?;
+    /// # let mut render_target = factory.make_render_target(
+    /// #     HWND(0),
+    /// #     Size2D { width: 100, height: 100 },
+    /// # );
+    /// # let mut brush = render_target.make_solid_color_brush(Color::red())?;
+    /// let mut ctx = render_target.begin_draw()?;
+    /// {
+    ///     let mut zoomed = ctx.scoped_transform(Transform2D::scale(2.0, 2.0));
+    ///     zoomed.fill_ellipse(Ellipse2D::new_circle(Point2D { x: 10.0, y: 10.0 }, 5.0), &mut brush);
+    /// }
+    /// ctx.end_draw()?;
+    /// # Ok::<(), ::d2d::Error>(())
+    /// ```
+    pub fn scoped_transform(&mut self, transform: Transform2D) -> TransformGuard<'_, 't> {
+        self.push_transform(transform);
+        TransformGuard { ctx: self }
+    }
+
+    /// Restricts all drawing to `rect` until the returned guard is dropped.
+    ///
+    /// The clip rectangle is transformed by the current world transform. If
+    /// the transform rotates or skews, the clip is the bounding box of the
+    /// transformed rectangle. Use [`push_layer`](Self::push_layer) with a
+    /// geometric mask for non-rectangular clips.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ::windows::Win32::Foundation::HWND;
+    /// # use ::d2d::{AntialiasMode, Color, D2DFactory};
+    /// use ::win_geom::d2::{Point2D, Rect2D, Size2D, Transform2D};
+    ///
+    /// # let factory = D2DFactory::new()?;
+    /// # let mut render_target = factory.make_render_target(
+    /// #     HWND(0),
+    /// #     Size2D { width: 100, height: 100 },
+    /// # );
+    /// # let mut brush = render_target.make_solid_color_brush(Color::red())?;
+    /// # let scroll_offset = 0.0;
+    /// let viewport = Rect2D { left: 10.0, top: 10.0, right: 90.0, bottom: 90.0 };
+    ///
+    /// let mut ctx = render_target.begin_draw()?;
+    /// {
+    ///     // A scrolling panel: content is clipped to the viewport.
+    ///     let mut panel = ctx.push_clip_rect(viewport, AntialiasMode::Aliased);
+    ///     let mut content = panel.scoped_transform(Transform2D::translation(0.0, -scroll_offset));
+    ///     content.fill_rect(Rect2D { left: 0.0, top: 0.0, right: 100.0, bottom: 400.0 }, &mut brush);
+    /// }
+    /// ctx.end_draw()?;
+    /// # Ok::<(), ::d2d::Error>(())
+    /// ```
+    pub fn push_clip_rect(
+        &mut self,
+        rect: Rect2D<f32>,
+        antialias: AntialiasMode,
+    ) -> ClipGuard<'_, 't> {
+        unsafe {
+            self.device_target
+                .PushAxisAlignedClip(&rect.into() as _, antialias.into());
+        }
+        self.scopes.push(Scope::Clip);
+
+        ClipGuard { ctx: self }
+    }
+
+    /// Redirects all drawing into a layer until the returned guard is dropped.
+    /// The layer's content is then composited back onto the render target,
+    /// clipped to the layer's bounds and mask and faded by its opacity.
+    pub fn push_layer(&mut self, params: LayerParameters<'_>) -> LayerGuard<'_, 't> {
+        let LayerParameters {
+            content_bounds,
+            mask,
+            mask_antialias,
+            mask_transform,
+            opacity,
+            opacity_brush,
+        } = params;
+
+        let opacity_brush = opacity_brush.and_then(|brush| self.paint(brush));
+        let device_params = D2D1_LAYER_PARAMETERS {
+            contentBounds: content_bounds
+                .unwrap_or(Rect2D {
+                    left: -f32::MAX,
+                    top: -f32::MAX,
+                    right: f32::MAX,
+                    bottom: f32::MAX,
+                })
+                .into(),
+            geometricMask: mask.map(|mask| mask.device_geometry().clone()),
+            maskAntialiasMode: mask_antialias.into(),
+            maskTransform: Matrix3x2::from(mask_transform),
+            opacity,
+            opacityBrush: opacity_brush,
+            layerOptions: D2D1_LAYER_OPTIONS_NONE,
+        };
+
+        match check_res(
+            || unsafe { self.device_target.CreateLayer(None) },
+            "CreateLayer",
+        ) {
+            Ok(layer) => {
+                unsafe {
+                    self.device_target.PushLayer(&device_params as _, &layer);
+                }
+                self.scopes.push(Scope::Layer);
+            }
+            Err(e) => {
+                self.error.get_or_insert(e.into());
+                self.scopes.push(Scope::Failed);
+            }
+        }
+
+        LayerGuard { ctx: self }
+    }
+
+    /// Pops the innermost clip or layer.
+    fn pop_scope(&mut self) {
+        match self.scopes.pop() {
+            Some(Scope::Clip) => unsafe { self.device_target.PopAxisAlignedClip() },
+            Some(Scope::Layer) => unsafe { self.device_target.PopLayer() },
+            Some(Scope::Failed) => {}
+            None => debug_assert!(false, "no clip or layer to pop"),
+        }
+    }
+
+    /// Sends the current world transform to the device render target.
+    fn apply_transform(&self) {
+        unsafe {
+            self.device_target
+                .SetTransform(&Matrix3x2::from(self.transforms.current()) as _);
         }
     }
 
     /// Clears the entire screen by filling with `color`.
-    pub fn clear(&self, color: Color) {
+    pub fn clear(&mut self, color: Color) {
+        self.frame.draw_calls().clears += 1;
         unsafe {
             self.device_target.Clear(Some(&color.into() as _));
         }
     }
 
-    /// Draws a line between the specified points using a solid stroke of width
+    /// Draws a line between the specified points using a stroke of width
     /// `stroke_width`.
     pub fn draw_line(
         &mut self,
         p0: Point2D<f32>,
         p1: Point2D<f32>,
         stroke_width: f32,
-        brush: &mut SolidColorBrush,
+        brush: impl Paint,
+        style: Option<&StrokeStyle>,
     ) {
-        brush.recreate_if_needed(self.render_target);
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.lines) else {
+            return;
+        };
+        let (p0, p1, stroke_width) = match self.snap() {
+            Some(snap) => {
+                let stroke_width = self.snap_width(&snap, stroke_width);
+                let (p0, p1) = snap.line(p0, p1, stroke_width);
+                (p0, p1, stroke_width)
+            }
+            None => (p0, p1, stroke_width),
+        };
 
         unsafe {
             self.device_target.DrawLine(
                 p0.into(),
                 p1.into(),
-                brush.device_brush(),
+                &brush,
                 stroke_width,
-                InParam::null(),
+                stroke_style_param(style),
             );
         }
     }
 
     /// Paints the interior of the specified rectangle.
-    pub fn fill_rect(&mut self, rect: Rect2D<f32>, brush: &mut SolidColorBrush) {
-        brush.recreate_if_needed(self.render_target);
+    pub fn fill_rect(&mut self, rect: Rect2D<f32>, brush: impl Paint) {
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.rects) else {
+            return;
+        };
+        let rect = self.snap().map_or(rect, |snap| snap.rect(rect));
         unsafe {
-            self.device_target
-                .FillRectangle(&rect.into() as _, brush.device_brush());
+            self.device_target.FillRectangle(&rect.into() as _, &brush);
         }
     }
 
     /// Draws the outline of a rectangle that has the specified dimensions with
-    /// a solid color stroke.
+    /// the given brush.
     pub fn stroke_rect(
         &mut self,
         rect: Rect2D<f32>,
-        brush: &mut SolidColorBrush,
+        brush: impl Paint,
         stroke_width: f32,
+        style: Option<&StrokeStyle>,
     ) {
-        brush.recreate_if_needed(self.render_target);
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.rects) else {
+            return;
+        };
+        let (rect, stroke_width) = self.snap_stroked(rect, stroke_width);
         unsafe {
             self.device_target.DrawRectangle(
                 &rect.into() as _,
-                brush.device_brush(),
+                &brush,
                 stroke_width,
-                InParam::null(),
+                stroke_style_param(style),
             );
         }
     }
@@ -123,16 +610,25 @@ impl<'t> Context<'t> {
     ///
     /// [`radius_x`]: RoundedRect2D.radius_x
     /// [`radius_y`]: RoundedRect2D.radius_y
-    pub fn fill_rounded_rect(&mut self, rect: RoundedRect2D<f32>, brush: &mut SolidColorBrush) {
-        brush.recreate_if_needed(self.render_target);
+    pub fn fill_rounded_rect(&mut self, rect: RoundedRect2D<f32>, brush: impl Paint) {
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.rounded_rects) else {
+            return;
+        };
+        let rect = match self.snap() {
+            Some(snap) => RoundedRect2D {
+                rect: snap.rect(rect.rect),
+                ..rect
+            },
+            None => rect,
+        };
         unsafe {
             self.device_target
-                .FillRoundedRectangle(&rect.into() as _, brush.device_brush());
+                .FillRoundedRectangle(&rect.into() as _, &brush);
         }
     }
 
     /// Draws the outline of a rounded rectangle that has the specified
-    /// dimensions with a solid color stroke.
+    /// dimensions with the given brush.
     ///
     /// Even when both [`radius_x`] and [`radius_y`] are zero, a
     /// [`RoundedRect2D`] is different from a [`Rect2D`]. When stroked, the
@@ -144,57 +640,556 @@ impl<'t> Context<'t> {
     pub fn stroke_rounded_rect(
         &mut self,
         rect: RoundedRect2D<f32>,
-        brush: &mut SolidColorBrush,
+        brush: impl Paint,
         stroke_width: f32,
+        style: Option<&StrokeStyle>,
     ) {
-        brush.recreate_if_needed(self.render_target);
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.rounded_rects) else {
+            return;
+        };
+        let (snapped, stroke_width) = self.snap_stroked(rect.rect, stroke_width);
+        let rect = RoundedRect2D {
+            rect: snapped,
+            ..rect
+        };
         unsafe {
             self.device_target.DrawRoundedRectangle(
                 &rect.into() as _,
-                brush.device_brush(),
+                &brush,
                 stroke_width,
-                InParam::null(),
+                stroke_style_param(style),
             );
         }
     }
 
     /// Paints the interior of the specified ellipse.
-    pub fn fill_ellipse(&mut self, ellipse: Ellipse2D<f32>, brush: &mut SolidColorBrush) {
-        brush.recreate_if_needed(self.render_target);
+    pub fn fill_ellipse(&mut self, ellipse: Ellipse2D<f32>, brush: impl Paint) {
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.ellipses) else {
+            return;
+        };
         unsafe {
-            self.device_target
-                .FillEllipse(&ellipse.into() as _, brush.device_brush());
+            self.device_target.FillEllipse(&ellipse.into() as _, &brush);
         }
     }
 
-    /// Draws the outline of an ellipse that has the specified dimensions with a
-    /// solid color stroke.
+    /// Draws the outline of an ellipse that has the specified dimensions with
+    /// the given brush.
     pub fn stroke_ellipse(
         &mut self,
         ellipse: Ellipse2D<f32>,
-        brush: &mut SolidColorBrush,
+        brush: impl Paint,
         stroke_width: f32,
+        style: Option<&StrokeStyle>,
     ) {
-        brush.recreate_if_needed(self.render_target);
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.ellipses) else {
+            return;
+        };
         unsafe {
             self.device_target.DrawEllipse(
                 &ellipse.into() as _,
-                brush.device_brush(),
+                &brush,
+                stroke_width,
+                stroke_style_param(style),
+            );
+        }
+    }
+
+    /// Paints the interior of the specified geometry.
+    pub fn fill_geometry(&mut self, geometry: &PathGeometry, brush: impl Paint) {
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.geometries) else {
+            return;
+        };
+        unsafe {
+            self.device_target
+                .FillGeometry(geometry.device_geometry(), &brush, None);
+        }
+    }
+
+    /// Draws the outline of the specified geometry with the given brush.
+    pub fn stroke_geometry(
+        &mut self,
+        geometry: &PathGeometry,
+        brush: impl Paint,
+        stroke_width: f32,
+        style: Option<&StrokeStyle>,
+    ) {
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.geometries) else {
+            return;
+        };
+        unsafe {
+            self.device_target.DrawGeometry(
+                geometry.device_geometry(),
+                &brush,
                 stroke_width,
-                InParam::null(),
+                stroke_style_param(style),
             );
         }
     }
 
+    /// Draws `bitmap` scaled into the `dest` rectangle.
+    ///
+    /// * `opacity` - A value between 0.0 and 1.0 which is multiplied against
+    ///   the bitmap's alpha channel.
+    /// * `interpolation` - The sampling used if the bitmap is scaled or
+    ///   rotated.
+    /// * `source` - The region of the bitmap to draw, in pixels. Pass `None` to
+    ///   draw the entire bitmap.
+    pub fn draw_bitmap(
+        &mut self,
+        bitmap: &mut Bitmap,
+        dest: Rect2D<f32>,
+        opacity: f32,
+        interpolation: InterpolationMode,
+        source: Option<Rect2D<f32>>,
+    ) {
+        if !self.prepare(bitmap) {
+            return;
+        }
+        self.draw_device_bitmap(bitmap.device_bitmap(), dest, opacity, interpolation, source);
+    }
+
+    /// Draws every sprite in `batch` from the `atlas` bitmap in a single
+    /// submission. See [`SpriteBatch`].
+    ///
+    /// Sprites are always drawn aliased, regardless of the
+    /// [rendering mode](Self::rendering_mode), as Direct2D requires.
+    pub fn draw_sprites(
+        &mut self,
+        batch: &mut SpriteBatch,
+        atlas: &mut Bitmap,
+        interpolation: InterpolationMode,
+    ) {
+        if batch.is_empty() || !self.prepare(atlas) {
+            return;
+        }
+        self.frame.draw_calls().sprite_batches += 1;
+
+        // Sprite batches are only offered by Direct2D 1.3 device contexts.
+        let Ok(device_context) = self.device_target.cast::<ID2D1DeviceContext3>() else {
+            self.draw_sprites_individually(batch, atlas, interpolation);
+            return;
+        };
+        let device_batch = match batch.device_batch(&device_context, self.render_target) {
+            Ok(device_batch) => device_batch,
+            Err(e) => {
+                self.error.get_or_insert(e);
+                return;
+            }
+        };
+
+        unsafe {
+            self.device_target
+                .SetAntialiasMode(D2D1_ANTIALIAS_MODE_ALIASED);
+            device_context.DrawSpriteBatch(
+                &device_batch,
+                0,
+                batch.len() as u32,
+                atlas.device_bitmap(),
+                interpolation.into(),
+                D2D1_SPRITE_OPTIONS_NONE,
+            );
+            self.device_target
+                .SetAntialiasMode(self.rendering_mode.antialias.into());
+        }
+    }
+
+    /// Draws each sprite of `batch` in turn, for devices without sprite
+    /// batches. Tints are approximated by their alpha.
+    fn draw_sprites_individually(
+        &mut self,
+        batch: &SpriteBatch,
+        atlas: &Bitmap,
+        interpolation: InterpolationMode,
+    ) {
+        let world = self.transforms.current();
+        for sprite in batch.sprites() {
+            let source = sprite.source.cast::<f32>();
+            unsafe {
+                self.device_target
+                    .SetTransform(&Matrix3x2::from(sprite.transform.then(world)) as _);
+                self.device_target.DrawBitmap(
+                    atlas.device_bitmap(),
+                    Some(&sprite.dest.into() as _),
+                    sprite.tint.alpha,
+                    interpolation.into(),
+                    Some(&source.into() as _),
+                );
+            }
+        }
+        self.apply_transform();
+    }
+
+    /// Draws `text` in the font of `format`, laid out within `rect`.
+    ///
+    /// The text is laid out afresh on every call. Text which is drawn every
+    /// frame, or which needs to be measured, is better kept in a
+    /// [`TextLayout`] and drawn with
+    /// [`draw_text_layout`](Self::draw_text_layout).
+    pub fn draw_text(
+        &mut self,
+        text: &str,
+        format: &TextFormat,
+        rect: Rect2D<f32>,
+        brush: impl Paint,
+    ) {
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.text) else {
+            return;
+        };
+        let text = text.encode_utf16().collect::<Vec<_>>();
+        unsafe {
+            self.device_target.DrawText(
+                &text,
+                format.device_format(),
+                &rect.into() as _,
+                &brush,
+                D2D1_DRAW_TEXT_OPTIONS_NONE,
+                DWRITE_MEASURING_MODE_NATURAL,
+            );
+        }
+    }
+
+    /// Draws `layout` with the top-left of its layout box at `origin`.
+    pub fn draw_text_layout(
+        &mut self,
+        origin: Point2D<f32>,
+        layout: &TextLayout,
+        brush: impl Paint,
+    ) {
+        let Some(brush) = self.draw_call(brush, |calls| &mut calls.text) else {
+            return;
+        };
+        unsafe {
+            self.device_target.DrawTextLayout(
+                origin.into(),
+                layout.device_layout(),
+                &brush,
+                D2D1_DRAW_TEXT_OPTIONS_NONE,
+            );
+        }
+    }
+
+    /// Draws `image` scaled into the `dest` rectangle, exactly as
+    /// [`draw_bitmap`](Self::draw_bitmap) but with a bitmap fetched from the
+    /// render target's [`ResourceCache`](crate::cache::ResourceCache) rather
+    /// than one created by hand.
+    pub fn draw_image(
+        &mut self,
+        image: &Rc<Image>,
+        dest: Rect2D<f32>,
+        opacity: f32,
+        interpolation: InterpolationMode,
+        source: Option<Rect2D<f32>>,
+    ) {
+        match self.render_target.cached_bitmap(image) {
+            Ok(bitmap) => self.draw_device_bitmap(&bitmap, dest, opacity, interpolation, source),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+    }
+
+    /// Draws a device bitmap for [`draw_bitmap`](Self::draw_bitmap) and
+    /// [`draw_image`](Self::draw_image).
+    fn draw_device_bitmap(
+        &mut self,
+        bitmap: &ID2D1Bitmap,
+        dest: Rect2D<f32>,
+        opacity: f32,
+        interpolation: InterpolationMode,
+        source: Option<Rect2D<f32>>,
+    ) {
+        self.frame.draw_calls().bitmaps += 1;
+        let source = source.map(Into::into);
+        unsafe {
+            self.device_target.DrawBitmap(
+                bitmap,
+                Some(&dest.into() as _),
+                opacity,
+                interpolation.into(),
+                source.as_ref().map(|r| r as _),
+            );
+        }
+    }
+
+    /// The stroke style described by `props`, fetched from the render target's
+    /// [`ResourceCache`](crate::cache::ResourceCache) so that it is only
+    /// created once.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the stroke style could not be created.
+    pub fn stroke_style(&mut self, props: &StrokeStyleProperties) -> Result<StrokeStyle> {
+        self.render_target.cached_stroke_style(props)
+    }
+
     /// Ends drawing operations on the render target causing the changes to
     /// become visible and the render target to become ready for the next
-    /// [`begin_draw`](RenderTarget::begin_draw) call.
-    pub fn end_draw(self) {
+    /// [`begin_draw`](RenderTarget::begin_draw) or
+    /// [`begin_draw_to`](RenderTarget::begin_draw_to) call.
+    ///
+    /// Returns the [`FrameStats`] gathered while drawing, which are also
+    /// emitted as a tracing event.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered while drawing, such as a resource
+    /// which could not be re-created, or [`Error::Draw`] if Direct2D failed to
+    /// draw the batch. Either way the frame was not fully drawn, but the render
+    /// target remains usable and the frame may simply be drawn again. A lost
+    /// device is not an error: it is re-created when next drawn.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if a [`push_transform`](Self::push_transform)
+    /// was not balanced by a [`pop_transform`](Self::pop_transform), or if a
+    /// clip or layer guard was leaked.
+    pub fn end_draw(mut self) -> Result<FrameStats> {
+        debug_assert!(
+            self.scopes.is_empty(),
+            "clip or layer guard leaked without being dropped"
+        );
+        // Direct2D fails the whole frame if pushes and pops are unbalanced.
+        while !self.scopes.is_empty() {
+            self.pop_scope();
+        }
+
         let Self {
             render_target,
             device_target,
+            surface,
+            transforms,
+            error,
+            frame,
+            ..
         } = self;
 
-        render_target.end_draw(device_target);
+        debug_assert_eq!(
+            transforms.depth(),
+            0,
+            "push_transform called without a matching pop_transform"
+        );
+        if !transforms.current().is_identity() {
+            // The device target retains its transform between frames, whereas
+            // every new context starts with the identity.
+            unsafe {
+                device_target.SetTransform(&Matrix3x2::from(Transform2D::identity()) as _);
+            }
+        }
+
+        let flushing = Instant::now();
+        let ended = ::tracing::trace_span!("end_draw").in_scope(|| match surface {
+            Surface::Window => render_target.end_draw(device_target),
+            Surface::Bitmap(target) => render_target.end_draw_to(target, device_target),
+        });
+        let stats = frame.finish(render_target.counters(), flushing.elapsed());
+
+        match error {
+            Some(e) => Err(e),
+            None => ended.map(|()| stats),
+        }
+    }
+}
+
+/// A [`Context`] with a temporarily pushed world transform, as returned by
+/// [`Context::scoped_transform`]. The transform is popped when the guard is
+/// dropped.
+pub struct TransformGuard<'c, 't> {
+    ctx: &'c mut Context<'t>,
+}
+
+impl<'c, 't> Deref for TransformGuard<'c, 't> {
+    type Target = Context<'t>;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl<'c, 't> DerefMut for TransformGuard<'c, 't> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ctx
+    }
+}
+
+impl<'c, 't> Drop for TransformGuard<'c, 't> {
+    fn drop(&mut self) {
+        self.ctx.pop_transform();
+    }
+}
+
+/// A [`Context`] with a clip rectangle pushed, as returned by
+/// [`Context::push_clip_rect`]. The clip is popped when the guard is dropped.
+pub struct ClipGuard<'c, 't> {
+    ctx: &'c mut Context<'t>,
+}
+
+impl<'c, 't> Deref for ClipGuard<'c, 't> {
+    type Target = Context<'t>;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl<'c, 't> DerefMut for ClipGuard<'c, 't> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ctx
+    }
+}
+
+impl<'c, 't> Drop for ClipGuard<'c, 't> {
+    fn drop(&mut self) {
+        self.ctx.pop_scope();
+    }
+}
+
+/// A [`Context`] with a temporarily changed rendering mode, as returned by
+/// [`Context::scoped_rendering_mode`]. The previous mode is restored when the
+/// guard is dropped.
+pub struct RenderingModeGuard<'c, 't> {
+    ctx: &'c mut Context<'t>,
+    previous: RenderingMode,
+}
+
+impl<'c, 't> Deref for RenderingModeGuard<'c, 't> {
+    type Target = Context<'t>;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl<'c, 't> DerefMut for RenderingModeGuard<'c, 't> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ctx
+    }
+}
+
+impl<'c, 't> Drop for RenderingModeGuard<'c, 't> {
+    fn drop(&mut self) {
+        self.ctx.set_rendering_mode(self.previous);
+    }
+}
+
+/// A [`Context`] with a layer pushed, as returned by [`Context::push_layer`].
+/// The layer is popped, and its content composited, when the guard is
+/// dropped.
+pub struct LayerGuard<'c, 't> {
+    ctx: &'c mut Context<'t>,
+}
+
+impl<'c, 't> Deref for LayerGuard<'c, 't> {
+    type Target = Context<'t>;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl<'c, 't> DerefMut for LayerGuard<'c, 't> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ctx
+    }
+}
+
+impl<'c, 't> Drop for LayerGuard<'c, 't> {
+    fn drop(&mut self) {
+        self.ctx.pop_scope();
+    }
+}
+
+/// A clip or layer which has been pushed onto the device render target and
+/// must be popped with the matching call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// Pushed with `PushAxisAlignedClip`.
+    Clip,
+    /// Pushed with `PushLayer`.
+    Layer,
+    /// A layer which failed to be pushed, and so has nothing to pop.
+    Failed,
+}
+
+/// A stack of world transforms, where each pushed transform is composed with
+/// the one beneath it.
+#[derive(Debug, Default)]
+pub(crate) struct TransformStack {
+    /// The transform currently in effect.
+    current: Transform2D,
+    /// The transforms to restore on each pop, innermost last.
+    saved: Vec<Transform2D>,
+}
+
+impl TransformStack {
+    /// The transform currently in effect.
+    pub(crate) fn current(&self) -> Transform2D {
+        self.current
+    }
+
+    /// The number of unpopped pushes.
+    pub(crate) fn depth(&self) -> usize {
+        self.saved.len()
+    }
+
+    /// Replaces the transform currently in effect.
+    pub(crate) fn set(&mut self, transform: Transform2D) {
+        self.current = transform;
+    }
+
+    /// Saves the current transform and applies `transform` within it.
+    pub(crate) fn push(&mut self, transform: Transform2D) {
+        self.saved.push(self.current);
+        self.current = transform.then(self.current);
+    }
+
+    /// Restores the most recently saved transform. Returns `false` if there was
+    /// nothing to restore, in which case the identity is restored instead.
+    pub(crate) fn pop(&mut self) -> bool {
+        match self.saved.pop() {
+            Some(transform) => {
+                self.current = transform;
+                true
+            }
+            None => {
+                self.current = Transform2D::identity();
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_push_composes_in_local_space() {
+        let mut stack = TransformStack::default();
+        stack.push(Transform2D::translation(100.0, 0.0));
+        stack.push(Transform2D::scale(2.0, 2.0));
+
+        // The scale is applied first, within the translated space.
+        assert_eq!(
+            stack.current().transform_point(Point2D { x: 1.0, y: 1.0 }),
+            Point2D { x: 102.0, y: 2.0 }
+        );
+        assert_eq!(stack.depth(), 2);
+    }
+
+    #[test]
+    fn test_pop_restores_saved_transform() {
+        let mut stack = TransformStack::default();
+        stack.push(Transform2D::translation(5.0, 5.0));
+        stack.push(Transform2D::scale(3.0, 3.0));
+        stack.set(Transform2D::rotation(1.0));
+
+        assert!(stack.pop());
+        assert_eq!(stack.current(), Transform2D::translation(5.0, 5.0));
+        assert!(stack.pop());
+        assert!(stack.current().is_identity());
+        assert!(!stack.pop());
+        assert_eq!(stack.depth(), 0);
     }
 }
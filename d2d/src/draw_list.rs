@@ -0,0 +1,978 @@
+//! Display lists: recorded sequences of drawing commands which can be
+//! inspected, compared and serialized without Direct2D, and later replayed onto
+//! any render target.
+//!
+//! Commands hold their brushes, stroke styles and paths _by value_ rather than
+//! as device resources, so a [`DrawList`] can be built (and snapshot tested) on
+//! any platform. Device resources are only created when the list is
+//! [replayed](DrawList::replay).
+
+use crate::{
+    bitmaps::InterpolationMode,
+    brushes::{BitmapBrushProperties, Brush},
+    color::Color,
+    context::Context,
+    errors::Result,
+    geometry::PathGeometry,
+    gradient::Gradient,
+    imaging::Image,
+    layer::{AntialiasMode, LayerParameters},
+    path::Path,
+    stroke::{StrokeStyle, StrokeStyleProperties},
+    svg::SvgDocument,
+    target::RenderTarget,
+    D2DFactory,
+};
+use ::std::rc::Rc;
+use ::win_geom::d2::{Ellipse2D, Point2D, Rect2D, RoundedRect2D, Size2D, Transform2D};
+
+/// A brush described by value. Each variant corresponds to one of the device
+/// brushes in [`brushes`](crate::brushes).
+///
+/// Plain colors convert directly into solid brushes, so most recording calls
+/// can simply be passed a [`Color`].
+///
+/// Bitmap brushes are only equal if they share the same [`Image`], as
+/// comparing their pixels would make looking brushes up costly.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum BrushSpec {
+    /// A [`SolidColorBrush`](crate::brushes::SolidColorBrush).
+    Solid(Color),
+    /// A [`LinearGradientBrush`](crate::brushes::LinearGradientBrush).
+    LinearGradient {
+        /// The gradient's stops, gamma and extend mode.
+        gradient: Gradient,
+        /// The start point of the gradient axis.
+        start: Point2D<f32>,
+        /// The end point of the gradient axis.
+        end: Point2D<f32>,
+    },
+    /// A [`RadialGradientBrush`](crate::brushes::RadialGradientBrush).
+    RadialGradient {
+        /// The gradient's stops, gamma and extend mode.
+        gradient: Gradient,
+        /// The ellipse at whose edge the gradient reaches its final stop.
+        ellipse: Ellipse2D<f32>,
+        /// The offset of the gradient origin relative to the ellipse's center.
+        origin_offset: Point2D<f32>,
+    },
+    /// A [`BitmapBrush`](crate::brushes::BitmapBrush).
+    Bitmap {
+        /// The image painted by the brush.
+        image: Rc<Image>,
+        /// How the image is tiled, sampled and positioned.
+        properties: BitmapBrushProperties,
+    },
+}
+
+impl PartialEq for BrushSpec {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Solid(a), Self::Solid(b)) => a == b,
+            (
+                Self::LinearGradient {
+                    gradient,
+                    start,
+                    end,
+                },
+                Self::LinearGradient {
+                    gradient: other_gradient,
+                    start: other_start,
+                    end: other_end,
+                },
+            ) => gradient == other_gradient && start == other_start && end == other_end,
+            (
+                Self::RadialGradient {
+                    gradient,
+                    ellipse,
+                    origin_offset,
+                },
+                Self::RadialGradient {
+                    gradient: other_gradient,
+                    ellipse: other_ellipse,
+                    origin_offset: other_origin_offset,
+                },
+            ) => {
+                gradient == other_gradient
+                    && ellipse == other_ellipse
+                    && origin_offset == other_origin_offset
+            }
+            (
+                Self::Bitmap { image, properties },
+                Self::Bitmap {
+                    image: other_image,
+                    properties: other_properties,
+                },
+            ) => Rc::ptr_eq(image, other_image) && properties == other_properties,
+            _ => false,
+        }
+    }
+}
+
+impl From<Color> for BrushSpec {
+    fn from(color: Color) -> Self {
+        Self::Solid(color)
+    }
+}
+
+/// A layer described by value. The recorded counterpart of
+/// [`LayerParameters`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct LayerSpec {
+    /// The bounds of the layer's content. `None` leaves the layer unbounded.
+    pub content_bounds: Option<Rect2D<f32>>,
+    /// An optional path which masks the layer's content.
+    pub mask: Option<Rc<Path>>,
+    /// How the edges of the mask are rendered.
+    pub mask_antialias: AntialiasMode,
+    /// A transform applied to the mask.
+    pub mask_transform: Transform2D,
+    /// A value between 0.0 and 1.0 which is multiplied against the layer's
+    /// content when it is composited.
+    pub opacity: f32,
+    /// An optional brush whose alpha channel is multiplied against the layer's
+    /// content.
+    pub opacity_brush: Option<BrushSpec>,
+}
+
+impl Default for LayerSpec {
+    fn default() -> Self {
+        Self {
+            content_bounds: None,
+            mask: None,
+            mask_antialias: AntialiasMode::default(),
+            mask_transform: Transform2D::identity(),
+            opacity: 1.0,
+            opacity_brush: None,
+        }
+    }
+}
+
+/// A single recorded drawing operation. Each variant corresponds to the
+/// [`Context`] method of the same name.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum DrawCommand {
+    /// See [`Context::clear`].
+    Clear(Color),
+    /// See [`Context::draw_line`].
+    DrawLine {
+        /// The start of the line.
+        p0: Point2D<f32>,
+        /// The end of the line.
+        p1: Point2D<f32>,
+        /// The width of the stroke.
+        stroke_width: f32,
+        /// The brush used to paint the line.
+        brush: BrushSpec,
+        /// The stroke style, or `None` for a solid stroke.
+        style: Option<StrokeStyleProperties>,
+    },
+    /// See [`Context::fill_rect`].
+    FillRect {
+        /// The rectangle to fill.
+        rect: Rect2D<f32>,
+        /// The brush used to paint the interior.
+        brush: BrushSpec,
+    },
+    /// See [`Context::stroke_rect`].
+    StrokeRect {
+        /// The rectangle to outline.
+        rect: Rect2D<f32>,
+        /// The brush used to paint the outline.
+        brush: BrushSpec,
+        /// The width of the stroke.
+        stroke_width: f32,
+        /// The stroke style, or `None` for a solid stroke.
+        style: Option<StrokeStyleProperties>,
+    },
+    /// See [`Context::fill_rounded_rect`].
+    FillRoundedRect {
+        /// The rounded rectangle to fill.
+        rect: RoundedRect2D<f32>,
+        /// The brush used to paint the interior.
+        brush: BrushSpec,
+    },
+    /// See [`Context::stroke_rounded_rect`].
+    StrokeRoundedRect {
+        /// The rounded rectangle to outline.
+        rect: RoundedRect2D<f32>,
+        /// The brush used to paint the outline.
+        brush: BrushSpec,
+        /// The width of the stroke.
+        stroke_width: f32,
+        /// The stroke style, or `None` for a solid stroke.
+        style: Option<StrokeStyleProperties>,
+    },
+    /// See [`Context::fill_ellipse`].
+    FillEllipse {
+        /// The ellipse to fill.
+        ellipse: Ellipse2D<f32>,
+        /// The brush used to paint the interior.
+        brush: BrushSpec,
+    },
+    /// See [`Context::stroke_ellipse`].
+    StrokeEllipse {
+        /// The ellipse to outline.
+        ellipse: Ellipse2D<f32>,
+        /// The brush used to paint the outline.
+        brush: BrushSpec,
+        /// The width of the stroke.
+        stroke_width: f32,
+        /// The stroke style, or `None` for a solid stroke.
+        style: Option<StrokeStyleProperties>,
+    },
+    /// See [`Context::fill_geometry`].
+    FillPath {
+        /// The path to fill.
+        path: Rc<Path>,
+        /// The brush used to paint the interior.
+        brush: BrushSpec,
+    },
+    /// See [`Context::stroke_geometry`].
+    StrokePath {
+        /// The path to outline.
+        path: Rc<Path>,
+        /// The brush used to paint the outline.
+        brush: BrushSpec,
+        /// The width of the stroke.
+        stroke_width: f32,
+        /// The stroke style, or `None` for a solid stroke.
+        style: Option<StrokeStyleProperties>,
+    },
+    /// See [`Context::draw_bitmap`].
+    DrawBitmap {
+        /// The image to draw.
+        image: Rc<Image>,
+        /// The destination rectangle.
+        dest: Rect2D<f32>,
+        /// The opacity with which the image is drawn.
+        opacity: f32,
+        /// The sampling used if the image is scaled or rotated.
+        interpolation: InterpolationMode,
+        /// The region of the image to draw, or `None` for the whole image.
+        source: Option<Rect2D<f32>>,
+    },
+    /// See [`Context::set_transform`].
+    SetTransform(Transform2D),
+    /// See [`Context::push_transform`].
+    PushTransform(Transform2D),
+    /// See [`Context::pop_transform`].
+    PopTransform,
+    /// See [`Context::push_clip_rect`]. Lasts until the matching
+    /// [`PopClip`](Self::PopClip).
+    PushClipRect {
+        /// The clip rectangle.
+        rect: Rect2D<f32>,
+        /// How the edges of the clip are rendered.
+        antialias: AntialiasMode,
+    },
+    /// Pops the innermost clip. See [clips and
+    /// layers](DrawList#clips-and-layers) for unmatched pops.
+    PopClip,
+    /// See [`Context::push_layer`]. Lasts until the matching
+    /// [`PopLayer`](Self::PopLayer).
+    PushLayer(LayerSpec),
+    /// Pops the innermost layer. See [clips and
+    /// layers](DrawList#clips-and-layers) for unmatched pops.
+    PopLayer,
+}
+
+impl DrawCommand {
+    /// The kind of scope which this command pops, if it is a pop.
+    pub(crate) fn popped_scope(&self) -> Option<ScopeKind> {
+        match self {
+            DrawCommand::PopClip => Some(ScopeKind::Clip),
+            DrawCommand::PopLayer => Some(ScopeKind::Layer),
+            _ => None,
+        }
+    }
+}
+
+/// A recorded list of drawing commands.
+///
+/// A [`DrawList`] mirrors the drawing methods of [`Context`], but records each
+/// call as a [`DrawCommand`] instead of drawing it. The list can be compared
+/// and (with _feature_ `"serde"`) serialized, e.g. to snapshot test a frame on
+/// a CI host without Direct2D, or kept and [replayed](Self::replay) each frame
+/// to avoid re-building static content.
+///
+/// # Clips and layers
+///
+/// Lists need not be balanced. Every way of rendering a list treats its clips
+/// and layers alike: a pop only ends the innermost clip or layer if that is of
+/// the same kind, and is ignored otherwise, and clips and layers left open are
+/// closed once the whole list has been rendered.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{Color, DrawCommand, DrawList};
+/// use ::win_geom::d2::{Rect2D, Transform2D};
+///
+/// let mut list = DrawList::new();
+/// list.clear(Color::white());
+/// list.push_transform(Transform2D::translation(10.0, 10.0));
+/// list.fill_rect(Rect2D { left: 0.0, top: 0.0, right: 5.0, bottom: 5.0 }, Color::red());
+/// list.pop_transform();
+///
+/// assert_eq!(list.len(), 4);
+/// assert_eq!(list.commands()[0], DrawCommand::Clear(Color::white()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct DrawList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DrawList {
+    /// Constructs a new, empty draw list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded commands, in order.
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// The number of recorded commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Whether no commands have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Discards all recorded commands so that the list can be re-used.
+    pub fn reset(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Records a raw command.
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    /// Records all commands of `other`, e.g. to include a cached static layer
+    /// in this frame.
+    pub fn append(&mut self, other: &DrawList) {
+        self.commands.extend_from_slice(&other.commands);
+    }
+
+    /// Records [`Context::clear`].
+    pub fn clear(&mut self, color: Color) {
+        self.push(DrawCommand::Clear(color));
+    }
+
+    /// Records [`Context::draw_line`].
+    pub fn draw_line(
+        &mut self,
+        p0: Point2D<f32>,
+        p1: Point2D<f32>,
+        stroke_width: f32,
+        brush: impl Into<BrushSpec>,
+        style: Option<StrokeStyleProperties>,
+    ) {
+        self.push(DrawCommand::DrawLine {
+            p0,
+            p1,
+            stroke_width,
+            brush: brush.into(),
+            style,
+        });
+    }
+
+    /// Records [`Context::fill_rect`].
+    pub fn fill_rect(&mut self, rect: Rect2D<f32>, brush: impl Into<BrushSpec>) {
+        self.push(DrawCommand::FillRect {
+            rect,
+            brush: brush.into(),
+        });
+    }
+
+    /// Records [`Context::stroke_rect`].
+    pub fn stroke_rect(
+        &mut self,
+        rect: Rect2D<f32>,
+        brush: impl Into<BrushSpec>,
+        stroke_width: f32,
+        style: Option<StrokeStyleProperties>,
+    ) {
+        self.push(DrawCommand::StrokeRect {
+            rect,
+            brush: brush.into(),
+            stroke_width,
+            style,
+        });
+    }
+
+    /// Records [`Context::fill_rounded_rect`].
+    pub fn fill_rounded_rect(&mut self, rect: RoundedRect2D<f32>, brush: impl Into<BrushSpec>) {
+        self.push(DrawCommand::FillRoundedRect {
+            rect,
+            brush: brush.into(),
+        });
+    }
+
+    /// Records [`Context::stroke_rounded_rect`].
+    pub fn stroke_rounded_rect(
+        &mut self,
+        rect: RoundedRect2D<f32>,
+        brush: impl Into<BrushSpec>,
+        stroke_width: f32,
+        style: Option<StrokeStyleProperties>,
+    ) {
+        self.push(DrawCommand::StrokeRoundedRect {
+            rect,
+            brush: brush.into(),
+            stroke_width,
+            style,
+        });
+    }
+
+    /// Records [`Context::fill_ellipse`].
+    pub fn fill_ellipse(&mut self, ellipse: Ellipse2D<f32>, brush: impl Into<BrushSpec>) {
+        self.push(DrawCommand::FillEllipse {
+            ellipse,
+            brush: brush.into(),
+        });
+    }
+
+    /// Records [`Context::stroke_ellipse`].
+    pub fn stroke_ellipse(
+        &mut self,
+        ellipse: Ellipse2D<f32>,
+        brush: impl Into<BrushSpec>,
+        stroke_width: f32,
+        style: Option<StrokeStyleProperties>,
+    ) {
+        self.push(DrawCommand::StrokeEllipse {
+            ellipse,
+            brush: brush.into(),
+            stroke_width,
+            style,
+        });
+    }
+
+    /// Records [`Context::fill_geometry`] for the geometry of `path`.
+    pub fn fill_path(&mut self, path: impl Into<Rc<Path>>, brush: impl Into<BrushSpec>) {
+        self.push(DrawCommand::FillPath {
+            path: path.into(),
+            brush: brush.into(),
+        });
+    }
+
+    /// Records [`Context::stroke_geometry`] for the geometry of `path`.
+    pub fn stroke_path(
+        &mut self,
+        path: impl Into<Rc<Path>>,
+        brush: impl Into<BrushSpec>,
+        stroke_width: f32,
+        style: Option<StrokeStyleProperties>,
+    ) {
+        self.push(DrawCommand::StrokePath {
+            path: path.into(),
+            brush: brush.into(),
+            stroke_width,
+            style,
+        });
+    }
+
+    /// Records [`Context::draw_bitmap`] for a bitmap of `image`.
+    pub fn draw_bitmap(
+        &mut self,
+        image: impl Into<Rc<Image>>,
+        dest: Rect2D<f32>,
+        opacity: f32,
+        interpolation: InterpolationMode,
+        source: Option<Rect2D<f32>>,
+    ) {
+        self.push(DrawCommand::DrawBitmap {
+            image: image.into(),
+            dest,
+            opacity,
+            interpolation,
+            source,
+        });
+    }
+
+    /// Records [`Context::set_transform`].
+    pub fn set_transform(&mut self, transform: Transform2D) {
+        self.push(DrawCommand::SetTransform(transform));
+    }
+
+    /// Records [`Context::push_transform`].
+    pub fn push_transform(&mut self, transform: Transform2D) {
+        self.push(DrawCommand::PushTransform(transform));
+    }
+
+    /// Records [`Context::pop_transform`].
+    pub fn pop_transform(&mut self) {
+        self.push(DrawCommand::PopTransform);
+    }
+
+    /// Records [`Context::push_clip_rect`]. The clip lasts until the matching
+    /// [`pop_clip`](Self::pop_clip).
+    pub fn push_clip_rect(&mut self, rect: Rect2D<f32>, antialias: AntialiasMode) {
+        self.push(DrawCommand::PushClipRect { rect, antialias });
+    }
+
+    /// Records the end of the innermost clip.
+    pub fn pop_clip(&mut self) {
+        self.push(DrawCommand::PopClip);
+    }
+
+    /// Records [`Context::push_layer`]. The layer lasts until the matching
+    /// [`pop_layer`](Self::pop_layer).
+    pub fn push_layer(&mut self, layer: LayerSpec) {
+        self.push(DrawCommand::PushLayer(layer));
+    }
+
+    /// Records the end of the innermost layer.
+    pub fn pop_layer(&mut self) {
+        self.push(DrawCommand::PopLayer);
+    }
+
+    /// Replays the recorded commands onto `ctx`, beneath the context's current
+    /// transform, clips and layers.
+    ///
+    /// Brushes, stroke styles and images are fetched from the render target's
+    /// [`ResourceCache`](crate::cache::ResourceCache), so they are shared
+    /// between replays, while each distinct path is realized as a geometry
+    /// once per replay. Unbalanced [clips and layers](Self#clips-and-layers)
+    /// are handled as for any other backend.
+    pub fn replay(&self, ctx: &mut Context<'_>) -> Result<()> {
+        let mut resources = ReplayResources::new(ctx.render_target().factory());
+        replay_scope(ctx, None, &mut self.commands.iter(), &mut resources)
+    }
+
+    /// Renders the list as an SVG document of `size`, e.g. to inspect it in a
+    /// browser. See [`SvgDocument`](crate::SvgDocument) for how commands map
+    /// to SVG.
+    pub fn to_svg(&self, size: Size2D<f32>) -> String {
+        let mut document = SvgDocument::new(size);
+        document.render(self);
+        document.to_svg()
+    }
+
+    /// Serializes the list as a JSON document, e.g. to store a snapshot.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> ::serde_json::Result<String> {
+        ::serde_json::to_string_pretty(self)
+    }
+
+    /// Loads a list from a JSON document produced by
+    /// [`to_json`](Self::to_json).
+    #[cfg(feature = "json")]
+    pub fn from_json(doc: &str) -> ::serde_json::Result<Self> {
+        ::serde_json::from_str(doc)
+    }
+}
+
+impl Extend<DrawCommand> for DrawList {
+    fn extend<I: IntoIterator<Item = DrawCommand>>(&mut self, commands: I) {
+        self.commands.extend(commands);
+    }
+}
+
+/// Replays commands until the end of the list or until the pop which ends the
+/// current scope of kind `scope`, ignoring pops of the other kind. Scopes are
+/// nested on the call stack, as the context's clip and layer guards borrow it.
+fn replay_scope(
+    ctx: &mut Context<'_>,
+    scope: Option<ScopeKind>,
+    commands: &mut ::std::slice::Iter<'_, DrawCommand>,
+    res: &mut ReplayResources,
+) -> Result<()> {
+    while let Some(command) = commands.next() {
+        match command {
+            DrawCommand::Clear(color) => ctx.clear(*color),
+            DrawCommand::DrawLine {
+                p0,
+                p1,
+                stroke_width,
+                brush,
+                style,
+            } => {
+                let style = realize_stroke_style(ctx, style.as_ref())?;
+                ctx.draw_line(*p0, *p1, *stroke_width, brush, style.as_ref());
+            }
+            DrawCommand::FillRect { rect, brush } => ctx.fill_rect(*rect, brush),
+            DrawCommand::StrokeRect {
+                rect,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let style = realize_stroke_style(ctx, style.as_ref())?;
+                ctx.stroke_rect(*rect, brush, *stroke_width, style.as_ref());
+            }
+            DrawCommand::FillRoundedRect { rect, brush } => ctx.fill_rounded_rect(*rect, brush),
+            DrawCommand::StrokeRoundedRect {
+                rect,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let style = realize_stroke_style(ctx, style.as_ref())?;
+                ctx.stroke_rounded_rect(*rect, brush, *stroke_width, style.as_ref());
+            }
+            DrawCommand::FillEllipse { ellipse, brush } => ctx.fill_ellipse(*ellipse, brush),
+            DrawCommand::StrokeEllipse {
+                ellipse,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let style = realize_stroke_style(ctx, style.as_ref())?;
+                ctx.stroke_ellipse(*ellipse, brush, *stroke_width, style.as_ref());
+            }
+            DrawCommand::FillPath { path, brush } => {
+                let geometry = realize_geometry(&mut res.geometries, &res.factory, path)?;
+                ctx.fill_geometry(geometry, brush);
+            }
+            DrawCommand::StrokePath {
+                path,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let style = realize_stroke_style(ctx, style.as_ref())?;
+                let geometry = realize_geometry(&mut res.geometries, &res.factory, path)?;
+                ctx.stroke_geometry(geometry, brush, *stroke_width, style.as_ref());
+            }
+            DrawCommand::DrawBitmap {
+                image,
+                dest,
+                opacity,
+                interpolation,
+                source,
+            } => {
+                ctx.draw_image(image, *dest, *opacity, *interpolation, *source);
+            }
+            DrawCommand::SetTransform(transform) => ctx.set_transform(*transform),
+            DrawCommand::PushTransform(transform) => ctx.push_transform(*transform),
+            DrawCommand::PopTransform => ctx.pop_transform(),
+            DrawCommand::PushClipRect { rect, antialias } => {
+                let mut clip = ctx.push_clip_rect(*rect, *antialias);
+                replay_scope(&mut clip, Some(ScopeKind::Clip), commands, res)?;
+            }
+            DrawCommand::PushLayer(layer) => {
+                let mask = match &layer.mask {
+                    Some(path) => Some(realize_geometry(&mut res.geometries, &res.factory, path)?),
+                    None => None,
+                };
+                let opacity_brush = match &layer.opacity_brush {
+                    Some(brush) => {
+                        Some(realize_brush(&mut res.brushes, ctx.render_target(), brush)?)
+                    }
+                    None => None,
+                };
+                let params = LayerParameters {
+                    content_bounds: layer.content_bounds,
+                    mask,
+                    mask_antialias: layer.mask_antialias,
+                    mask_transform: layer.mask_transform,
+                    opacity: layer.opacity,
+                    opacity_brush,
+                };
+
+                let mut layer = ctx.push_layer(params);
+                replay_scope(&mut layer, Some(ScopeKind::Layer), commands, res)?;
+            }
+            DrawCommand::PopClip | DrawCommand::PopLayer => {
+                if command.popped_scope() == scope {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a scope is a clip or a layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ScopeKind {
+    Clip,
+    Layer,
+}
+
+/// The clips and layers pushed while rendering a [`DrawList`], innermost last,
+/// each with the state `T` needed to pop it. Backends which cannot nest scopes
+/// on the call stack track them with this so that unbalanced lists are
+/// handled as [documented](DrawList#clips-and-layers).
+#[derive(Debug)]
+pub(crate) struct ScopeStack<T> {
+    scopes: Vec<(ScopeKind, T)>,
+}
+
+impl<T> Default for ScopeStack<T> {
+    fn default() -> Self {
+        Self { scopes: Vec::new() }
+    }
+}
+
+impl<T> ScopeStack<T> {
+    /// The number of open scopes.
+    pub(crate) fn len(&self) -> usize {
+        self.scopes.len()
+    }
+
+    pub(crate) fn push(&mut self, kind: ScopeKind, scope: T) {
+        self.scopes.push((kind, scope));
+    }
+
+    /// Pops the innermost scope if it is of `kind`. Any other pop is unmatched,
+    /// so nothing is popped.
+    pub(crate) fn pop(&mut self, kind: ScopeKind) -> Option<T> {
+        match self.scopes.last() {
+            Some((innermost, _)) if *innermost == kind => self.scopes.pop().map(|(_, s)| s),
+            _ => None,
+        }
+    }
+
+    /// Pops the innermost scope whatever its kind, to close the scopes left
+    /// open at the end of a list.
+    pub(crate) fn pop_innermost(&mut self) -> Option<T> {
+        self.scopes.pop().map(|(_, s)| s)
+    }
+}
+
+/// The resources realized while replaying a single [`DrawList`] rather than
+/// fetched from the render target's cache, de-duplicated so that each is only
+/// created once. Lists typically hold only a handful of distinct resources, so
+/// each is found with a linear search.
+struct ReplayResources {
+    /// The factory from which geometries are created.
+    factory: Rc<D2DFactory>,
+    /// Layer opacity brushes, which must be device brushes.
+    brushes: Vec<(BrushSpec, Box<dyn Brush>)>,
+    /// Keyed by the identity of the shared path.
+    geometries: Vec<(Rc<Path>, PathGeometry)>,
+}
+
+impl ReplayResources {
+    fn new(factory: Rc<D2DFactory>) -> Self {
+        Self {
+            factory,
+            brushes: Vec::new(),
+            geometries: Vec::new(),
+        }
+    }
+}
+
+/// Finds the value whose key matches, or inserts the entry made by `make`.
+fn find_or_insert<K, V>(
+    entries: &mut Vec<(K, V)>,
+    matches: impl Fn(&K) -> bool,
+    make: impl FnOnce() -> Result<(K, V)>,
+) -> Result<&mut V> {
+    let index = match entries.iter().position(|(key, _)| matches(key)) {
+        Some(index) => index,
+        None => {
+            entries.push(make()?);
+            entries.len() - 1
+        }
+    };
+
+    Ok(&mut entries[index].1)
+}
+
+/// The device brush for `spec`, created on first use.
+fn realize_brush<'r>(
+    brushes: &'r mut Vec<(BrushSpec, Box<dyn Brush>)>,
+    render_target: &mut RenderTarget,
+    spec: &BrushSpec,
+) -> Result<&'r mut dyn Brush> {
+    let brush = find_or_insert(
+        brushes,
+        |s| s == spec,
+        || Ok((spec.clone(), make_brush(render_target, spec)?)),
+    )?;
+
+    Ok(&mut **brush)
+}
+
+/// The stroke style for `props`, fetched from the render target's cache.
+fn realize_stroke_style(
+    ctx: &mut Context<'_>,
+    props: Option<&StrokeStyleProperties>,
+) -> Result<Option<StrokeStyle>> {
+    props.map(|props| ctx.stroke_style(props)).transpose()
+}
+
+/// The geometry for `path`, created on first use.
+fn realize_geometry<'r>(
+    geometries: &'r mut Vec<(Rc<Path>, PathGeometry)>,
+    factory: &D2DFactory,
+    path: &Rc<Path>,
+) -> Result<&'r PathGeometry> {
+    let geometry = find_or_insert(
+        geometries,
+        |p| Rc::ptr_eq(p, path),
+        || Ok((path.clone(), factory.make_path_geometry(path.clone())?)),
+    )?;
+    Ok(geometry)
+}
+
+/// Creates the device brush described by `spec`.
+pub(crate) fn make_brush(
+    render_target: &mut RenderTarget,
+    spec: &BrushSpec,
+) -> Result<Box<dyn Brush>> {
+    Ok(match spec {
+        BrushSpec::Solid(color) => Box::new(render_target.make_solid_color_brush(*color)?),
+        BrushSpec::LinearGradient {
+            gradient,
+            start,
+            end,
+        } => Box::new(render_target.make_linear_gradient_brush(gradient, *start, *end)?),
+        BrushSpec::RadialGradient {
+            gradient,
+            ellipse,
+            origin_offset,
+        } => Box::new(render_target.make_radial_gradient_brush(
+            gradient,
+            *ellipse,
+            *origin_offset,
+        )?),
+        BrushSpec::Bitmap { image, properties } => {
+            let mut bitmap = render_target.make_bitmap(image.clone())?;
+            Box::new(render_target.make_bitmap_brush(&mut bitmap, *properties)?)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    fn rect(size: f32) -> Rect2D<f32> {
+        Rect2D {
+            left: 0.0,
+            top: 0.0,
+            right: size,
+            bottom: size,
+        }
+    }
+
+    #[test]
+    fn test_records_commands_in_order() {
+        let mut list = DrawList::new();
+        list.push_clip_rect(rect(10.0), AntialiasMode::Aliased);
+        list.fill_rect(rect(5.0), Color::red());
+        list.pop_clip();
+
+        assert_eq!(
+            list.commands(),
+            &[
+                DrawCommand::PushClipRect {
+                    rect: rect(10.0),
+                    antialias: AntialiasMode::Aliased
+                },
+                DrawCommand::FillRect {
+                    rect: rect(5.0),
+                    brush: BrushSpec::Solid(Color::red())
+                },
+                DrawCommand::PopClip,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_append_and_reset() {
+        let mut background = DrawList::new();
+        background.clear(Color::black());
+
+        let mut frame = DrawList::new();
+        frame.append(&background);
+        frame.fill_ellipse(
+            Ellipse2D::new_circle(Point2D { x: 1.0, y: 1.0 }, 1.0),
+            Color::white(),
+        );
+        assert_eq!(frame.len(), 2);
+        assert_eq!(frame.commands()[0], background.commands()[0]);
+
+        frame.reset();
+        assert!(frame.is_empty());
+    }
+
+    #[test]
+    fn test_bitmap_brushes_compare_images_by_identity() {
+        let image = || {
+            let size = Size2D {
+                width: 1,
+                height: 1,
+            };
+            Rc::new(Image::from_premultiplied_bgra(size, vec![0, 0, 255, 255]).unwrap())
+        };
+        let brush = |image: &Rc<Image>| BrushSpec::Bitmap {
+            image: image.clone(),
+            properties: BitmapBrushProperties::default(),
+        };
+        let (shared, copy) = (image(), image());
+
+        assert_eq!(brush(&shared), brush(&shared));
+        assert_ne!(brush(&shared), brush(&copy));
+        assert_ne!(brush(&shared), BrushSpec::Solid(Color::red()));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip() {
+        let mut path = Path::new();
+        path.move_to(Point2D { x: 0.0, y: 0.0 })
+            .line_to(Point2D { x: 4.0, y: 0.0 })
+            .close();
+
+        // Colors are serialized at byte precision, so only use byte-aligned
+        // colors.
+        let mut list = DrawList::new();
+        list.push_transform(Transform2D::rotation_about(0.5, Point2D { x: 1.0, y: 1.0 }));
+        list.stroke_path(
+            path,
+            BrushSpec::LinearGradient {
+                gradient: Gradient::two_stop(Color::red(), Color::blue()),
+                start: Point2D { x: 0.0, y: 0.0 },
+                end: Point2D { x: 4.0, y: 0.0 },
+            },
+            2.0,
+            Some(StrokeStyleProperties {
+                dash_style: crate::DashStyle::Custom(vec![2.0, 1.0]),
+                ..Default::default()
+            }),
+        );
+        list.push_layer(LayerSpec {
+            opacity: 0.5,
+            opacity_brush: Some(Color::new(0, 0, 0, 128).into()),
+            ..Default::default()
+        });
+        list.draw_bitmap(
+            Image::from_rgba(
+                ::win_geom::d2::Size2D {
+                    width: 1,
+                    height: 1,
+                },
+                &[255, 0, 0, 255],
+            )
+            .unwrap(),
+            rect(8.0),
+            1.0,
+            InterpolationMode::NearestNeighbor,
+            None,
+        );
+        list.pop_layer();
+        list.pop_transform();
+
+        let json = list.to_json().unwrap();
+        assert_eq!(DrawList::from_json(&json).unwrap(), list);
+    }
+}
@@ -0,0 +1,75 @@
+//! Crate-specific error and result types.
+
+use crate::text::TextRangeError;
+use ::win32::errors::Error as Win32Error;
+use ::windows::core::HRESULT;
+
+/// Result type returned by fallible rendering operations.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Error type for rendering with Direct2D.
+///
+/// None of these errors leave a [`RenderTarget`](crate::RenderTarget)
+/// permanently unusable. A failed frame can simply be drawn again, and a
+/// [poisoned](Self::Poisoned) render target can be restored with
+/// [`RenderTarget::recover`](crate::RenderTarget::recover).
+#[derive(::thiserror::Error, Debug)]
+pub enum Error {
+    /// A Direct2D call failed, e.g. when creating a device or resource.
+    #[error(transparent)]
+    Win32(#[from] Win32Error),
+    /// Direct2D reported an error for the batch of drawing flushed by
+    /// [`Context::end_draw`](crate::Context::end_draw), and so the frame was
+    /// not presented.
+    ///
+    /// The tags are those most recently set with
+    /// [`Context::set_tags`](crate::Context::set_tags) before the failing
+    /// drawing call, which helps identify it.
+    #[error("drawing failed with tags ({tag1}, {tag2}). {source}")]
+    Draw {
+        /// The first tag set before the failing drawing call.
+        tag1: u64,
+        /// The second tag set before the failing drawing call.
+        tag2: u64,
+        /// The error reported by `EndDraw`.
+        source: Win32Error,
+    },
+    /// Drawing began while a previous [`Context`](crate::Context) for the
+    /// render target was still drawing, i.e. it was dropped or leaked rather
+    /// than ended with [`end_draw`](crate::Context::end_draw).
+    #[error("render target is already drawing")]
+    DrawInProgress,
+    /// The render target was left in an unusable state by a panic during a
+    /// state transition.
+    #[error("render target poisoned")]
+    Poisoned,
+    /// A frame was captured before one was drawn (and, for a window, before
+    /// one was [requested](crate::RenderTarget::request_capture)), or after the
+    /// device which drew it was lost.
+    #[error("no frame has been drawn to capture")]
+    NothingToCapture,
+    /// A bitmap of the contents of a
+    /// [`BitmapTarget`](crate::bitmaps::BitmapTarget) was drawn after those
+    /// contents were lost along with the device. Redraw the target and fetch
+    /// its [`bitmap`](crate::bitmaps::BitmapTarget::bitmap) again.
+    #[error("bitmap target contents were lost with the device")]
+    TargetBitmapLost,
+    /// A text range or index was reversed, past the end of the text or within
+    /// a character.
+    #[error(transparent)]
+    TextRange(#[from] TextRangeError),
+}
+
+impl Error {
+    /// Returns the underlying Win32 error code, if any.
+    pub fn code(&self) -> Option<HRESULT> {
+        match self {
+            Self::Win32(e) | Self::Draw { source: e, .. } => e.code(),
+            Self::DrawInProgress
+            | Self::Poisoned
+            | Self::NothingToCapture
+            | Self::TargetBitmapLost
+            | Self::TextRange(_) => None,
+        }
+    }
+}
@@ -1,22 +1,29 @@
-//! Direct2D factory - the starting point for using Direct2D and creating other
-//! resources.
+//! Direct2D and DirectWrite factories - the starting points for using Direct2D
+//! and DirectWrite and creating other resources.
 
-use crate::RenderTarget;
+use crate::{
+    geometry::PathGeometry,
+    options::RenderTargetOptions,
+    path::Path,
+    stroke::{StrokeStyle, StrokeStyleProperties},
+    text::{wide, TextFormat, TextFormatProperties, TextLayout},
+    RenderTarget,
+};
 use ::std::{cell::UnsafeCell, marker::PhantomData, rc::Rc};
 use ::tracing::debug;
-use ::win32::{
-    errors::Result,
-    invoke::{check_res, chk},
-    window::DPI,
-};
+use ::win32::{errors::Result, invoke::check_res, window::DPI};
 use ::win_geom::d2::Size2D;
-use ::windows::Win32::{
-    Foundation::HWND,
-    Graphics::Direct2D::{
-        D2D1CreateFactory, ID2D1Factory, ID2D1HwndRenderTarget, D2D1_ANTIALIAS_MODE_PER_PRIMITIVE,
-        D2D1_FACTORY_OPTIONS, D2D1_FACTORY_TYPE_SINGLE_THREADED, D2D1_FEATURE_LEVEL_10,
-        D2D1_HWND_RENDER_TARGET_PROPERTIES, D2D1_RENDER_TARGET_PROPERTIES,
-        D2D1_RENDER_TARGET_TYPE_HARDWARE,
+use ::windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::HWND,
+        Graphics::{
+            Direct2D::{
+                ID2D1Factory, ID2D1HwndRenderTarget, D2D1_FACTORY_OPTIONS,
+                D2D1_HWND_RENDER_TARGET_PROPERTIES,
+            },
+            DirectWrite::{IDWriteFactory, DWRITE_FONT_STRETCH_NORMAL},
+        },
     },
 };
 
@@ -48,8 +55,7 @@ impl D2DFactory {
             },
         };
 
-        let factory: ID2D1Factory =
-            chk!(res; D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, Some(&options as _)))?;
+        let factory = create_factory(&options)?;
 
         Ok(Rc::new(Self {
             phantom: Default::default(),
@@ -66,7 +72,50 @@ impl D2DFactory {
     /// let factory = D2DFactory::new().unwrap();
     /// ```
     pub fn make_render_target(self: &Rc<Self>, hwnd: HWND, size: Size2D<i32>) -> RenderTarget {
-        RenderTarget::new(self, hwnd, size)
+        self.make_render_target_with_options(hwnd, size, RenderTargetOptions::default())
+    }
+
+    /// Makes a new Direct2D render target which targets a Win32 window, as
+    /// for [`make_render_target`](Self::make_render_target) but created with
+    /// `options`, e.g. to render in software or without waiting for vsync.
+    pub fn make_render_target_with_options(
+        self: &Rc<Self>,
+        hwnd: HWND,
+        size: Size2D<i32>,
+        options: RenderTargetOptions,
+    ) -> RenderTarget {
+        RenderTarget::new(self, hwnd, size, options)
+    }
+
+    /// Makes a new [`StrokeStyle`] describing the dashes, caps and joins of
+    /// stroked lines and outlines.
+    ///
+    /// Stroke styles are factory-level resources and may be used with any
+    /// render target created by this factory. They should be created once and
+    /// re-used.
+    pub fn make_stroke_style(&self, props: StrokeStyleProperties) -> Result<StrokeStyle> {
+        let (device_props, dashes) = props.device_properties();
+        let stroke_style = check_res(
+            || unsafe { self.inner.CreateStrokeStyle(&device_props as _, dashes) },
+            "CreateStrokeStyle",
+        )?;
+
+        Ok(StrokeStyle::new(props, stroke_style))
+    }
+
+    /// Makes a new [`PathGeometry`] from `path`, which can then be filled,
+    /// stroked or queried.
+    ///
+    /// Geometries are factory-level resources and may be used with any render
+    /// target created by this factory. They should be created once and
+    /// re-used.
+    pub fn make_path_geometry(&self, path: impl Into<Rc<Path>>) -> Result<PathGeometry> {
+        let geometry = check_res(
+            || unsafe { self.inner.CreatePathGeometry() },
+            "CreatePathGeometry",
+        )?;
+
+        PathGeometry::new(path.into(), geometry)
     }
 
     /// (Re-)creates the device render target. Called once on initialization and
@@ -75,25 +124,18 @@ impl D2DFactory {
     pub(crate) fn make_device_render_target(
         &self,
         hwnd: HWND,
-        size: Size2D<i32>,
+        pixel_size: Size2D<u32>,
+        dpi: DPI,
+        options: &RenderTargetOptions,
     ) -> Result<ID2D1HwndRenderTarget> {
-        let dpi = DPI::detect(hwnd);
-
-        let render_props = D2D1_RENDER_TARGET_PROPERTIES {
-            r#type: D2D1_RENDER_TARGET_TYPE_HARDWARE,
-            dpiX: dpi.into(),
-            dpiY: dpi.into(),
-            minLevel: D2D1_FEATURE_LEVEL_10, // Require DirectX 10
-            ..Default::default()
-        };
+        let render_props = options.render_target_properties(dpi);
 
-        let pixel_size = dpi.scale_size(size);
-        ::tracing::warn!("dpi.scale(size) = {pixel_size:?}");
+        debug!("Creating render target of {pixel_size:?} pixels at {dpi} with {options:?}");
 
         let hwnd_target_props = D2D1_HWND_RENDER_TARGET_PROPERTIES {
             hwnd,
-            pixelSize: pixel_size.cast::<u32>().into(),
-            ..Default::default()
+            pixelSize: pixel_size.into(),
+            presentOptions: options.present_mode().into(),
         };
 
         // TODO: macro parsing for field access, not just free functions
@@ -105,10 +147,160 @@ impl D2DFactory {
             "CreateHwndRenderTarget",
         )?;
 
-        unsafe {
-            render_target.SetAntialiasMode(D2D1_ANTIALIAS_MODE_PER_PRIMITIVE);
-        }
-
         Ok(render_target)
     }
 }
+
+/// A DirectWrite factory - the starting point for drawing text, from which
+/// [`TextFormat`]s and [`TextLayout`]s are made.
+///
+/// Text formats and layouts are factory-level resources. They may be drawn to
+/// any render target and never need to be re-created after device loss.
+///
+/// # Example
+///
+/// ```no_run
+/// use ::d2d::{DWriteFactory, TextFormatProperties};
+///
+/// let factory = DWriteFactory::new()?;
+/// let format = factory.make_text_format(TextFormatProperties {
+///     font_size: 18.0,
+///     ..Default::default()
+/// })?;
+/// # Ok::<(), ::win32::errors::Error>(())
+/// ```
+pub struct DWriteFactory {
+    inner: IDWriteFactory,
+
+    /// Force !Send & !Sync, as the text formats and layouts made by the
+    /// factory are only used on the thread which draws them.
+    phantom: PhantomData<UnsafeCell<()>>,
+}
+
+impl DWriteFactory {
+    /// Create a new factory from which text formats and layouts can be made.
+    ///
+    /// As with the [`D2DFactory`], one factory should exist per thread for the
+    /// lifetime of the thread.
+    pub fn new() -> Result<Rc<Self>> {
+        let factory = create_write_factory()?;
+
+        Ok(Rc::new(Self {
+            phantom: Default::default(),
+            inner: factory,
+        }))
+    }
+
+    /// Makes a new [`TextFormat`] describing the font and paragraph layout of
+    /// text.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the format could not be created, e.g. if the locale is not
+    /// valid. A font family which is not installed does not fail, but falls
+    /// back to a system font when drawn.
+    pub fn make_text_format(&self, props: TextFormatProperties) -> Result<TextFormat> {
+        let font_family = wide(&props.font_family);
+        let locale = wide(&props.locale);
+        let format = check_res(
+            || unsafe {
+                self.inner.CreateTextFormat(
+                    PCWSTR::from_raw(font_family.as_ptr()),
+                    None,
+                    props.weight.into(),
+                    props.style.into(),
+                    DWRITE_FONT_STRETCH_NORMAL,
+                    props.font_size,
+                    PCWSTR::from_raw(locale.as_ptr()),
+                )
+            },
+            "CreateTextFormat",
+        )?;
+
+        check_res(
+            || unsafe { format.SetTextAlignment(props.alignment.into()) },
+            "SetTextAlignment",
+        )?;
+        check_res(
+            || unsafe { format.SetParagraphAlignment(props.paragraph_alignment.into()) },
+            "SetParagraphAlignment",
+        )?;
+        check_res(
+            || unsafe { format.SetWordWrapping(props.wrapping.into()) },
+            "SetWordWrapping",
+        )?;
+
+        Ok(TextFormat::new(props, format))
+    }
+
+    /// Lays out `text` in the font of `format` within a box of `max_size`,
+    /// making a [`TextLayout`] which can be measured, hit-tested and styled.
+    pub fn make_text_layout(
+        &self,
+        text: impl Into<String>,
+        format: &TextFormat,
+        max_size: Size2D<f32>,
+    ) -> Result<TextLayout> {
+        let text = text.into();
+        let utf16 = text.encode_utf16().collect::<Vec<_>>();
+        let layout = check_res(
+            || unsafe {
+                self.inner.CreateTextLayout(
+                    &utf16,
+                    format.device_format(),
+                    max_size.width,
+                    max_size.height,
+                )
+            },
+            "CreateTextLayout",
+        )?;
+
+        Ok(TextLayout::new(text, layout))
+    }
+}
+
+/// Creates the underlying single-threaded Direct2D factory.
+#[cfg(windows)]
+fn create_factory(options: &D2D1_FACTORY_OPTIONS) -> Result<ID2D1Factory> {
+    use ::win32::invoke::chk;
+    use ::windows::Win32::Graphics::Direct2D::{
+        D2D1CreateFactory, D2D1_FACTORY_TYPE_SINGLE_THREADED,
+    };
+
+    chk!(res; D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, Some(options as _)))
+}
+
+/// Direct2D is only available on Windows. The crate still builds on other
+/// platforms so that the CPU-side functionality (colors, gradients, image
+/// decoding, etc.) can be unit tested on any CI host, but a factory can never
+/// be created.
+#[cfg(not(windows))]
+fn create_factory(_options: &D2D1_FACTORY_OPTIONS) -> Result<ID2D1Factory> {
+    Err(::win32::errors::Error::Unexpected {
+        function: "D2D1CreateFactory",
+        context: ::windows::Win32::Foundation::E_NOTIMPL.into(),
+    })
+}
+
+/// Creates the underlying shared DirectWrite factory.
+#[cfg(windows)]
+fn create_write_factory() -> Result<IDWriteFactory> {
+    use ::windows::Win32::Graphics::DirectWrite::{
+        DWriteCreateFactory, DWRITE_FACTORY_TYPE_SHARED,
+    };
+
+    check_res(
+        || unsafe { DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED) },
+        "DWriteCreateFactory",
+    )
+}
+
+/// As with Direct2D, DirectWrite is only available on Windows and so a factory
+/// can never be created on other platforms.
+#[cfg(not(windows))]
+fn create_write_factory() -> Result<IDWriteFactory> {
+    Err(::win32::errors::Error::Unexpected {
+        function: "DWriteCreateFactory",
+        context: ::windows::Win32::Foundation::E_NOTIMPL.into(),
+    })
+}
@@ -0,0 +1,174 @@
+//! Drawable path geometries and geometric queries such as bounds and hit
+//! testing.
+
+use crate::{
+    path::{Path, PathSegment},
+    stroke::{stroke_style_param, StrokeStyle},
+};
+use ::std::rc::Rc;
+use ::win32::{errors::Result, invoke::check_res};
+use ::win_geom::d2::{Point2D, Rect2D};
+use ::windows::Win32::Graphics::Direct2D::{
+    Common::{
+        D2D1_BEZIER_SEGMENT, D2D1_FIGURE_BEGIN_FILLED, D2D1_FIGURE_END_CLOSED, D2D1_FIGURE_END_OPEN,
+    },
+    ID2D1Geometry, ID2D1GeometrySink, ID2D1PathGeometry, D2D1_DEFAULT_FLATTENING_TOLERANCE,
+    D2D1_QUADRATIC_BEZIER_SEGMENT,
+};
+
+/// A [`Path`] which has been realized by the factory so that it can be drawn
+/// with [`Context::fill_geometry`](crate::Context::fill_geometry) and
+/// [`Context::stroke_geometry`](crate::Context::stroke_geometry), and queried
+/// for its bounds, length and hit tests.
+///
+/// Path geometries are factory-level resources. They may be used with any
+/// render target created by the same factory and never need to be re-created
+/// after device loss. Cloning a geometry is cheap and shares the underlying
+/// Direct2D geometry.
+///
+/// All queries are performed in the geometry's own coordinate space.
+#[derive(Clone, Debug)]
+pub struct PathGeometry {
+    /// The CPU-side path from which the geometry was created.
+    path: Rc<Path>,
+    /// The underlying Direct2D geometry.
+    inner: ID2D1PathGeometry,
+}
+
+impl PathGeometry {
+    /// A crate-private constructor. Only a [`D2DFactory`](crate::D2DFactory)
+    /// should be able to create geometries.
+    pub(crate) fn new(path: Rc<Path>, inner: ID2D1PathGeometry) -> Result<Self> {
+        let sink = check_res(|| unsafe { inner.Open() }, "Open")?;
+        populate(&sink, &path);
+        check_res(|| unsafe { sink.Close() }, "Close")?;
+
+        Ok(Self { path, inner })
+    }
+
+    /// The path from which the geometry was created.
+    pub fn path(&self) -> &Rc<Path> {
+        &self.path
+    }
+
+    /// The smallest rectangle which contains the geometry's fill.
+    pub fn bounds(&self) -> Result<Rect2D<f32>> {
+        check_res(|| unsafe { self.inner.GetBounds(None) }, "GetBounds").map(Into::into)
+    }
+
+    /// The smallest rectangle which contains the geometry when stroked with
+    /// the given width and style.
+    pub fn widened_bounds(
+        &self,
+        stroke_width: f32,
+        style: Option<&StrokeStyle>,
+    ) -> Result<Rect2D<f32>> {
+        check_res(
+            || unsafe {
+                self.inner.GetWidenedBounds(
+                    stroke_width,
+                    stroke_style_param(style),
+                    None,
+                    D2D1_DEFAULT_FLATTENING_TOLERANCE,
+                )
+            },
+            "GetWidenedBounds",
+        )
+        .map(Into::into)
+    }
+
+    /// Whether `point` lies within the geometry's fill, according to the
+    /// path's [`FillMode`](crate::FillMode).
+    pub fn fill_contains_point(&self, point: Point2D<f32>) -> Result<bool> {
+        check_res(
+            || unsafe {
+                self.inner
+                    .FillContainsPoint(point.into(), None, D2D1_DEFAULT_FLATTENING_TOLERANCE)
+            },
+            "FillContainsPoint",
+        )
+        .map(|contains| contains.as_bool())
+    }
+
+    /// Whether `point` lies on the geometry's outline when stroked with the
+    /// given width and style. Useful for hit testing lines and outlines.
+    pub fn stroke_contains_point(
+        &self,
+        point: Point2D<f32>,
+        stroke_width: f32,
+        style: Option<&StrokeStyle>,
+    ) -> Result<bool> {
+        check_res(
+            || unsafe {
+                self.inner.StrokeContainsPoint(
+                    point.into(),
+                    stroke_width,
+                    stroke_style_param(style),
+                    None,
+                    D2D1_DEFAULT_FLATTENING_TOLERANCE,
+                )
+            },
+            "StrokeContainsPoint",
+        )
+        .map(|contains| contains.as_bool())
+    }
+
+    /// The total length of all segments in the geometry, as if each were
+    /// unrolled into a line.
+    pub fn length(&self) -> Result<f32> {
+        check_res(
+            || unsafe {
+                self.inner
+                    .ComputeLength(None, D2D1_DEFAULT_FLATTENING_TOLERANCE)
+            },
+            "ComputeLength",
+        )
+    }
+
+    /// Accesses the underlying Direct2D geometry.
+    pub(crate) fn device_geometry(&self) -> &ID2D1Geometry {
+        (&self.inner).into()
+    }
+}
+
+/// Replays the segments of `path` into a Direct2D geometry sink.
+fn populate(sink: &ID2D1GeometrySink, path: &Path) {
+    let mut in_figure = false;
+
+    unsafe {
+        sink.SetFillMode(path.fill_mode().into());
+
+        for segment in path.segments() {
+            match *segment {
+                PathSegment::MoveTo(to) => {
+                    if in_figure {
+                        sink.EndFigure(D2D1_FIGURE_END_OPEN);
+                    }
+                    sink.BeginFigure(to.into(), D2D1_FIGURE_BEGIN_FILLED);
+                    in_figure = true;
+                }
+                PathSegment::LineTo(to) => sink.AddLine(to.into()),
+                PathSegment::QuadTo { ctrl, to } => {
+                    sink.AddQuadraticBezier(&D2D1_QUADRATIC_BEZIER_SEGMENT {
+                        point1: ctrl.into(),
+                        point2: to.into(),
+                    } as _)
+                }
+                PathSegment::CubicTo { ctrl1, ctrl2, to } => sink.AddBezier(&D2D1_BEZIER_SEGMENT {
+                    point1: ctrl1.into(),
+                    point2: ctrl2.into(),
+                    point3: to.into(),
+                } as _),
+                PathSegment::ArcTo(arc) => sink.AddArc(&arc.into() as _),
+                PathSegment::Close => {
+                    sink.EndFigure(D2D1_FIGURE_END_CLOSED);
+                    in_figure = false;
+                }
+            }
+        }
+
+        if in_figure {
+            sink.EndFigure(D2D1_FIGURE_END_OPEN);
+        }
+    }
+}
@@ -0,0 +1,363 @@
+//! Color gradients which describe how gradient brushes blend between colors.
+//!
+//! A [`Gradient`] is a plain CPU-side description which can be sampled without
+//! a render target. Device brushes such as
+//! [`LinearGradientBrush`](crate::brushes::LinearGradientBrush) are created
+//! from a [`Gradient`] by a [`RenderTarget`](crate::RenderTarget).
+
+use crate::color::Color;
+use ::windows::Win32::Graphics::Direct2D::{
+    D2D1_EXTEND_MODE, D2D1_EXTEND_MODE_CLAMP, D2D1_EXTEND_MODE_MIRROR, D2D1_EXTEND_MODE_WRAP,
+    D2D1_GAMMA, D2D1_GAMMA_1_0, D2D1_GAMMA_2_2, D2D1_GRADIENT_STOP,
+};
+
+/// Specifies how a brush paints areas outside of its normal content area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum ExtendMode {
+    /// Repeat the edge pixels (or the color of the first or last gradient
+    /// stop) indefinitely.
+    #[default]
+    Clamp,
+    /// Repeat the content (or gradient) indefinitely.
+    Wrap,
+    /// Repeat the content (or gradient) indefinitely, reversing direction on
+    /// every second repetition.
+    Mirror,
+}
+
+impl From<ExtendMode> for D2D1_EXTEND_MODE {
+    fn from(mode: ExtendMode) -> Self {
+        match mode {
+            ExtendMode::Clamp => D2D1_EXTEND_MODE_CLAMP,
+            ExtendMode::Wrap => D2D1_EXTEND_MODE_WRAP,
+            ExtendMode::Mirror => D2D1_EXTEND_MODE_MIRROR,
+        }
+    }
+}
+
+/// The color space in which gradient stops are interpolated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum Gamma {
+    /// Interpolation is performed in the standard RGB (sRGB) gamma space. This
+    /// is the Direct2D default.
+    #[default]
+    Srgb,
+    /// Interpolation is performed in linear gamma space, which generally gives
+    /// brighter and more physically accurate midpoints.
+    Linear,
+}
+
+impl From<Gamma> for D2D1_GAMMA {
+    fn from(gamma: Gamma) -> Self {
+        match gamma {
+            Gamma::Srgb => D2D1_GAMMA_2_2,
+            Gamma::Linear => D2D1_GAMMA_1_0,
+        }
+    }
+}
+
+/// A single color at a given position within a [`Gradient`].
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct GradientStop {
+    /// The relative position of the stop in the gradient. Positions are
+    /// normally in the range from 0.0 through 1.0.
+    pub position: f32,
+    /// The color at this position.
+    pub color: Color,
+}
+
+impl GradientStop {
+    /// Construct a new gradient stop.
+    pub fn new(position: f32, color: Color) -> Self {
+        Self { position, color }
+    }
+}
+
+impl From<GradientStop> for D2D1_GRADIENT_STOP {
+    fn from(stop: GradientStop) -> Self {
+        Self {
+            position: stop.position,
+            color: stop.color.into(),
+        }
+    }
+}
+
+/// An ordered collection of [`GradientStop`]s together with the gamma and
+/// extend mode which govern how the stops are blended.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{Color, ExtendMode, Gradient};
+///
+/// let gradient = Gradient::two_stop(Color::black(), Color::white())
+///     .with_extend_mode(ExtendMode::Mirror);
+///
+/// assert_eq!(gradient.sample(0.0), Color::black());
+/// assert_eq!(gradient.sample(1.0), Color::white());
+/// assert_eq!(gradient.sample(2.0), Color::black());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize))]
+pub struct Gradient {
+    /// Stops, sorted by position.
+    stops: Vec<GradientStop>,
+    gamma: Gamma,
+    extend_mode: ExtendMode,
+}
+
+impl Gradient {
+    /// Construct a new gradient from a set of stops. Stops need not be sorted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stops` is empty. Direct2D requires at least one stop.
+    pub fn new(stops: impl Into<Vec<GradientStop>>) -> Self {
+        let mut stops = stops.into();
+        assert!(!stops.is_empty(), "Gradient requires at least one stop");
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+        Self {
+            stops,
+            gamma: Gamma::default(),
+            extend_mode: ExtendMode::default(),
+        }
+    }
+
+    /// Construct a simple gradient which blends from `start` at position 0.0
+    /// to `end` at position 1.0.
+    pub fn two_stop(start: Color, end: Color) -> Self {
+        Self::new([GradientStop::new(0.0, start), GradientStop::new(1.0, end)])
+    }
+
+    /// Sets the color space in which stops are interpolated.
+    pub fn with_gamma(mut self, gamma: Gamma) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Sets how the gradient behaves outside of the 0.0 - 1.0 range.
+    pub fn with_extend_mode(mut self, extend_mode: ExtendMode) -> Self {
+        self.extend_mode = extend_mode;
+        self
+    }
+
+    /// The gradient stops, sorted by position.
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    /// The color space in which stops are interpolated.
+    pub fn gamma(&self) -> Gamma {
+        self.gamma
+    }
+
+    /// How the gradient behaves outside of the 0.0 - 1.0 range.
+    pub fn extend_mode(&self) -> ExtendMode {
+        self.extend_mode
+    }
+
+    /// Computes the color of the gradient at position `t`, applying the extend
+    /// mode and gamma in the same manner as Direct2D. A `t` which is NaN, or
+    /// infinite when wrapping or mirroring, samples the first stop.
+    pub fn sample(&self, t: f32) -> Color {
+        let t = match self.extend_mode {
+            ExtendMode::Clamp => t.clamp(0.0, 1.0),
+            ExtendMode::Wrap => t - t.floor(),
+            ExtendMode::Mirror => {
+                let t = t.rem_euclid(2.0);
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+        };
+
+        let first = self.stops.first().expect("gradient has at least one stop");
+        let last = self.stops.last().expect("gradient has at least one stop");
+        if t.is_nan() || t <= first.position {
+            return first.color;
+        }
+        if t >= last.position {
+            return last.color;
+        }
+
+        let (a, b) = self
+            .stops
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .find(|(_, b)| t < b.position)
+            .expect("t lies strictly within the first and last stop");
+
+        let span = b.position - a.position;
+        let f = if span > 0.0 {
+            (t - a.position) / span
+        } else {
+            1.0
+        };
+
+        match self.gamma {
+            Gamma::Srgb => a.color.lerp(b.color, f),
+            Gamma::Linear => {
+                let mix = |x: f32, y: f32| {
+                    linear_to_srgb(srgb_to_linear(x) + (srgb_to_linear(y) - srgb_to_linear(x)) * f)
+                };
+                Color {
+                    red: mix(a.color.red, b.color.red),
+                    green: mix(a.color.green, b.color.green),
+                    blue: mix(a.color.blue, b.color.blue),
+                    alpha: a.color.alpha + (b.color.alpha - a.color.alpha) * f,
+                }
+            }
+        }
+    }
+
+    /// The stops in their Direct2D representation.
+    pub(crate) fn device_stops(&self) -> Vec<D2D1_GRADIENT_STOP> {
+        self.stops.iter().copied().map(Into::into).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use super::{ExtendMode, Gamma, Gradient, GradientStop};
+    use ::serde::{de::Error, Deserialize, Deserializer};
+
+    /// The serialized fields of a [`Gradient`], which are validated and sorted
+    /// by [`Gradient::new`] on deserialization.
+    #[derive(Deserialize)]
+    struct Fields {
+        stops: Vec<GradientStop>,
+        #[serde(default)]
+        gamma: Gamma,
+        #[serde(default)]
+        extend_mode: ExtendMode,
+    }
+
+    impl<'de> Deserialize<'de> for Gradient {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let Fields {
+                stops,
+                gamma,
+                extend_mode,
+            } = Fields::deserialize(deserializer)?;
+            if stops.is_empty() {
+                return Err(D::Error::custom("gradient requires at least one stop"));
+            }
+
+            Ok(Gradient::new(stops)
+                .with_gamma(gamma)
+                .with_extend_mode(extend_mode))
+        }
+    }
+}
+
+/// Converts an sRGB encoded component into linear light.
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear light component into sRGB encoding.
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    fn assert_close(a: Color, b: Color) {
+        let close = |x: f32, y: f32| (x - y).abs() < 1e-4;
+        assert!(
+            close(a.red, b.red)
+                && close(a.green, b.green)
+                && close(a.blue, b.blue)
+                && close(a.alpha, b.alpha),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_sample_interpolates_between_stops() {
+        let gradient = Gradient::new([
+            GradientStop::new(1.0, Color::white()),
+            GradientStop::new(0.0, Color::black()),
+            GradientStop::new(0.5, Color::red()),
+        ]);
+
+        assert_eq!(gradient.stops()[1].color, Color::red());
+        assert_eq!(gradient.sample(0.5), Color::red());
+        assert_close(
+            gradient.sample(0.25),
+            Color::black().lerp(Color::red(), 0.5),
+        );
+        assert_close(
+            gradient.sample(0.75),
+            Color::red().lerp(Color::white(), 0.5),
+        );
+    }
+
+    #[test]
+    fn test_sample_extend_modes() {
+        let gradient = Gradient::two_stop(Color::black(), Color::white());
+        let gray = |v: f32| Color::new_normalized(v, v, v, 1.0);
+
+        let clamp = gradient.clone().with_extend_mode(ExtendMode::Clamp);
+        assert_eq!(clamp.sample(-1.0), Color::black());
+        assert_eq!(clamp.sample(1.25), Color::white());
+
+        let wrap = gradient.clone().with_extend_mode(ExtendMode::Wrap);
+        assert_close(wrap.sample(1.25), gray(0.25));
+        assert_close(wrap.sample(-0.25), gray(0.75));
+
+        let mirror = gradient.with_extend_mode(ExtendMode::Mirror);
+        assert_close(mirror.sample(1.25), gray(0.75));
+        assert_close(mirror.sample(-0.25), gray(0.25));
+    }
+
+    #[test]
+    fn test_sample_non_finite() {
+        let gradient = Gradient::two_stop(Color::black(), Color::white());
+        assert_eq!(gradient.sample(f32::NAN), Color::black());
+        assert_eq!(gradient.sample(f32::INFINITY), Color::white());
+
+        for mode in [ExtendMode::Wrap, ExtendMode::Mirror] {
+            let gradient = gradient.clone().with_extend_mode(mode);
+            assert_eq!(gradient.sample(f32::INFINITY), Color::black());
+            assert_eq!(gradient.sample(f32::NEG_INFINITY), Color::black());
+        }
+    }
+
+    #[test]
+    fn test_sample_linear_gamma() {
+        let gradient = Gradient::two_stop(Color::black(), Color::white());
+        let srgb = gradient.clone().sample(0.5);
+        let linear = gradient.with_gamma(Gamma::Linear).sample(0.5);
+
+        assert_close(srgb, Color::new_normalized(0.5, 0.5, 0.5, 1.0));
+        // Half of the linear light is considerably brighter than half of the
+        // encoded value.
+        assert!((linear.red - 0.7354).abs() < 1e-3, "{linear:?}");
+    }
+
+    #[test]
+    fn test_single_stop() {
+        let gradient = Gradient::new([GradientStop::new(0.3, Color::red())]);
+        assert_eq!(gradient.sample(0.0), Color::red());
+        assert_eq!(gradient.sample(1.0), Color::red());
+    }
+}
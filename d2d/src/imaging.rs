@@ -0,0 +1,346 @@
+//! CPU-side images: decoding PNG and BMP files and converting pixel data into
+//! the premultiplied BGRA format which Direct2D bitmaps expect.
+//!
+//! Nothing in this module touches a render target, so it can be used (and
+//! tested) on any platform.
+
+use ::std::fmt::{self, Debug};
+use ::win_geom::d2::Size2D;
+
+/// Errors which can occur when constructing or decoding an [`Image`].
+#[derive(::thiserror::Error, Debug)]
+pub enum ImageError {
+    /// The pixel buffer length does not match the image dimensions.
+    #[error("pixel buffer has {actual} bytes, expected {expected}")]
+    InvalidLength {
+        /// The number of bytes required by the image dimensions.
+        expected: usize,
+        /// The number of bytes which were provided.
+        actual: usize,
+    },
+    /// The encoded data is not a supported image format (PNG or BMP).
+    #[error("unsupported image format")]
+    UnsupportedFormat,
+    /// The encoded data could not be decoded.
+    #[error("failed to decode image: {0}")]
+    Decode(String),
+    /// The image could not be encoded.
+    #[error("failed to encode image: {0}")]
+    Encode(String),
+}
+
+/// An image held in CPU memory as 32-bit premultiplied BGRA pixels, ready to be
+/// uploaded into a [`Bitmap`](crate::bitmaps::Bitmap).
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize))]
+pub struct Image {
+    /// Dimensions of the image, in pixels.
+    size: Size2D<u32>,
+    /// Tightly packed rows of premultiplied BGRA pixels.
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Constructs an image from tightly packed rows of straight (i.e. not
+    /// premultiplied) RGBA pixels, as produced by most image libraries.
+    pub fn from_rgba(size: Size2D<u32>, rgba: &[u8]) -> Result<Self, ImageError> {
+        check_len(size, rgba.len())?;
+        Ok(Self {
+            size,
+            pixels: rgba_to_premultiplied_bgra(rgba),
+        })
+    }
+
+    /// Constructs an image from tightly packed rows of pixels which are
+    /// already in premultiplied BGRA format.
+    pub fn from_premultiplied_bgra(size: Size2D<u32>, bgra: Vec<u8>) -> Result<Self, ImageError> {
+        check_len(size, bgra.len())?;
+        Ok(Self { size, pixels: bgra })
+    }
+
+    /// Decodes a PNG or BMP file held in memory. The format is detected from
+    /// the file's signature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ::d2d::Image;
+    ///
+    /// let bytes = ::std::fs::read("sprite.png").unwrap();
+    /// let image = Image::decode(&bytes).unwrap();
+    /// ```
+    pub fn decode(bytes: &[u8]) -> Result<Self, ImageError> {
+        use ::image::ImageFormat;
+
+        let format = match ::image::guess_format(bytes) {
+            Ok(format @ (ImageFormat::Png | ImageFormat::Bmp)) => format,
+            _ => return Err(ImageError::UnsupportedFormat),
+        };
+
+        let rgba = ::image::load_from_memory_with_format(bytes, format)
+            .map_err(|e| ImageError::Decode(e.to_string()))?
+            .into_rgba8();
+        let size = Size2D {
+            width: rgba.width(),
+            height: rgba.height(),
+        };
+
+        Self::from_rgba(size, rgba.as_raw())
+    }
+
+    /// Encodes the image as a PNG file held in memory.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ::d2d::Image;
+    /// # let image = Image::decode(&[]).unwrap();
+    ///
+    /// ::std::fs::write("frame.png", image.encode_png().unwrap()).unwrap();
+    /// ```
+    pub fn encode_png(&self) -> Result<Vec<u8>, ImageError> {
+        use ::image::{codecs::png::PngEncoder, ColorType, ImageEncoder};
+
+        let mut png = Vec::new();
+        PngEncoder::new(&mut png)
+            .write_image(
+                &self.to_rgba(),
+                self.size.width,
+                self.size.height,
+                ColorType::Rgba8,
+            )
+            .map_err(|e| ImageError::Encode(e.to_string()))?;
+
+        Ok(png)
+    }
+
+    /// Dimensions of the image, in pixels.
+    pub fn size(&self) -> Size2D<u32> {
+        self.size
+    }
+
+    /// The number of bytes between the start of each row of pixels.
+    pub fn stride(&self) -> u32 {
+        self.size.width * 4
+    }
+
+    /// The raw premultiplied BGRA pixel data.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Converts the image back into tightly packed rows of straight RGBA
+    /// pixels.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        premultiplied_bgra_to_rgba(&self.pixels)
+    }
+}
+
+impl Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Image").field("size", &self.size).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use super::Image;
+    use ::serde::{de::Error, Deserialize, Deserializer};
+    use ::win_geom::d2::Size2D;
+
+    /// The serialized fields of an [`Image`], which are validated on
+    /// deserialization.
+    #[derive(Deserialize)]
+    struct Fields {
+        size: Size2D<u32>,
+        pixels: Vec<u8>,
+    }
+
+    impl<'de> Deserialize<'de> for Image {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let Fields { size, pixels } = Fields::deserialize(deserializer)?;
+            Image::from_premultiplied_bgra(size, pixels).map_err(D::Error::custom)
+        }
+    }
+}
+
+/// Verifies that a buffer of `len` bytes holds exactly one 32-bit pixel for
+/// each pixel in `size`.
+fn check_len(size: Size2D<u32>, len: usize) -> Result<(), ImageError> {
+    let expected = size.width as usize * size.height as usize * 4;
+    if len == expected {
+        Ok(())
+    } else {
+        Err(ImageError::InvalidLength {
+            expected,
+            actual: len,
+        })
+    }
+}
+
+/// Multiplies a color channel by an alpha value, with rounding.
+fn premultiply(c: u8, a: u8) -> u8 {
+    ((c as u32 * a as u32 + 127) / 255) as u8
+}
+
+/// Divides a premultiplied color channel by its alpha value, with rounding.
+fn unpremultiply(c: u8, a: u8) -> u8 {
+    match a {
+        0 => 0,
+        _ => ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8,
+    }
+}
+
+/// Converts straight RGBA pixels into premultiplied BGRA pixels.
+fn rgba_to_premultiplied_bgra(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|px| {
+            let [r, g, b, a] = [px[0], px[1], px[2], px[3]];
+            [premultiply(b, a), premultiply(g, a), premultiply(r, a), a]
+        })
+        .collect()
+}
+
+/// Converts premultiplied BGRA pixels into straight RGBA pixels.
+fn premultiplied_bgra_to_rgba(bgra: &[u8]) -> Vec<u8> {
+    bgra.chunks_exact(4)
+        .flat_map(|px| {
+            let [b, g, r, a] = [px[0], px[1], px[2], px[3]];
+            [
+                unpremultiply(r, a),
+                unpremultiply(g, a),
+                unpremultiply(b, a),
+                a,
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_premultiplied_conversion() {
+        let rgba = [
+            255, 0, 0, 255, // Opaque red
+            255, 255, 255, 128, // Half transparent white
+            10, 20, 30, 0, // Fully transparent
+        ];
+
+        let image = Image::from_rgba(
+            Size2D {
+                width: 3,
+                height: 1,
+            },
+            &rgba,
+        )
+        .unwrap();
+        assert_eq!(
+            image.pixels(),
+            &[
+                0, 0, 255, 255, // Opaque red
+                128, 128, 128, 128, // Half transparent white
+                0, 0, 0, 0, // Fully transparent
+            ]
+        );
+
+        assert_eq!(
+            image.to_rgba(),
+            vec![255, 0, 0, 255, 255, 255, 255, 128, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        let err = Image::from_rgba(
+            Size2D {
+                width: 2,
+                height: 2,
+            },
+            &[0; 12],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ImageError::InvalidLength {
+                expected: 16,
+                actual: 12
+            }
+        ));
+    }
+
+    #[test]
+    fn test_decode_png() {
+        let mut png = Vec::new();
+        ::image::RgbaImage::from_raw(2, 1, vec![0, 255, 0, 255, 0, 0, 255, 64])
+            .unwrap()
+            .write_to(
+                &mut ::std::io::Cursor::new(&mut png),
+                ::image::ImageFormat::Png,
+            )
+            .unwrap();
+
+        let image = Image::decode(&png).unwrap();
+        assert_eq!(
+            image.size(),
+            Size2D {
+                width: 2,
+                height: 1
+            }
+        );
+        assert_eq!(image.pixels(), &[0, 255, 0, 255, 64, 0, 0, 64]);
+    }
+
+    #[test]
+    fn test_decode_bmp() {
+        // A 1x1 24-bit uncompressed bitmap containing a single blue pixel.
+        let mut bmp = Vec::new();
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&58_u32.to_le_bytes()); // File size
+        bmp.extend_from_slice(&0_u32.to_le_bytes()); // Reserved
+        bmp.extend_from_slice(&54_u32.to_le_bytes()); // Pixel data offset
+        bmp.extend_from_slice(&40_u32.to_le_bytes()); // Header size
+        bmp.extend_from_slice(&1_i32.to_le_bytes()); // Width
+        bmp.extend_from_slice(&1_i32.to_le_bytes()); // Height
+        bmp.extend_from_slice(&1_u16.to_le_bytes()); // Planes
+        bmp.extend_from_slice(&24_u16.to_le_bytes()); // Bits per pixel
+        bmp.extend_from_slice(&[0; 24]); // Compression, sizes, palette
+        bmp.extend_from_slice(&[255, 0, 0, 0]); // BGR pixel + row padding
+
+        let image = Image::decode(&bmp).unwrap();
+        assert_eq!(
+            image.size(),
+            Size2D {
+                width: 1,
+                height: 1
+            }
+        );
+        assert_eq!(image.pixels(), &[255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_encode_png_round_trip() {
+        let image = Image::from_rgba(
+            Size2D {
+                width: 2,
+                height: 1,
+            },
+            &[255, 0, 0, 255, 0, 0, 255, 0],
+        )
+        .unwrap();
+
+        let png = image.encode_png().unwrap();
+        assert_eq!(Image::decode(&png).unwrap(), image);
+    }
+
+    #[test]
+    fn test_decode_unsupported() {
+        assert!(matches!(
+            Image::decode(b"GIF89a"),
+            Err(ImageError::UnsupportedFormat)
+        ));
+    }
+}
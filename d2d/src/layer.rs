@@ -0,0 +1,81 @@
+//! Parameters for clipping and layering drawing operations within a
+//! [`Context`](crate::Context).
+
+use crate::{brushes::Brush, geometry::PathGeometry};
+use ::win_geom::d2::{Rect2D, Transform2D};
+use ::windows::Win32::Graphics::Direct2D::{
+    D2D1_ANTIALIAS_MODE, D2D1_ANTIALIAS_MODE_ALIASED, D2D1_ANTIALIAS_MODE_PER_PRIMITIVE,
+};
+
+/// Specifies how the edges of clips, masks and primitives are rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum AntialiasMode {
+    /// Edges are antialiased using the Direct2D per-primitive method of
+    /// high-quality antialiasing.
+    #[default]
+    PerPrimitive,
+    /// Edges are aliased. This is the fastest mode and avoids seams between
+    /// adjacent pixel-aligned clips.
+    Aliased,
+}
+
+impl From<AntialiasMode> for D2D1_ANTIALIAS_MODE {
+    fn from(mode: AntialiasMode) -> Self {
+        match mode {
+            AntialiasMode::PerPrimitive => D2D1_ANTIALIAS_MODE_PER_PRIMITIVE,
+            AntialiasMode::Aliased => D2D1_ANTIALIAS_MODE_ALIASED,
+        }
+    }
+}
+
+/// Describes a layer pushed with
+/// [`Context::push_layer`](crate::Context::push_layer).
+///
+/// All drawing within the layer is composited back onto the render target
+/// when the layer is popped, clipped to the content bounds and geometric mask
+/// and faded by the opacity and opacity brush.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::LayerParameters;
+///
+/// // Fade in a panel at half opacity.
+/// let params = LayerParameters {
+///     opacity: 0.5,
+///     ..Default::default()
+/// };
+/// ```
+pub struct LayerParameters<'a> {
+    /// The bounds of the layer's content. Drawing outside these bounds is
+    /// clipped. `None` leaves the layer unbounded.
+    pub content_bounds: Option<Rect2D<f32>>,
+    /// An optional geometry which masks the layer's content. Only content
+    /// within the geometry's fill is drawn.
+    pub mask: Option<&'a PathGeometry>,
+    /// How the edges of the geometric mask are rendered.
+    pub mask_antialias: AntialiasMode,
+    /// A transform applied to the geometric mask, in addition to the
+    /// context's world transform.
+    pub mask_transform: Transform2D,
+    /// A value between 0.0 and 1.0 which is multiplied against the layer's
+    /// content when it is composited.
+    pub opacity: f32,
+    /// An optional brush whose alpha channel is multiplied against the layer's
+    /// content, e.g. a gradient brush for a fade-out edge.
+    pub opacity_brush: Option<&'a mut dyn Brush>,
+}
+
+impl<'a> Default for LayerParameters<'a> {
+    fn default() -> Self {
+        Self {
+            content_bounds: None,
+            mask: None,
+            mask_antialias: AntialiasMode::default(),
+            mask_transform: Transform2D::identity(),
+            opacity: 1.0,
+            opacity_brush: None,
+        }
+    }
+}
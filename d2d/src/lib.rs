@@ -15,14 +15,53 @@
     )
 )]
 
+mod atlas;
+mod backend;
+mod capture;
 mod color;
 mod context;
+mod draw_list;
+mod errors;
 mod factory;
+mod geometry;
+mod gradient;
+mod imaging;
+mod layer;
+mod options;
+mod palette;
+mod path;
 mod resources;
+mod snap;
+pub mod snapshot;
+mod software;
+mod sprites;
+mod stats;
+mod stroke;
+mod svg;
 mod target;
+mod text;
 
+pub use atlas::*;
+pub use backend::*;
+pub use capture::*;
 pub use color::*;
 pub use context::*;
+pub use draw_list::*;
+pub use errors::*;
 pub use factory::*;
+pub use geometry::*;
+pub use gradient::*;
+pub use imaging::*;
+pub use layer::*;
+pub use options::*;
+pub use palette::*;
+pub use path::*;
 pub use resources::*;
+pub use snap::*;
+pub use software::*;
+pub use sprites::*;
+pub use stats::*;
+pub use stroke::*;
+pub use svg::*;
 pub use target::*;
+pub use text::*;
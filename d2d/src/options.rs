@@ -0,0 +1,320 @@
+//! Options from which a [`RenderTarget`](crate::RenderTarget) creates its
+//! device render target, and the modes with which drawing is rendered.
+
+use crate::layer::AntialiasMode;
+use ::win32::window::DPI;
+use ::windows::Win32::Graphics::Direct2D::{
+    Common::{
+        D2D1_ALPHA_MODE, D2D1_ALPHA_MODE_IGNORE, D2D1_ALPHA_MODE_PREMULTIPLIED, D2D1_PIXEL_FORMAT,
+    },
+    D2D1_FEATURE_LEVEL, D2D1_FEATURE_LEVEL_10, D2D1_FEATURE_LEVEL_9, D2D1_FEATURE_LEVEL_DEFAULT,
+    D2D1_PRESENT_OPTIONS, D2D1_PRESENT_OPTIONS_IMMEDIATELY, D2D1_PRESENT_OPTIONS_NONE,
+    D2D1_PRIMITIVE_BLEND, D2D1_PRIMITIVE_BLEND_ADD, D2D1_PRIMITIVE_BLEND_COPY,
+    D2D1_PRIMITIVE_BLEND_MAX, D2D1_PRIMITIVE_BLEND_MIN, D2D1_PRIMITIVE_BLEND_SOURCE_OVER,
+    D2D1_RENDER_TARGET_PROPERTIES, D2D1_RENDER_TARGET_TYPE, D2D1_RENDER_TARGET_TYPE_DEFAULT,
+    D2D1_RENDER_TARGET_TYPE_HARDWARE, D2D1_RENDER_TARGET_TYPE_SOFTWARE, D2D1_TEXT_ANTIALIAS_MODE,
+    D2D1_TEXT_ANTIALIAS_MODE_ALIASED, D2D1_TEXT_ANTIALIAS_MODE_CLEARTYPE,
+    D2D1_TEXT_ANTIALIAS_MODE_DEFAULT, D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE,
+};
+
+/// Options for creating a [`RenderTarget`](crate::RenderTarget), as passed to
+/// [`D2DFactory::make_render_target_with_options`](crate::D2DFactory::make_render_target_with_options).
+///
+/// The defaults require a DirectX 10 capable graphics card, present in sync
+/// with the display's refresh and antialias each primitive.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{AntialiasMode, PresentMode, RenderTargetOptions, RenderingMode};
+///
+/// // A pixel-art game which renders as fast as it can.
+/// let options = RenderTargetOptions::new()
+///     .with_present_mode(PresentMode::Immediate)
+///     .with_rendering_mode(RenderingMode {
+///         antialias: AntialiasMode::Aliased,
+///         ..Default::default()
+///     });
+///
+/// assert_eq!(options.present_mode(), PresentMode::Immediate);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct RenderTargetOptions {
+    target_type: RenderTargetType,
+    alpha_mode: AlphaMode,
+    present_mode: PresentMode,
+    min_feature_level: FeatureLevel,
+    rendering_mode: RenderingMode,
+}
+
+impl RenderTargetOptions {
+    /// Constructs the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether rendering uses the graphics card or the CPU.
+    pub fn with_target_type(mut self, target_type: RenderTargetType) -> Self {
+        self.target_type = target_type;
+        self
+    }
+
+    /// Sets how the alpha channel of the window's contents is treated.
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Sets when each frame is presented.
+    pub fn with_present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Sets the minimum feature level a graphics card must support to be
+    /// rendered with.
+    pub fn with_min_feature_level(mut self, min_feature_level: FeatureLevel) -> Self {
+        self.min_feature_level = min_feature_level;
+        self
+    }
+
+    /// Sets the rendering mode with which each frame begins.
+    pub fn with_rendering_mode(mut self, rendering_mode: RenderingMode) -> Self {
+        self.rendering_mode = rendering_mode;
+        self
+    }
+
+    /// Whether rendering uses the graphics card or the CPU.
+    pub fn target_type(&self) -> RenderTargetType {
+        self.target_type
+    }
+
+    /// How the alpha channel of the window's contents is treated.
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    /// When each frame is presented.
+    pub fn present_mode(&self) -> PresentMode {
+        self.present_mode
+    }
+
+    /// The minimum feature level a graphics card must support to be rendered
+    /// with.
+    pub fn min_feature_level(&self) -> FeatureLevel {
+        self.min_feature_level
+    }
+
+    /// The rendering mode with which each frame begins.
+    pub fn rendering_mode(&self) -> RenderingMode {
+        self.rendering_mode
+    }
+
+    /// The properties of a device render target created at `dpi`.
+    pub(crate) fn render_target_properties(&self, dpi: DPI) -> D2D1_RENDER_TARGET_PROPERTIES {
+        D2D1_RENDER_TARGET_PROPERTIES {
+            r#type: self.target_type.into(),
+            pixelFormat: D2D1_PIXEL_FORMAT {
+                alphaMode: self.alpha_mode.into(),
+                ..Default::default()
+            },
+            dpiX: dpi.into(),
+            dpiY: dpi.into(),
+            minLevel: self.min_feature_level.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Whether a render target renders with the graphics card or the CPU.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RenderTargetType {
+    /// Render with the graphics card, failing if none is capable of the
+    /// [minimum feature level](RenderTargetOptions::with_min_feature_level).
+    #[default]
+    Hardware,
+    /// Render with the CPU. This is slower, but works without a capable
+    /// graphics card, e.g. on a remote desktop or in a virtual machine.
+    Software,
+    /// Render with the graphics card when capable, or else with the CPU.
+    Auto,
+}
+
+impl From<RenderTargetType> for D2D1_RENDER_TARGET_TYPE {
+    fn from(target_type: RenderTargetType) -> Self {
+        match target_type {
+            RenderTargetType::Hardware => D2D1_RENDER_TARGET_TYPE_HARDWARE,
+            RenderTargetType::Software => D2D1_RENDER_TARGET_TYPE_SOFTWARE,
+            RenderTargetType::Auto => D2D1_RENDER_TARGET_TYPE_DEFAULT,
+        }
+    }
+}
+
+/// How the alpha channel of a render target's contents is treated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    /// Colors are premultiplied by their alpha, which is kept.
+    Premultiplied,
+    /// The alpha channel is ignored and the contents are opaque. This is how
+    /// Direct2D treats a window's render target unless told otherwise.
+    #[default]
+    Ignore,
+}
+
+impl From<AlphaMode> for D2D1_ALPHA_MODE {
+    fn from(alpha_mode: AlphaMode) -> Self {
+        match alpha_mode {
+            AlphaMode::Premultiplied => D2D1_ALPHA_MODE_PREMULTIPLIED,
+            AlphaMode::Ignore => D2D1_ALPHA_MODE_IGNORE,
+        }
+    }
+}
+
+/// When a window's render target presents each frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PresentMode {
+    /// Wait for the display's vertical refresh, which limits the frame rate to
+    /// the refresh rate and avoids tearing.
+    #[default]
+    VSync,
+    /// Present as soon as each frame is drawn, which may tear.
+    Immediate,
+}
+
+impl From<PresentMode> for D2D1_PRESENT_OPTIONS {
+    fn from(present_mode: PresentMode) -> Self {
+        match present_mode {
+            PresentMode::VSync => D2D1_PRESENT_OPTIONS_NONE,
+            PresentMode::Immediate => D2D1_PRESENT_OPTIONS_IMMEDIATELY,
+        }
+    }
+}
+
+/// The Direct3D feature level a graphics card must support to be rendered
+/// with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FeatureLevel {
+    /// Any graphics card capable of rendering.
+    Any,
+    /// A DirectX 9 capable graphics card.
+    Level9,
+    /// A DirectX 10 capable graphics card.
+    #[default]
+    Level10,
+}
+
+impl From<FeatureLevel> for D2D1_FEATURE_LEVEL {
+    fn from(level: FeatureLevel) -> Self {
+        match level {
+            FeatureLevel::Any => D2D1_FEATURE_LEVEL_DEFAULT,
+            FeatureLevel::Level9 => D2D1_FEATURE_LEVEL_9,
+            FeatureLevel::Level10 => D2D1_FEATURE_LEVEL_10,
+        }
+    }
+}
+
+/// How drawing is antialiased and blended, as set for a frame with
+/// [`Context::set_rendering_mode`](crate::Context::set_rendering_mode).
+///
+/// Each frame begins with the mode given by
+/// [`RenderTargetOptions::with_rendering_mode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct RenderingMode {
+    /// How the edges of primitives are rendered. Aliasing them keeps
+    /// pixel-art crisp.
+    pub antialias: AntialiasMode,
+    /// How text is antialiased.
+    pub text_antialias: TextAntialiasMode,
+    /// How primitives are blended with what was drawn before them.
+    pub primitive_blend: PrimitiveBlend,
+}
+
+/// Specifies how the edges of text are rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TextAntialiasMode {
+    /// Use the system's setting, which is usually ClearType.
+    #[default]
+    System,
+    /// Antialias with ClearType, which renders at sub-pixel precision on LCD
+    /// displays but requires an opaque background.
+    ClearType,
+    /// Antialias in grayscale, which suits text drawn over transparency.
+    Grayscale,
+    /// Render text without antialiasing.
+    Aliased,
+}
+
+impl From<TextAntialiasMode> for D2D1_TEXT_ANTIALIAS_MODE {
+    fn from(mode: TextAntialiasMode) -> Self {
+        match mode {
+            TextAntialiasMode::System => D2D1_TEXT_ANTIALIAS_MODE_DEFAULT,
+            TextAntialiasMode::ClearType => D2D1_TEXT_ANTIALIAS_MODE_CLEARTYPE,
+            TextAntialiasMode::Grayscale => D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE,
+            TextAntialiasMode::Aliased => D2D1_TEXT_ANTIALIAS_MODE_ALIASED,
+        }
+    }
+}
+
+/// Specifies how a primitive's colors are combined with those already drawn
+/// beneath it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PrimitiveBlend {
+    /// Composite the primitive over what is beneath, according to its alpha.
+    #[default]
+    SourceOver,
+    /// Replace what is beneath with the primitive, including its alpha.
+    Copy,
+    /// Keep the minimum of each channel.
+    Min,
+    /// Add each channel, e.g. for glowing particles.
+    Add,
+    /// Keep the maximum of each channel.
+    Max,
+}
+
+impl From<PrimitiveBlend> for D2D1_PRIMITIVE_BLEND {
+    fn from(blend: PrimitiveBlend) -> Self {
+        match blend {
+            PrimitiveBlend::SourceOver => D2D1_PRIMITIVE_BLEND_SOURCE_OVER,
+            PrimitiveBlend::Copy => D2D1_PRIMITIVE_BLEND_COPY,
+            PrimitiveBlend::Min => D2D1_PRIMITIVE_BLEND_MIN,
+            PrimitiveBlend::Add => D2D1_PRIMITIVE_BLEND_ADD,
+            PrimitiveBlend::Max => D2D1_PRIMITIVE_BLEND_MAX,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_default_properties_match_previous_behavior() {
+        let props = RenderTargetOptions::new().render_target_properties(DPI::from(144.0));
+
+        assert_eq!(props.r#type, D2D1_RENDER_TARGET_TYPE_HARDWARE);
+        assert_eq!(props.minLevel, D2D1_FEATURE_LEVEL_10);
+        assert_eq!((props.dpiX, props.dpiY), (144.0, 144.0));
+        assert_eq!(props.pixelFormat.alphaMode, D2D1_ALPHA_MODE_IGNORE);
+        assert_eq!(
+            D2D1_PRESENT_OPTIONS::from(PresentMode::default()),
+            D2D1_PRESENT_OPTIONS_NONE
+        );
+    }
+
+    #[test]
+    fn test_builder_sets_properties() {
+        let options = RenderTargetOptions::new()
+            .with_target_type(RenderTargetType::Software)
+            .with_alpha_mode(AlphaMode::Premultiplied)
+            .with_min_feature_level(FeatureLevel::Any);
+        let props = options.render_target_properties(DPI::from(96.0));
+
+        assert_eq!(props.r#type, D2D1_RENDER_TARGET_TYPE_SOFTWARE);
+        assert_eq!(props.pixelFormat.alphaMode, D2D1_ALPHA_MODE_PREMULTIPLIED);
+        assert_eq!(props.minLevel, D2D1_FEATURE_LEVEL_DEFAULT);
+        assert_eq!(options.rendering_mode(), RenderingMode::default());
+    }
+}
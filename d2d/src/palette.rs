@@ -0,0 +1,436 @@
+//! Semantic color palettes which map UI roles (background, text, accent, etc.)
+//! onto concrete colors, with built-in light and dark variants to match the
+//! window [`Theme`].
+
+use crate::{brushes::SolidColorBrush, color::Color, target::RenderTarget};
+use ::win32::window::Theme;
+
+/// A semantic role which a color plays within a UI. Roles decouple drawing
+/// code from concrete colors so that the same drawing code renders correctly in
+/// both light and dark mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// The window or canvas background.
+    Background,
+    /// Raised surfaces drawn over the background, such as panels and cards.
+    Surface,
+    /// Primary foreground content such as text and glyphs.
+    Text,
+    /// The accent color used for highlights and primary actions.
+    Accent,
+    /// Outlines, dividers and grid lines.
+    Border,
+    /// Accent shade for elements under the mouse cursor.
+    Hover,
+    /// Accent shade for elements which are actively pressed.
+    Pressed,
+    /// Foreground content for elements which are disabled.
+    Disabled,
+}
+
+impl Role {
+    /// All roles, in declaration order.
+    pub const ALL: [Role; 8] = [
+        Role::Background,
+        Role::Surface,
+        Role::Text,
+        Role::Accent,
+        Role::Border,
+        Role::Hover,
+        Role::Pressed,
+        Role::Disabled,
+    ];
+}
+
+/// A set of colors for each semantic [`Role`].
+///
+/// Use [`Palette::light`] or [`Palette::dark`] for the built-in palettes,
+/// [`Palette::from_accent`] to derive a palette from a single brand color, or
+/// (with the `toml` or `json` features) load a palette from a document.
+///
+/// # Serialization
+///
+/// With the `serde` feature enabled, palettes serialize as a `theme` (`"light"`
+/// or `"dark"`, inferred from the background) plus each role as a hex color
+/// string. When deserializing, only `theme` and `accent` are required. Any
+/// omitted role is derived as per [`Palette::from_accent`]:
+///
+/// ```toml
+/// theme = "dark"
+/// accent = "#FF8C00"
+/// border = "#505050"
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(::serde::Serialize, ::serde::Deserialize),
+    serde(from = "spec::PaletteSpec", into = "spec::PaletteSpec")
+)]
+pub struct Palette {
+    /// The color for [`Role::Background`].
+    pub background: Color,
+    /// The color for [`Role::Surface`].
+    pub surface: Color,
+    /// The color for [`Role::Text`].
+    pub text: Color,
+    /// The color for [`Role::Accent`].
+    pub accent: Color,
+    /// The color for [`Role::Border`].
+    pub border: Color,
+    /// The color for [`Role::Hover`].
+    pub hover: Color,
+    /// The color for [`Role::Pressed`].
+    pub pressed: Color,
+    /// The color for [`Role::Disabled`].
+    pub disabled: Color,
+}
+
+impl Palette {
+    /// The default accent color for both built-in palettes.
+    const DEFAULT_ACCENT: u32 = 0xFF0078D4;
+
+    /// The built-in light mode palette.
+    pub fn light() -> Self {
+        Self::from_accent(Theme::LightMode, Color::new_argb(Self::DEFAULT_ACCENT))
+    }
+
+    /// The built-in dark mode palette.
+    pub fn dark() -> Self {
+        Self::from_accent(Theme::DarkMode, Color::new_argb(Self::DEFAULT_ACCENT))
+    }
+
+    /// The built-in palette which matches `theme`.
+    pub fn for_theme(theme: Theme) -> Self {
+        match theme {
+            Theme::LightMode => Self::light(),
+            Theme::DarkMode => Self::dark(),
+        }
+    }
+
+    /// Derives a full palette for `theme` from a single `accent` color.
+    ///
+    /// The neutral roles (background, surface, text, border) are fixed for the
+    /// theme. [`Role::Hover`] and [`Role::Pressed`] are progressively darker
+    /// shades of the accent in light mode and lighter shades in dark mode, so
+    /// that interaction states always move away from the background.
+    /// [`Role::Disabled`] is the text color faded towards the background.
+    pub fn from_accent(theme: Theme, accent: Color) -> Self {
+        let (background, surface, text, border) = match theme {
+            Theme::LightMode => (
+                Color::new_argb(0xFFFFFFFF),
+                Color::new_argb(0xFFF3F3F3),
+                Color::new_argb(0xFF1B1B1B),
+                Color::new_argb(0xFFD1D1D1),
+            ),
+            Theme::DarkMode => (
+                Color::new_argb(0xFF202020),
+                Color::new_argb(0xFF2D2D2D),
+                Color::new_argb(0xFFFFFFFF),
+                Color::new_argb(0xFF454545),
+            ),
+        };
+
+        let shade = |amount: f32| match theme {
+            Theme::LightMode => accent.darken(amount),
+            Theme::DarkMode => accent.lighten(amount),
+        };
+
+        Self {
+            background,
+            surface,
+            text,
+            accent,
+            border,
+            hover: shade(0.15),
+            pressed: shade(0.30),
+            disabled: text.lerp(background, 0.6),
+        }
+    }
+
+    /// The color assigned to `role`.
+    pub fn color(&self, role: Role) -> Color {
+        match role {
+            Role::Background => self.background,
+            Role::Surface => self.surface,
+            Role::Text => self.text,
+            Role::Accent => self.accent,
+            Role::Border => self.border,
+            Role::Hover => self.hover,
+            Role::Pressed => self.pressed,
+            Role::Disabled => self.disabled,
+        }
+    }
+
+    /// Loads a palette from a TOML document. See the [`Palette`] docs for the
+    /// expected format.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(doc: &str) -> Result<Self, ::toml::de::Error> {
+        ::toml::from_str(doc)
+    }
+
+    /// Loads a palette from a JSON document. See the [`Palette`] docs for the
+    /// expected format.
+    #[cfg(feature = "json")]
+    pub fn from_json(doc: &str) -> Result<Self, ::serde_json::Error> {
+        ::serde_json::from_str(doc)
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+/// A set of device brushes, one for each [`Role`] of a [`Palette`].
+///
+/// Switching the palette or theme re-resolves every brush in one go so that
+/// drawing code can simply ask for a role's brush each frame.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ::windows::Win32::Foundation::HWND;
+/// # use ::win_geom::d2::Size2D;
+/// use ::d2d::{D2DFactory, Palette, PaletteBrushes, Role};
+/// use ::win32::window::Theme;
+///
+/// # let factory = D2DFactory::new()?;
+/// # let mut render_target = factory.make_render_target(
+/// #     HWND(0),
+/// #     Size2D { width: 100, height: 100 },
+/// # );
+/// let mut brushes = PaletteBrushes::new(&mut render_target, Palette::light())?;
+///
+/// // The user switched the system to dark mode.
+/// brushes.set_theme(&mut render_target, Theme::DarkMode)?;
+///
+/// let mut ctx = render_target.begin_draw()?;
+/// ctx.clear(brushes.palette().background);
+/// // ...draw using `brushes.brush(Role::Accent)` etc.
+/// ctx.end_draw()?;
+/// # Ok::<(), ::d2d::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PaletteBrushes {
+    /// The palette from which the brushes were resolved.
+    palette: Palette,
+    /// One brush per role, indexed by the role's declaration order.
+    brushes: Vec<SolidColorBrush>,
+}
+
+impl PaletteBrushes {
+    /// Creates a brush for every role in `palette`.
+    pub fn new(render_target: &mut RenderTarget, palette: Palette) -> crate::Result<Self> {
+        let brushes = Self::resolve(render_target, &palette)?;
+        Ok(Self { palette, brushes })
+    }
+
+    /// The palette from which the brushes were resolved.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Replaces the palette and re-resolves every brush. On failure the
+    /// current palette and brushes are kept.
+    pub fn set_palette(
+        &mut self,
+        render_target: &mut RenderTarget,
+        palette: Palette,
+    ) -> crate::Result<()> {
+        self.brushes = Self::resolve(render_target, &palette)?;
+        self.palette = palette;
+        Ok(())
+    }
+
+    /// Re-derives the palette for `theme`, keeping the current accent color,
+    /// and re-resolves every brush. Any individually customized roles are
+    /// replaced by their derived values.
+    pub fn set_theme(
+        &mut self,
+        render_target: &mut RenderTarget,
+        theme: Theme,
+    ) -> crate::Result<()> {
+        let palette = Palette::from_accent(theme, self.palette.accent);
+        self.set_palette(render_target, palette)
+    }
+
+    /// The brush for `role`.
+    pub fn brush(&mut self, role: Role) -> &mut SolidColorBrush {
+        &mut self.brushes[role as usize]
+    }
+
+    fn resolve(
+        render_target: &mut RenderTarget,
+        palette: &Palette,
+    ) -> crate::Result<Vec<SolidColorBrush>> {
+        Role::ALL
+            .iter()
+            .map(|role| render_target.make_solid_color_brush(palette.color(*role)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod spec {
+    use super::Palette;
+    use crate::color::Color;
+    use ::serde::{Deserialize, Serialize};
+    use ::win32::window::Theme;
+
+    /// The on-disk form of a [`Palette`]. Only the theme and accent are
+    /// required, every other role falls back to its derived value.
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub(super) struct PaletteSpec {
+        theme: ThemeSpec,
+        accent: Color,
+        background: Option<Color>,
+        surface: Option<Color>,
+        text: Option<Color>,
+        border: Option<Color>,
+        hover: Option<Color>,
+        pressed: Option<Color>,
+        disabled: Option<Color>,
+    }
+
+    /// Serializable mirror of [`Theme`].
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum ThemeSpec {
+        Light,
+        Dark,
+    }
+
+    impl From<PaletteSpec> for Palette {
+        fn from(spec: PaletteSpec) -> Self {
+            let theme = match spec.theme {
+                ThemeSpec::Light => Theme::LightMode,
+                ThemeSpec::Dark => Theme::DarkMode,
+            };
+            let derived = Palette::from_accent(theme, spec.accent);
+
+            Self {
+                background: spec.background.unwrap_or(derived.background),
+                surface: spec.surface.unwrap_or(derived.surface),
+                text: spec.text.unwrap_or(derived.text),
+                accent: spec.accent,
+                border: spec.border.unwrap_or(derived.border),
+                hover: spec.hover.unwrap_or(derived.hover),
+                pressed: spec.pressed.unwrap_or(derived.pressed),
+                disabled: spec.disabled.unwrap_or(derived.disabled),
+            }
+        }
+    }
+
+    impl From<Palette> for PaletteSpec {
+        fn from(palette: Palette) -> Self {
+            Self {
+                theme: if palette.background.is_dark() {
+                    ThemeSpec::Dark
+                } else {
+                    ThemeSpec::Light
+                },
+                accent: palette.accent,
+                background: Some(palette.background),
+                surface: Some(palette.surface),
+                text: Some(palette.text),
+                border: Some(palette.border),
+                hover: Some(palette.hover),
+                pressed: Some(palette.pressed),
+                disabled: Some(palette.disabled),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_role_lookup_matches_fields() {
+        let palette = Palette::dark();
+        let colors: Vec<_> = Role::ALL.iter().map(|r| palette.color(*r)).collect();
+
+        assert_eq!(
+            colors,
+            vec![
+                palette.background,
+                palette.surface,
+                palette.text,
+                palette.accent,
+                palette.border,
+                palette.hover,
+                palette.pressed,
+                palette.disabled,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shades_move_away_from_background() {
+        let accent = Color::new_argb(0xFF808080);
+
+        let light = Palette::from_accent(Theme::LightMode, accent);
+        assert!(light.hover.red < accent.red);
+        assert!(light.pressed.red < light.hover.red);
+
+        let dark = Palette::from_accent(Theme::DarkMode, accent);
+        assert!(dark.hover.red > accent.red);
+        assert!(dark.pressed.red > dark.hover.red);
+    }
+
+    #[test]
+    fn test_builtin_palettes_contrast() {
+        assert!(Palette::light().background.is_light());
+        assert!(Palette::light().text.is_dark());
+        assert!(Palette::dark().background.is_dark());
+        assert!(Palette::dark().text.is_light());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml_derives_missing_roles() {
+        let palette = Palette::from_toml(
+            r##"
+            theme = "dark"
+            accent = "#FF8C00"
+            border = "#505050"
+            "##,
+        )
+        .unwrap();
+
+        let derived = Palette::from_accent(Theme::DarkMode, Color::new_argb(0xFFFF8C00));
+        assert_eq!(palette.border, Color::new_argb(0xFF505050));
+        assert_eq!(palette.hover, derived.hover);
+        assert_eq!(palette.background, derived.background);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_json() {
+        let palette = Palette::from_json(r##"{ "theme": "light", "accent": "#0078D4" }"##).unwrap();
+        assert_eq!(palette, Palette::light());
+
+        assert!(Palette::from_json(r##"{ "theme": "light" }"##).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip() {
+        // Colors are serialized at byte precision, so start from a palette
+        // which is already byte-aligned.
+        let palette = Palette {
+            hover: Color::new(1, 2, 3, 255),
+            pressed: Color::new(4, 5, 6, 255),
+            disabled: Color::new(7, 8, 9, 255),
+            ..Palette::dark()
+        };
+
+        let json = ::serde_json::to_string(&palette).unwrap();
+        assert_eq!(Palette::from_json(&json).unwrap(), palette);
+    }
+}
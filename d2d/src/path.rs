@@ -0,0 +1,386 @@
+//! CPU-side path descriptions made up of lines, Bézier curves and elliptical
+//! arcs.
+//!
+//! A [`Path`] is a plain description which can be built and inspected without a
+//! factory or render target. It is turned into a drawable
+//! [`PathGeometry`](crate::PathGeometry) by a
+//! [`D2DFactory`](crate::D2DFactory).
+
+use crate::svg::{parse_path_data, PathDataError};
+use ::win_geom::d2::{Point2D, Size2D};
+use ::windows::Win32::Graphics::Direct2D::{
+    Common::{D2D1_FILL_MODE, D2D1_FILL_MODE_ALTERNATE, D2D1_FILL_MODE_WINDING},
+    D2D1_ARC_SEGMENT, D2D1_ARC_SIZE, D2D1_ARC_SIZE_LARGE, D2D1_ARC_SIZE_SMALL,
+    D2D1_SWEEP_DIRECTION, D2D1_SWEEP_DIRECTION_CLOCKWISE, D2D1_SWEEP_DIRECTION_COUNTER_CLOCKWISE,
+};
+
+/// Specifies how the intersecting areas of a path are combined to form the
+/// area to be filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum FillMode {
+    /// A point is inside the path if a ray drawn from the point to infinity
+    /// crosses an odd number of segments (the "even-odd" rule).
+    #[default]
+    Alternate,
+    /// A point is inside the path if the segments crossed by a ray drawn from
+    /// the point to infinity do not wind around it an equal number of times in
+    /// each direction (the "non-zero" rule).
+    Winding,
+}
+
+impl From<FillMode> for D2D1_FILL_MODE {
+    fn from(mode: FillMode) -> Self {
+        match mode {
+            FillMode::Alternate => D2D1_FILL_MODE_ALTERNATE,
+            FillMode::Winding => D2D1_FILL_MODE_WINDING,
+        }
+    }
+}
+
+/// The direction in which an arc is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum SweepDirection {
+    /// Arcs are drawn counterclockwise (negative-angle) direction.
+    CounterClockwise,
+    /// Arcs are drawn in a clockwise (positive-angle) direction.
+    #[default]
+    Clockwise,
+}
+
+impl From<SweepDirection> for D2D1_SWEEP_DIRECTION {
+    fn from(sweep: SweepDirection) -> Self {
+        match sweep {
+            SweepDirection::CounterClockwise => D2D1_SWEEP_DIRECTION_COUNTER_CLOCKWISE,
+            SweepDirection::Clockwise => D2D1_SWEEP_DIRECTION_CLOCKWISE,
+        }
+    }
+}
+
+/// Whether an arc should take the shorter or the longer of the two possible
+/// routes between its end points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum ArcSize {
+    /// An arc's sweep should be 180 degrees or less.
+    #[default]
+    Small,
+    /// An arc's sweep should be 180 degrees or greater.
+    Large,
+}
+
+impl From<ArcSize> for D2D1_ARC_SIZE {
+    fn from(size: ArcSize) -> Self {
+        match size {
+            ArcSize::Small => D2D1_ARC_SIZE_SMALL,
+            ArcSize::Large => D2D1_ARC_SIZE_LARGE,
+        }
+    }
+}
+
+/// An elliptical arc from the current point of a path to `to`.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct ArcSegment {
+    /// The end point of the arc.
+    pub to: Point2D<f32>,
+    /// The x and y radii of the ellipse from which the arc is taken.
+    pub radius: Size2D<f32>,
+    /// The clockwise rotation of the ellipse's x axis, in radians.
+    pub rotation: f32,
+    /// The direction in which the arc is swept.
+    pub sweep: SweepDirection,
+    /// Whether the arc takes the shorter or longer route.
+    pub arc_size: ArcSize,
+}
+
+impl From<ArcSegment> for D2D1_ARC_SEGMENT {
+    fn from(arc: ArcSegment) -> Self {
+        Self {
+            point: arc.to.into(),
+            size: arc.radius.into(),
+            rotationAngle: arc.rotation.to_degrees(),
+            sweepDirection: arc.sweep.into(),
+            arcSize: arc.arc_size.into(),
+        }
+    }
+}
+
+/// A single command within a [`Path`].
+///
+/// Every figure in a path starts with a [`MoveTo`](Self::MoveTo) and is
+/// either terminated by a [`Close`](Self::Close) or left open by the next
+/// [`MoveTo`](Self::MoveTo) or the end of the path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum PathSegment {
+    /// Begins a new figure at the given point.
+    MoveTo(Point2D<f32>),
+    /// A straight line from the current point.
+    LineTo(Point2D<f32>),
+    /// A quadratic Bézier curve from the current point.
+    QuadTo {
+        /// The control point.
+        ctrl: Point2D<f32>,
+        /// The end point.
+        to: Point2D<f32>,
+    },
+    /// A cubic Bézier curve from the current point.
+    CubicTo {
+        /// The first control point.
+        ctrl1: Point2D<f32>,
+        /// The second control point.
+        ctrl2: Point2D<f32>,
+        /// The end point.
+        to: Point2D<f32>,
+    },
+    /// An elliptical arc from the current point.
+    ArcTo(ArcSegment),
+    /// Closes the current figure with a straight line back to its start.
+    Close,
+}
+
+/// A sequence of figures made up of lines, Bézier curves and arcs.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{FillMode, Path};
+/// use ::win_geom::d2::Point2D;
+///
+/// // A right-pointing arrow head.
+/// let mut arrow = Path::new().with_fill_mode(FillMode::Winding);
+/// arrow
+///     .move_to(Point2D { x: 0.0, y: 0.0 })
+///     .line_to(Point2D { x: 10.0, y: 5.0 })
+///     .line_to(Point2D { x: 0.0, y: 10.0 })
+///     .close();
+///
+/// assert_eq!(arrow.segments().len(), 4);
+/// assert_eq!(arrow.current_point(), Some(Point2D { x: 0.0, y: 0.0 }));
+/// ```
+#[derive(Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize))]
+pub struct Path {
+    segments: Vec<PathSegment>,
+    fill_mode: FillMode,
+    /// The start of the current (or most recently closed) figure.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    figure_start: Option<Point2D<f32>>,
+    /// The end point of the last segment.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    current_point: Option<Point2D<f32>>,
+    /// Whether a figure is open and can accept further segments.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    in_figure: bool,
+}
+
+impl Path {
+    /// Constructs a new, empty path.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses SVG path data, the value of the `d` attribute of a `<path>`, e.g.
+    /// `"M0 0 h10 v10 z"`. All commands are supported, both absolute and
+    /// relative.
+    ///
+    /// The path has the [`Winding`](FillMode::Winding) fill mode, which is
+    /// SVG's default fill rule.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ::d2d::{Path, PathSegment};
+    /// use ::win_geom::d2::Point2D;
+    ///
+    /// let path = Path::from_svg("M1 2 l3 0")?;
+    ///
+    /// assert_eq!(path.segments()[1], PathSegment::LineTo(Point2D { x: 4.0, y: 2.0 }));
+    /// # Ok::<(), ::d2d::PathDataError>(())
+    /// ```
+    pub fn from_svg(data: &str) -> Result<Self, PathDataError> {
+        parse_path_data(data)
+    }
+
+    /// Sets how the path's intersecting areas are filled.
+    pub fn with_fill_mode(mut self, fill_mode: FillMode) -> Self {
+        self.fill_mode = fill_mode;
+        self
+    }
+
+    /// How the path's intersecting areas are filled.
+    pub fn fill_mode(&self) -> FillMode {
+        self.fill_mode
+    }
+
+    /// The segments which make up the path.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Whether the path contains no segments.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// The point at which the next segment will start, if any segments have
+    /// been added. After [`close`](Self::close), this is the start of the
+    /// closed figure.
+    pub fn current_point(&self) -> Option<Point2D<f32>> {
+        self.current_point
+    }
+
+    /// Begins a new figure at `to`, leaving any current figure open.
+    pub fn move_to(&mut self, to: Point2D<f32>) -> &mut Self {
+        self.segments.push(PathSegment::MoveTo(to));
+        self.figure_start = Some(to);
+        self.current_point = Some(to);
+        self.in_figure = true;
+        self
+    }
+
+    /// Adds a straight line from the current point to `to`.
+    pub fn line_to(&mut self, to: Point2D<f32>) -> &mut Self {
+        self.push_drawing(PathSegment::LineTo(to), to)
+    }
+
+    /// Adds a quadratic Bézier curve from the current point to `to`.
+    pub fn quad_to(&mut self, ctrl: Point2D<f32>, to: Point2D<f32>) -> &mut Self {
+        self.push_drawing(PathSegment::QuadTo { ctrl, to }, to)
+    }
+
+    /// Adds a cubic Bézier curve from the current point to `to`.
+    pub fn cubic_to(
+        &mut self,
+        ctrl1: Point2D<f32>,
+        ctrl2: Point2D<f32>,
+        to: Point2D<f32>,
+    ) -> &mut Self {
+        self.push_drawing(PathSegment::CubicTo { ctrl1, ctrl2, to }, to)
+    }
+
+    /// Adds an elliptical arc from the current point to `arc.to`.
+    pub fn arc_to(&mut self, arc: ArcSegment) -> &mut Self {
+        self.push_drawing(PathSegment::ArcTo(arc), arc.to)
+    }
+
+    /// Closes the current figure with a straight line back to its start. Has
+    /// no effect if there is no open figure.
+    pub fn close(&mut self) -> &mut Self {
+        if self.in_figure {
+            self.segments.push(PathSegment::Close);
+            self.current_point = self.figure_start;
+            self.in_figure = false;
+        }
+        self
+    }
+
+    /// Appends a drawing segment, implicitly beginning a new figure at the
+    /// current point (or the origin) if none is open.
+    fn push_drawing(&mut self, segment: PathSegment, to: Point2D<f32>) -> &mut Self {
+        if !self.in_figure {
+            self.move_to(self.current_point.unwrap_or_default());
+        }
+        self.segments.push(segment);
+        self.current_point = Some(to);
+        self
+    }
+
+    /// Appends a segment using the builder method which corresponds to it.
+    pub fn push(&mut self, segment: PathSegment) -> &mut Self {
+        match segment {
+            PathSegment::MoveTo(to) => self.move_to(to),
+            PathSegment::LineTo(to) => self.line_to(to),
+            PathSegment::QuadTo { ctrl, to } => self.quad_to(ctrl, to),
+            PathSegment::CubicTo { ctrl1, ctrl2, to } => self.cubic_to(ctrl1, ctrl2, to),
+            PathSegment::ArcTo(arc) => self.arc_to(arc),
+            PathSegment::Close => self.close(),
+        }
+    }
+}
+
+impl Extend<PathSegment> for Path {
+    fn extend<I: IntoIterator<Item = PathSegment>>(&mut self, segments: I) {
+        for segment in segments {
+            self.push(segment);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use super::{FillMode, Path, PathSegment};
+    use ::serde::{Deserialize, Deserializer};
+
+    /// The serialized fields of a [`Path`]. The segments are replayed through
+    /// the path builder on deserialization so that the builder state is
+    /// restored.
+    #[derive(Deserialize)]
+    struct Fields {
+        segments: Vec<PathSegment>,
+        #[serde(default)]
+        fill_mode: FillMode,
+    }
+
+    impl<'de> Deserialize<'de> for Path {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let Fields {
+                segments,
+                fill_mode,
+            } = Fields::deserialize(deserializer)?;
+
+            let mut path = Path::new().with_fill_mode(fill_mode);
+            path.extend(segments);
+            Ok(path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    fn pt(x: f32, y: f32) -> Point2D<f32> {
+        Point2D { x, y }
+    }
+
+    #[test]
+    fn test_implicit_move_to() {
+        let mut path = Path::new();
+        path.line_to(pt(1.0, 1.0));
+
+        assert_eq!(
+            path.segments(),
+            &[
+                PathSegment::MoveTo(pt(0.0, 0.0)),
+                PathSegment::LineTo(pt(1.0, 1.0))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drawing_after_close_starts_new_figure() {
+        let mut path = Path::new();
+        path.move_to(pt(1.0, 1.0))
+            .line_to(pt(2.0, 1.0))
+            .close()
+            .close()
+            .line_to(pt(1.0, 2.0));
+
+        assert_eq!(
+            path.segments(),
+            &[
+                PathSegment::MoveTo(pt(1.0, 1.0)),
+                PathSegment::LineTo(pt(2.0, 1.0)),
+                PathSegment::Close,
+                PathSegment::MoveTo(pt(1.0, 1.0)),
+                PathSegment::LineTo(pt(1.0, 2.0)),
+            ]
+        );
+        assert_eq!(path.current_point(), Some(pt(1.0, 2.0)));
+    }
+}
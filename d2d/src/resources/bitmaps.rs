@@ -0,0 +1,307 @@
+//! Device-mapped Direct2D bitmaps for drawing images into a render target, and
+//! offscreen targets for rendering into bitmaps.
+
+use crate::{
+    capture::{read_bitmap, Capture},
+    errors::{Error, Result},
+    imaging::Image,
+    resources::DeviceResource,
+    target::RenderTarget,
+};
+use ::std::{
+    fmt::{self, Debug},
+    rc::Rc,
+};
+use ::win32::invoke::check_res;
+use ::win_geom::d2::Size2D;
+use ::windows::Win32::Graphics::Direct2D::{
+    ID2D1Bitmap, ID2D1BitmapRenderTarget, ID2D1RenderTarget, D2D1_BITMAP_INTERPOLATION_MODE,
+    D2D1_BITMAP_INTERPOLATION_MODE_LINEAR, D2D1_BITMAP_INTERPOLATION_MODE_NEAREST_NEIGHBOR,
+};
+
+/// Specifies the algorithm that is used when images are scaled or rotated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum InterpolationMode {
+    /// Use the exact color of the nearest bitmap pixel to the current rendering
+    /// pixel. Best suited to pixel art.
+    NearestNeighbor,
+    /// Interpolate a color from the four bitmap pixels that are the nearest to
+    /// the rendering pixel.
+    #[default]
+    Linear,
+}
+
+impl From<InterpolationMode> for D2D1_BITMAP_INTERPOLATION_MODE {
+    fn from(mode: InterpolationMode) -> Self {
+        match mode {
+            InterpolationMode::NearestNeighbor => D2D1_BITMAP_INTERPOLATION_MODE_NEAREST_NEIGHBOR,
+            InterpolationMode::Linear => D2D1_BITMAP_INTERPOLATION_MODE_LINEAR,
+        }
+    }
+}
+
+/// An image which has been uploaded to the device, or the contents of a
+/// [`BitmapTarget`], which can be drawn with
+/// [`Context::draw_bitmap`](crate::Context::draw_bitmap).
+///
+/// This is a device-specific resource and is tied to the [`RenderTarget`] by
+/// which it was created. Cache the bitmap and re-use on subsequent draw calls
+/// for best performance. Cloning a bitmap is cheap and shares the underlying
+/// device bitmap.
+#[derive(Clone)]
+pub struct Bitmap {
+    /// Where the bitmap's pixels came from. This is used to re-create the
+    /// bitmap internally in the event that we must re-create our device
+    /// specific resources.
+    source: Source,
+    /// A cached Direct2D device-specific bitmap. May become invalidated if the
+    /// corresponding render target is re-created.
+    device_bitmap: ID2D1Bitmap,
+    /// The generation of the render target for which this bitmap was created.
+    /// If the two generations no longer agree, the bitmap must be re-created.
+    generation: usize,
+}
+
+/// The pixels from which a [`Bitmap`] was created.
+#[derive(Clone, Debug)]
+enum Source {
+    /// A CPU-side [`Image`], shared so that cloning a bitmap's source is cheap.
+    Image(Rc<Image>),
+    /// The contents of a [`BitmapTarget`] of the given pixel size. These only
+    /// exist on the device and are lost along with it.
+    Target(Size2D<u32>),
+}
+
+impl Bitmap {
+    /// A crate-private constructor. Only a [`RenderTarget`] should be able to
+    /// create bitmaps.
+    pub(crate) fn new(image: Rc<Image>, device_bitmap: ID2D1Bitmap, generation: usize) -> Self {
+        Self {
+            source: Source::Image(image),
+            device_bitmap,
+            generation,
+        }
+    }
+
+    /// The CPU-side image from which the bitmap was created, or `None` if the
+    /// bitmap holds the contents of a [`BitmapTarget`].
+    pub fn image(&self) -> Option<&Rc<Image>> {
+        match &self.source {
+            Source::Image(image) => Some(image),
+            Source::Target(_) => None,
+        }
+    }
+
+    /// Dimensions of the bitmap, in pixels.
+    pub fn size(&self) -> Size2D<u32> {
+        match &self.source {
+            Source::Image(image) => image.size(),
+            Source::Target(size) => *size,
+        }
+    }
+
+    /// Accesses the underlying device bitmap.
+    pub(crate) fn device_bitmap(&self) -> &ID2D1Bitmap {
+        &self.device_bitmap
+    }
+}
+
+impl DeviceResource for Bitmap {
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget) -> Result<()> {
+        if self.generation() != render_target.generation() {
+            *self = match &self.source {
+                Source::Image(image) => render_target.make_bitmap(image.clone())?,
+                // The rendered contents were lost with the device, and only the
+                // re-created target can provide them again.
+                Source::Target(_) => return Err(Error::TargetBitmapLost),
+            };
+            render_target.count_recreated_resource();
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Bitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bitmap")
+            .field("source", &self.source)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// An offscreen render target whose contents can be drawn as a [`Bitmap`], or
+/// painted with a [`BitmapBrush`](crate::brushes::BitmapBrush), by the
+/// [`RenderTarget`] which created it. Useful for caching expensive layers of a
+/// scene, minimaps and post-processing.
+///
+/// Draw into the target with [`RenderTarget::begin_draw_to`]. The target shares
+/// the device of its parent render target, so any brushes and bitmaps created
+/// by the parent may be used while drawing.
+///
+/// This is a device-specific resource and its contents are lost if the device
+/// is. Check [`needs_redraw`](Self::needs_redraw) before using the contents.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ::windows::Win32::Foundation::HWND;
+/// use ::win_geom::d2::{Point2D, Rect2D, Size2D};
+/// use ::d2d::{bitmaps::InterpolationMode, Color, D2DFactory};
+///
+/// # let hwnd = HWND(0);
+/// # let size = Size2D { width: 100, height: 100 };
+/// let factory = D2DFactory::new()?;
+/// let mut render_target = factory.make_render_target(hwnd, size);
+/// let mut minimap = render_target.make_bitmap_target(Size2D {
+///     width: 64.0,
+///     height: 64.0,
+/// })?;
+///
+/// if minimap.needs_redraw(&render_target) {
+///     let mut ctx = render_target.begin_draw_to(&mut minimap)?;
+///     ctx.clear(Color::dark_slate_gray());
+///     ctx.end_draw()?;
+/// }
+///
+/// let mut bitmap = minimap.bitmap()?;
+/// let mut ctx = render_target.begin_draw()?;
+/// ctx.clear(Color::black());
+/// ctx.draw_bitmap(
+///     &mut bitmap,
+///     Rect2D::from_size_and_origin(minimap.size(), Point2D { x: 8.0, y: 8.0 }),
+///     1.0,
+///     InterpolationMode::Linear,
+///     None,
+/// );
+/// ctx.end_draw()?;
+/// # Ok::<(), ::d2d::Error>(())
+/// ```
+pub struct BitmapTarget {
+    /// Size of the target in device independent pixels.
+    size: Size2D<f32>,
+    /// The Direct2D compatible render target. May become invalidated if the
+    /// parent render target is re-created.
+    device_target: ID2D1BitmapRenderTarget,
+    /// The generation of the render target for which this target was created.
+    /// If the two generations no longer agree, the target must be re-created.
+    generation: usize,
+    /// Whether a draw into the target has completed since it was created.
+    drawn: bool,
+}
+
+impl BitmapTarget {
+    /// A crate-private constructor. Only a [`RenderTarget`] should be able to
+    /// create bitmap targets.
+    pub(crate) fn new(
+        size: Size2D<f32>,
+        device_target: ID2D1BitmapRenderTarget,
+        generation: usize,
+    ) -> Self {
+        Self {
+            size,
+            device_target,
+            generation,
+            drawn: false,
+        }
+    }
+
+    /// Size of the target in device independent pixels.
+    pub fn size(&self) -> Size2D<f32> {
+        self.size
+    }
+
+    /// Size of the target in pixels, which differs from [`size`](Self::size)
+    /// according to the DPI of the parent render target.
+    pub fn pixel_size(&self) -> Size2D<u32> {
+        let size = unsafe { self.device_target.GetPixelSize() };
+        Size2D {
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    /// Whether the target must be drawn before its contents are used, either
+    /// because it has never been drawn or because the contents were lost along
+    /// with the device of `render_target`.
+    pub fn needs_redraw(&self, render_target: &RenderTarget) -> bool {
+        !self.drawn || self.generation != render_target.generation()
+    }
+
+    /// The contents of the target as a bitmap, sharing the target's pixels.
+    ///
+    /// Fetch the bitmap again after the target is re-created. Drawing a bitmap
+    /// which outlives its target's device fails with
+    /// [`Error::TargetBitmapLost`].
+    pub fn bitmap(&self) -> Result<Bitmap> {
+        let device_bitmap = check_res(|| unsafe { self.device_target.GetBitmap() }, "GetBitmap")?;
+
+        Ok(Bitmap {
+            source: Source::Target(self.pixel_size()),
+            device_bitmap,
+            generation: self.generation,
+        })
+    }
+
+    /// Copies the contents of the target into an [`Image`], e.g. to check the
+    /// output of an automated test without a window.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::NothingToCapture`] if the target has never been
+    /// drawn, or if its contents could not be read back from the device.
+    pub fn capture(&self) -> Result<Image> {
+        if !self.drawn {
+            return Err(Error::NothingToCapture);
+        }
+        let device_bitmap = check_res(|| unsafe { self.device_target.GetBitmap() }, "GetBitmap")?;
+        read_bitmap(&self.device_target(), &device_bitmap)
+    }
+
+    /// Accesses the underlying device render target.
+    pub(crate) fn device_target(&self) -> ID2D1RenderTarget {
+        self.device_target.clone().into()
+    }
+
+    /// Records that a draw into the target completed.
+    pub(crate) fn set_drawn(&mut self) {
+        self.drawn = true;
+    }
+}
+
+impl DeviceResource for BitmapTarget {
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget) -> Result<()> {
+        if self.generation() != render_target.generation() {
+            *self = render_target.make_bitmap_target(self.size)?;
+            render_target.count_recreated_resource();
+        }
+        Ok(())
+    }
+}
+
+impl Capture for BitmapTarget {
+    type Error = Error;
+
+    fn capture(&self) -> Result<Image> {
+        BitmapTarget::capture(self)
+    }
+}
+
+impl Debug for BitmapTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitmapTarget")
+            .field("size", &self.size)
+            .field("generation", &self.generation)
+            .field("drawn", &self.drawn)
+            .finish()
+    }
+}
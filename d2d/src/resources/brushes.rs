@@ -1,15 +1,98 @@
 //! Device-mapped Direct2D brushes for "painting" areas of a render target.
 
-use crate::{color::Color, resources::DeviceResource, target::RenderTarget};
+use crate::{
+    bitmaps::{Bitmap, InterpolationMode},
+    color::Color,
+    draw_list::BrushSpec,
+    errors::Result,
+    gradient::{ExtendMode, Gradient},
+    resources::DeviceResource,
+    target::RenderTarget,
+};
 use ::std::fmt::{self, Debug};
-use ::windows::Win32::Graphics::Direct2D::{ID2D1Brush, ID2D1SolidColorBrush};
+use ::win_geom::d2::{Ellipse2D, Point2D, Transform2D};
+use ::windows::{
+    Foundation::Numerics::Matrix3x2,
+    Win32::Graphics::Direct2D::{
+        ID2D1BitmapBrush, ID2D1Brush, ID2D1LinearGradientBrush, ID2D1RadialGradientBrush,
+        ID2D1SolidColorBrush,
+    },
+};
 
-/// A trait shared by all brush types [`SolidColorBrush`], etc. Allows private
-/// crate access to the underlying Direct2D brush.
-pub(crate) trait Brush {
-    /// Accesses the underlying device brush as a loosely-typed `ID2D1Brush`
-    /// which is the top-level Direct2D interface to which all brushes conform.
-    fn device_brush(&self) -> &'_ ID2D1Brush;
+/// A trait shared by all brush types [`SolidColorBrush`],
+/// [`LinearGradientBrush`], etc. Any brush may be passed to the drawing
+/// methods of a [`Context`](crate::Context).
+///
+/// The trait is sealed and cannot be implemented outside of this crate.
+pub trait Brush: DeviceResource + sealed::Sealed {}
+
+mod sealed {
+    use ::windows::Win32::Graphics::Direct2D::ID2D1Brush;
+
+    /// Seals the [`Brush`](super::Brush) trait and allows private crate
+    /// access to the underlying Direct2D brush.
+    pub trait Sealed {
+        /// Accesses the underlying device brush as a loosely-typed
+        /// `ID2D1Brush` which is the top-level Direct2D interface to which all
+        /// brushes conform.
+        fn device_brush(&self) -> &'_ ID2D1Brush;
+    }
+}
+
+/// Anything with which the drawing methods of a [`Context`](crate::Context)
+/// may paint: a device [`Brush`], or a [`Color`] or [`BrushSpec`] describing
+/// one.
+///
+/// Described brushes are fetched from the render target's
+/// [`ResourceCache`](crate::cache::ResourceCache), so that each is created once
+/// and then shared.
+///
+/// The trait is sealed and cannot be implemented outside of this crate.
+pub trait Paint: paint::Sealed {}
+
+mod paint {
+    use crate::{errors::Result, target::RenderTarget};
+    use ::windows::Win32::Graphics::Direct2D::ID2D1Brush;
+
+    /// Seals the [`Paint`](super::Paint) trait and allows private crate
+    /// access to the device brush with which to paint.
+    pub trait Sealed {
+        /// The device brush, (re-)created for `render_target` if needed.
+        fn realize(self, render_target: &mut RenderTarget) -> Result<ID2D1Brush>;
+    }
+}
+
+impl<B: Brush + ?Sized> Paint for &mut B {}
+
+impl<B: Brush + ?Sized> paint::Sealed for &mut B {
+    fn realize(self, render_target: &mut RenderTarget) -> Result<ID2D1Brush> {
+        self.recreate_if_needed(render_target)?;
+        Ok(self.device_brush().clone())
+    }
+}
+
+impl Paint for Color {}
+
+impl paint::Sealed for Color {
+    fn realize(self, render_target: &mut RenderTarget) -> Result<ID2D1Brush> {
+        render_target.cached_brush(&BrushSpec::Solid(self))
+    }
+}
+
+impl Paint for &BrushSpec {}
+
+impl paint::Sealed for &BrushSpec {
+    fn realize(self, render_target: &mut RenderTarget) -> Result<ID2D1Brush> {
+        render_target.cached_brush(self)
+    }
+}
+
+impl Paint for BrushSpec {}
+
+impl paint::Sealed for BrushSpec {
+    fn realize(self, render_target: &mut RenderTarget) -> Result<ID2D1Brush> {
+        render_target.cached_brush(&self)
+    }
 }
 
 /// A brush which paints an area with a solid color.
@@ -47,7 +130,9 @@ impl SolidColorBrush {
     }
 }
 
-impl Brush for SolidColorBrush {
+impl Brush for SolidColorBrush {}
+
+impl sealed::Sealed for SolidColorBrush {
     fn device_brush(&self) -> &'_ ID2D1Brush {
         (&self.device_brush).into()
     }
@@ -58,10 +143,12 @@ impl DeviceResource for SolidColorBrush {
         self.generation
     }
 
-    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget) {
+    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget) -> Result<()> {
         if self.generation() != render_target.generation() {
-            *self = render_target.make_solid_color_brush(self.color);
+            *self = render_target.make_solid_color_brush(self.color)?;
+            render_target.count_recreated_resource();
         }
+        Ok(())
     }
 }
 
@@ -73,3 +160,339 @@ impl Debug for SolidColorBrush {
             .finish()
     }
 }
+
+/// A brush which paints an area with a linear gradient along the line from
+/// [`start`](Self::start) to [`end`](Self::end).
+///
+/// This is a device-specific resource and is tied to the [`RenderTarget`] by
+/// which it was created. Cache the brush and re-use on subsequent draw calls
+/// for best performance.
+pub struct LinearGradientBrush {
+    /// A copy of the [`Gradient`] from which the brush was created. This is
+    /// used to re-create the brush internally in the event that we must
+    /// re-create our device specific resources.
+    gradient: Gradient,
+    /// The starting point of the gradient axis, in brush space.
+    start: Point2D<f32>,
+    /// The ending point of the gradient axis, in brush space.
+    end: Point2D<f32>,
+    /// A cached Direct2D device-specific linear gradient brush. May become
+    /// invalidated if the corresponding render target is re-created.
+    device_brush: ID2D1LinearGradientBrush,
+    /// The generation of the render target for which this brush was created. If
+    /// the two generations no longer agree, the brush must be re-created.
+    generation: usize,
+}
+
+impl LinearGradientBrush {
+    /// A crate-private constructor. Only a [`RenderTarget`] should be able to
+    /// create brushes.
+    pub(crate) fn new(
+        gradient: Gradient,
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        device_brush: ID2D1LinearGradientBrush,
+        generation: usize,
+    ) -> Self {
+        Self {
+            gradient,
+            start,
+            end,
+            device_brush,
+            generation,
+        }
+    }
+
+    /// The gradient from which the brush was created.
+    pub fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+
+    /// The starting point of the gradient axis.
+    pub fn start(&self) -> Point2D<f32> {
+        self.start
+    }
+
+    /// The ending point of the gradient axis.
+    pub fn end(&self) -> Point2D<f32> {
+        self.end
+    }
+}
+
+impl Brush for LinearGradientBrush {}
+
+impl sealed::Sealed for LinearGradientBrush {
+    fn device_brush(&self) -> &'_ ID2D1Brush {
+        (&self.device_brush).into()
+    }
+}
+
+impl DeviceResource for LinearGradientBrush {
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget) -> Result<()> {
+        if self.generation() != render_target.generation() {
+            *self =
+                render_target.make_linear_gradient_brush(&self.gradient, self.start, self.end)?;
+            render_target.count_recreated_resource();
+        }
+        Ok(())
+    }
+}
+
+impl Debug for LinearGradientBrush {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinearGradientBrush")
+            .field("gradient", &self.gradient)
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// A brush which paints an area with a radial gradient.
+///
+/// The gradient is centered on the ellipse's center, with the gradient
+/// origin (the point at which the first stop is painted) offset from the center
+/// by [`origin_offset`](Self::origin_offset). The final stop is painted along
+/// the ellipse's perimeter.
+///
+/// This is a device-specific resource and is tied to the [`RenderTarget`] by
+/// which it was created. Cache the brush and re-use on subsequent draw calls
+/// for best performance.
+pub struct RadialGradientBrush {
+    /// A copy of the [`Gradient`] from which the brush was created. This is
+    /// used to re-create the brush internally in the event that we must
+    /// re-create our device specific resources.
+    gradient: Gradient,
+    /// The ellipse which contains the gradient, in brush space.
+    ellipse: Ellipse2D<f32>,
+    /// The offset of the gradient origin relative to the ellipse's center.
+    origin_offset: Point2D<f32>,
+    /// A cached Direct2D device-specific radial gradient brush. May become
+    /// invalidated if the corresponding render target is re-created.
+    device_brush: ID2D1RadialGradientBrush,
+    /// The generation of the render target for which this brush was created. If
+    /// the two generations no longer agree, the brush must be re-created.
+    generation: usize,
+}
+
+impl RadialGradientBrush {
+    /// A crate-private constructor. Only a [`RenderTarget`] should be able to
+    /// create brushes.
+    pub(crate) fn new(
+        gradient: Gradient,
+        ellipse: Ellipse2D<f32>,
+        origin_offset: Point2D<f32>,
+        device_brush: ID2D1RadialGradientBrush,
+        generation: usize,
+    ) -> Self {
+        Self {
+            gradient,
+            ellipse,
+            origin_offset,
+            device_brush,
+            generation,
+        }
+    }
+
+    /// The gradient from which the brush was created.
+    pub fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+
+    /// The ellipse which contains the gradient.
+    pub fn ellipse(&self) -> Ellipse2D<f32> {
+        self.ellipse
+    }
+
+    /// The offset of the gradient origin relative to the ellipse's center.
+    pub fn origin_offset(&self) -> Point2D<f32> {
+        self.origin_offset
+    }
+}
+
+impl Brush for RadialGradientBrush {}
+
+impl sealed::Sealed for RadialGradientBrush {
+    fn device_brush(&self) -> &'_ ID2D1Brush {
+        (&self.device_brush).into()
+    }
+}
+
+impl DeviceResource for RadialGradientBrush {
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget) -> Result<()> {
+        if self.generation() != render_target.generation() {
+            *self = render_target.make_radial_gradient_brush(
+                &self.gradient,
+                self.ellipse,
+                self.origin_offset,
+            )?;
+            render_target.count_recreated_resource();
+        }
+        Ok(())
+    }
+}
+
+impl Debug for RadialGradientBrush {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RadialGradientBrush")
+            .field("gradient", &self.gradient)
+            .field("ellipse", &self.ellipse)
+            .field("origin_offset", &self.origin_offset)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// Properties which control how a [`BitmapBrush`] tiles, samples and positions
+/// its bitmap.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{bitmaps::InterpolationMode, brushes::BitmapBrushProperties, ExtendMode};
+///
+/// // A repeating pixel-art pattern.
+/// let props = BitmapBrushProperties {
+///     extend_mode_x: ExtendMode::Wrap,
+///     extend_mode_y: ExtendMode::Wrap,
+///     interpolation: InterpolationMode::NearestNeighbor,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct BitmapBrushProperties {
+    /// How the brush paints areas to the left and right of the bitmap.
+    pub extend_mode_x: ExtendMode,
+    /// How the brush paints areas above and below the bitmap.
+    pub extend_mode_y: ExtendMode,
+    /// The sampling used when the bitmap is scaled or rotated.
+    pub interpolation: InterpolationMode,
+    /// A value between 0.0 and 1.0 which is multiplied against the bitmap's
+    /// alpha channel.
+    pub opacity: f32,
+    /// The transform applied to the bitmap in brush space. By default, the
+    /// bitmap's top-left corner is anchored at the origin of the render target.
+    pub transform: Transform2D,
+}
+
+impl Default for BitmapBrushProperties {
+    fn default() -> Self {
+        Self {
+            extend_mode_x: ExtendMode::Clamp,
+            extend_mode_y: ExtendMode::Clamp,
+            interpolation: InterpolationMode::Linear,
+            opacity: 1.0,
+            transform: Transform2D::identity(),
+        }
+    }
+}
+
+/// A brush which paints an area with a bitmap, optionally tiled in either
+/// direction. Useful for patterned fills such as terrain or UI backgrounds.
+///
+/// This is a device-specific resource and is tied to the [`RenderTarget`] by
+/// which it was created. Cache the brush and re-use on subsequent draw calls
+/// for best performance.
+pub struct BitmapBrush {
+    /// The bitmap painted by the brush. Kept so that the brush can be
+    /// re-created internally in the event that we must re-create our device
+    /// specific resources.
+    bitmap: Bitmap,
+    /// The properties with which the brush was created, kept up to date with
+    /// any subsequent changes.
+    props: BitmapBrushProperties,
+    /// A cached Direct2D device-specific bitmap brush. May become invalidated
+    /// if the corresponding render target is re-created.
+    device_brush: ID2D1BitmapBrush,
+    /// The generation of the render target for which this brush was created. If
+    /// the two generations no longer agree, the brush must be re-created.
+    generation: usize,
+}
+
+impl BitmapBrush {
+    /// A crate-private constructor. Only a [`RenderTarget`] should be able to
+    /// create brushes.
+    pub(crate) fn new(
+        bitmap: Bitmap,
+        props: BitmapBrushProperties,
+        device_brush: ID2D1BitmapBrush,
+        generation: usize,
+    ) -> Self {
+        Self {
+            bitmap,
+            props,
+            device_brush,
+            generation,
+        }
+    }
+
+    /// The bitmap painted by the brush.
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    /// The brush's current properties.
+    pub fn properties(&self) -> BitmapBrushProperties {
+        self.props
+    }
+
+    /// Sets the opacity of the brush. Takes effect on the next draw call.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.props.opacity = opacity;
+        unsafe {
+            self.device_brush.SetOpacity(opacity);
+        }
+    }
+
+    /// Sets the brush-space transform, e.g. to scroll or scale a tiled
+    /// pattern. Takes effect on the next draw call.
+    pub fn set_transform(&mut self, transform: Transform2D) {
+        self.props.transform = transform;
+        unsafe {
+            self.device_brush
+                .SetTransform(&Matrix3x2::from(transform) as _);
+        }
+    }
+}
+
+impl Brush for BitmapBrush {}
+
+impl sealed::Sealed for BitmapBrush {
+    fn device_brush(&self) -> &'_ ID2D1Brush {
+        (&self.device_brush).into()
+    }
+}
+
+impl DeviceResource for BitmapBrush {
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget) -> Result<()> {
+        if self.generation() != render_target.generation() {
+            *self = render_target.make_bitmap_brush(&mut self.bitmap, self.props)?;
+            render_target.count_recreated_resource();
+        }
+        Ok(())
+    }
+}
+
+impl Debug for BitmapBrush {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitmapBrush")
+            .field("bitmap", &self.bitmap)
+            .field("props", &self.props)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
@@ -0,0 +1,344 @@
+//! An automatic cache of device resources, interned by descriptor.
+
+use crate::{
+    bitmaps::Bitmap,
+    brushes::Brush,
+    draw_list::BrushSpec,
+    errors::Result,
+    factory::D2DFactory,
+    imaging::Image,
+    stroke::{StrokeStyle, StrokeStyleProperties},
+};
+use ::std::{
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+/// A cache of the brushes, stroke styles and bitmaps used to draw with a
+/// [`RenderTarget`], keyed by the descriptors from which they are made.
+///
+/// Each [`RenderTarget`] owns a cache, which is used whenever a [`Color`] or
+/// [`BrushSpec`] is drawn with rather than a hand-made brush, e.g.
+/// `ctx.fill_rect(rect, Color::red())`, and when [`DrawList`]s are replayed.
+/// The first use of a descriptor creates its resource, and later uses share
+/// it. Like any device resource, cached resources are re-created
+/// transparently after device loss.
+///
+/// The cache holds up to [`capacity`](Self::capacity) resources of each kind,
+/// evicting the least recently used beyond that. Entries are found with a
+/// linear search from the most recently used, which suits the modest number
+/// of distinct resources an application typically draws with.
+///
+/// Bitmaps are keyed by the identity of their shared [`Image`], which the
+/// cache keeps alive until the bitmap is evicted.
+///
+/// [`Color`]: crate::Color
+/// [`RenderTarget`]: crate::RenderTarget
+/// [`DrawList`]: crate::DrawList
+pub struct ResourceCache {
+    brushes: Lru<BrushSpec, Box<dyn Brush>>,
+    stroke_styles: Lru<StrokeStyleProperties, StrokeStyle>,
+    bitmaps: Lru<Rc<Image>, Bitmap>,
+    stats: CacheStats,
+}
+
+/// Counts of the lookups made in a [`ResourceCache`], as returned by
+/// [`ResourceCache::stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    /// Lookups which found an existing resource.
+    pub hits: usize,
+    /// Lookups which had to create a resource.
+    pub misses: usize,
+    /// Cached resources re-created because the device was lost since they
+    /// were created.
+    pub recreations: usize,
+    /// Resources evicted to keep within the cache's capacity.
+    pub evictions: usize,
+}
+
+impl ResourceCache {
+    /// The capacity of a new render target's cache.
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Constructs an empty cache holding up to `capacity` resources of each
+    /// kind. A capacity of zero is treated as one.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            brushes: Lru::new(capacity),
+            stroke_styles: Lru::new(capacity),
+            bitmaps: Lru::new(capacity),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// The number of resources of each kind held before the least recently
+    /// used are evicted.
+    pub fn capacity(&self) -> usize {
+        self.brushes.capacity
+    }
+
+    /// Changes the capacity, immediately evicting the least recently used
+    /// resources beyond it.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.stats.evictions += self.brushes.set_capacity(capacity)
+            + self.stroke_styles.set_capacity(capacity)
+            + self.bitmaps.set_capacity(capacity);
+    }
+
+    /// The total number of resources held.
+    pub fn len(&self) -> usize {
+        self.brushes.len() + self.stroke_styles.len() + self.bitmaps.len()
+    }
+
+    /// Whether the cache holds no resources.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Releases all cached resources. The statistics are kept.
+    pub fn clear(&mut self) {
+        self.brushes.clear();
+        self.stroke_styles.clear();
+        self.bitmaps.clear();
+    }
+
+    /// Counts of the lookups made since the cache was created or the
+    /// statistics were last [reset](Self::reset_stats).
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Resets the lookup statistics to zero, e.g. at the start of each frame.
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Removes the brush described by `spec` while it is used, to be put back
+    /// with [`insert_brush`](Self::insert_brush). The entry is taken rather
+    /// than borrowed so that the render target owning the cache can (re-)create
+    /// it meanwhile.
+    pub(crate) fn take_brush(&mut self, spec: &BrushSpec) -> Option<(BrushSpec, Box<dyn Brush>)> {
+        self.brushes.take(|s| s == spec, &mut self.stats)
+    }
+
+    /// Inserts a brush as the most recently used.
+    pub(crate) fn insert_brush(&mut self, spec: BrushSpec, brush: Box<dyn Brush>) {
+        self.stats.evictions += self.brushes.insert(spec, brush);
+    }
+
+    /// The stroke style described by `props`, created on first use.
+    pub(crate) fn stroke_style(
+        &mut self,
+        factory: &D2DFactory,
+        props: &StrokeStyleProperties,
+    ) -> Result<StrokeStyle> {
+        let style = self.stroke_styles.get_or_insert(
+            |p| p == props,
+            || Ok((props.clone(), factory.make_stroke_style(props.clone())?)),
+            &mut self.stats,
+        )?;
+
+        Ok(style.clone())
+    }
+
+    /// Removes the bitmap of `image` while it is used, to be put back with
+    /// [`insert_bitmap`](Self::insert_bitmap), as with
+    /// [`take_brush`](Self::take_brush).
+    pub(crate) fn take_bitmap(&mut self, image: &Rc<Image>) -> Option<(Rc<Image>, Bitmap)> {
+        self.bitmaps.take(|i| Rc::ptr_eq(i, image), &mut self.stats)
+    }
+
+    /// Inserts a bitmap as the most recently used.
+    pub(crate) fn insert_bitmap(&mut self, image: Rc<Image>, bitmap: Bitmap) {
+        self.stats.evictions += self.bitmaps.insert(image, bitmap);
+    }
+
+    /// Records that a cached resource was re-created because the device was
+    /// lost since it was created.
+    pub(crate) fn count_recreation(&mut self) {
+        self.stats.recreations += 1;
+    }
+}
+
+impl Default for ResourceCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl Debug for ResourceCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceCache")
+            .field("capacity", &self.capacity())
+            .field("brushes", &self.brushes.len())
+            .field("stroke_styles", &self.stroke_styles.len())
+            .field("bitmaps", &self.bitmaps.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+/// Entries ordered from least to most recently used, holding at most
+/// `capacity` of them.
+struct Lru<K, V> {
+    entries: Vec<(K, V)>,
+    capacity: usize,
+}
+
+impl<K, V> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Changes the capacity, returning the number of entries evicted.
+    fn set_capacity(&mut self, capacity: usize) -> usize {
+        self.capacity = capacity.max(1);
+        self.evict()
+    }
+
+    /// Finds the value whose key matches and marks it as the most recently
+    /// used, or else inserts the entry made by `make`, evicting the least
+    /// recently used entries beyond capacity.
+    fn get_or_insert(
+        &mut self,
+        matches: impl Fn(&K) -> bool,
+        make: impl FnOnce() -> Result<(K, V)>,
+        stats: &mut CacheStats,
+    ) -> Result<&mut V> {
+        let (key, value) = match self.take(matches, stats) {
+            Some(entry) => entry,
+            None => make()?,
+        };
+        stats.evictions += self.insert(key, value);
+
+        let (_, value) = self.entries.last_mut().expect("entry was just pushed");
+        Ok(value)
+    }
+
+    /// Removes the entry whose key matches, counting the lookup as a hit if
+    /// there is one and otherwise as a miss.
+    fn take(&mut self, matches: impl Fn(&K) -> bool, stats: &mut CacheStats) -> Option<(K, V)> {
+        match self.entries.iter().rposition(|(key, _)| matches(key)) {
+            Some(index) => {
+                stats.hits += 1;
+                Some(self.entries.remove(index))
+            }
+            None => {
+                stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Inserts an entry as the most recently used, returning the number of
+    /// entries evicted to make room for it.
+    fn insert(&mut self, key: K, value: V) -> usize {
+        self.entries.push((key, value));
+        self.evict()
+    }
+
+    /// Evicts the least recently used entries beyond capacity, returning the
+    /// number evicted.
+    fn evict(&mut self) -> usize {
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess);
+        excess
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    /// Looks up `key` in `lru`, inserting its square on a miss.
+    fn lookup(lru: &mut Lru<u32, u32>, key: u32, stats: &mut CacheStats) -> u32 {
+        match lru.get_or_insert(|k| *k == key, || Ok((key, key * key)), stats) {
+            Ok(value) => *value,
+            Err(_) => panic!("unexpected error"),
+        }
+    }
+
+    fn keys(lru: &Lru<u32, u32>) -> Vec<u32> {
+        lru.entries.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn test_hits_share_the_entry() {
+        let mut lru = Lru::new(4);
+        let mut stats = CacheStats::default();
+
+        assert_eq!(lookup(&mut lru, 3, &mut stats), 9);
+        assert_eq!(lookup(&mut lru, 3, &mut stats), 9);
+        assert_eq!(lookup(&mut lru, 4, &mut stats), 16);
+
+        assert_eq!(lru.len(), 2);
+        assert_eq!(
+            stats,
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut lru = Lru::new(3);
+        let mut stats = CacheStats::default();
+
+        for key in [1, 2, 3, 1, 4] {
+            lookup(&mut lru, key, &mut stats);
+        }
+
+        assert_eq!(keys(&lru), [3, 1, 4]);
+        assert_eq!(stats.evictions, 1);
+    }
+
+    #[test]
+    fn test_shrinking_capacity_evicts() {
+        let mut lru = Lru::new(4);
+        let mut stats = CacheStats::default();
+        for key in [1, 2, 3, 4] {
+            lookup(&mut lru, key, &mut stats);
+        }
+
+        assert_eq!(lru.set_capacity(2), 2);
+        assert_eq!(keys(&lru), [3, 4]);
+
+        // A capacity of zero still keeps the most recent entry.
+        assert_eq!(lru.set_capacity(0), 1);
+        lookup(&mut lru, 5, &mut stats);
+        assert_eq!(keys(&lru), [5]);
+    }
+
+    #[test]
+    fn test_failed_insert_counts_a_miss() {
+        let mut lru: Lru<u32, u32> = Lru::new(2);
+        let mut stats = CacheStats::default();
+
+        let failed = lru.get_or_insert(
+            |k| *k == 1,
+            || Err(crate::Error::DrawInProgress),
+            &mut stats,
+        );
+
+        assert!(failed.is_err());
+        assert_eq!(lru.len(), 0);
+        assert_eq!(stats.misses, 1);
+    }
+}
@@ -1,18 +1,28 @@
 //! Device-specific Direct2D resources (brushed, bitmaps, etc.)
 
-use crate::RenderTarget;
+use crate::{errors::Result, RenderTarget};
 
+pub mod bitmaps;
 pub mod brushes;
+pub mod cache;
 
 /// A trait shared in common with all device-specific resources. A
 /// device-specific resource is a Direct2D resource which must be re-created if
 /// the render target is lost.
-pub(crate) trait DeviceResource {
+///
+/// Resources are re-created lazily when next used for drawing, but may also be
+/// re-created eagerly via [`recreate_if_needed`](Self::recreate_if_needed).
+pub trait DeviceResource {
     /// The generation of the render target for which this resource was created.
     /// If the two generations no longer agree, the resource must be re-created.
     fn generation(&self) -> usize;
 
     /// Re-create the resource if required (i.e. if the resource's generation no
     /// longer matches that of the [`RenderTarget`]).
-    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget);
+    ///
+    /// # Errors
+    ///
+    /// Fails if the resource, or the render target's device, could not be
+    /// re-created. The resource is left unchanged and may be retried.
+    fn recreate_if_needed(&mut self, render_target: &mut RenderTarget) -> Result<()>;
 }
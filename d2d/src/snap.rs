@@ -0,0 +1,275 @@
+//! Pixel snapping, which aligns lines and rectangles with device pixels so
+//! that they are drawn crisply at any DPI.
+
+use ::win32::window::DPI;
+use ::win_geom::d2::{Point2D, Rect2D};
+
+/// How a [`Context`](crate::Context) snaps lines and rectangles to device
+/// pixels, as set with
+/// [`Context::set_snap_mode`](crate::Context::set_snap_mode).
+///
+/// Snapping applies to lines, rectangles and rounded rectangles drawn while
+/// the world transform is a plain translation, and is skipped otherwise. See
+/// [`PixelSnap`] for how each is snapped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SnapMode {
+    /// Draw everything exactly where given.
+    #[default]
+    Off,
+    /// Move lines and rectangles onto device pixels, keeping stroke widths.
+    /// A stroke narrower than a device pixel, such as a hairline, covers a
+    /// single pixel at partial intensity rather than blurring across two.
+    Positions,
+    /// Move lines and rectangles onto device pixels and round stroke widths
+    /// to whole device pixels, of at least one. Every stroke is then drawn at
+    /// full intensity.
+    PositionsAndWidths,
+}
+
+/// Snaps coordinates in device-independent pixels (DIPs) to the device pixels
+/// of a given DPI.
+///
+/// A stroke is crisp when it covers whole device pixels. One whose width
+/// rounds to an odd number of pixels must be centered on a pixel's center,
+/// and one whose width rounds to an even number on a boundary between pixels.
+/// A fill is crisp when its edges lie on pixel boundaries.
+///
+/// Use [`RenderTarget::pixel_snap`](crate::RenderTarget::pixel_snap) to snap
+/// at the DPI of a render target, e.g. when recording a
+/// [`DrawList`](crate::DrawList).
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::PixelSnap;
+/// use ::win32::window::DPI;
+/// use ::win_geom::d2::Point2D;
+///
+/// // At 200% scaling, a hairline at x = 8.0 DIPs lies on the boundary
+/// // between device pixels 15 and 16, blurring across both...
+/// let snap = PixelSnap::new(DPI::from(192.0));
+/// let p0 = Point2D { x: 8.0, y: 0.0 };
+/// let p1 = Point2D { x: 8.0, y: 64.0 };
+///
+/// // ...so it is moved to the center of pixel 16.
+/// let (p0, p1) = snap.line(p0, p1, 0.5);
+/// assert_eq!((p0.x, p1.x), (8.25, 8.25));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelSnap {
+    /// Device pixels per DIP.
+    scale: f32,
+    /// The translation applied to coordinates before they are drawn.
+    offset: Point2D<f32>,
+}
+
+impl PixelSnap {
+    /// Constructs a snapper for the device pixels of `dpi`.
+    pub fn new(dpi: DPI) -> Self {
+        Self {
+            scale: f32::from(dpi) / 96.0,
+            offset: Point2D { x: 0.0, y: 0.0 },
+        }
+    }
+
+    /// Snaps coordinates which are translated by `offset` before being drawn,
+    /// e.g. by a world transform, such that they land on device pixels once
+    /// translated.
+    pub fn with_offset(self, offset: Point2D<f32>) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Rounds `stroke_width` to a whole number of device pixels, of at least
+    /// one.
+    pub fn stroke_width(&self, stroke_width: f32) -> f32 {
+        self.device_width(stroke_width) / self.scale
+    }
+
+    /// Snaps `point` such that a stroke of `stroke_width` centered on it
+    /// covers whole device pixels.
+    pub fn point(&self, point: Point2D<f32>, stroke_width: f32) -> Point2D<f32> {
+        Point2D {
+            x: self.center(point.x, self.offset.x, stroke_width),
+            y: self.center(point.y, self.offset.y, stroke_width),
+        }
+    }
+
+    /// Snaps a horizontal or vertical line stroked with `stroke_width`. The
+    /// line is centered across whole device pixels and its ends are moved to
+    /// pixel boundaries. Diagonal lines cannot be snapped and are returned
+    /// unchanged.
+    pub fn line(
+        &self,
+        p0: Point2D<f32>,
+        p1: Point2D<f32>,
+        stroke_width: f32,
+    ) -> (Point2D<f32>, Point2D<f32>) {
+        let snap_end = |p: Point2D<f32>, horizontal: bool| {
+            if horizontal {
+                Point2D {
+                    x: self.edge(p.x, self.offset.x),
+                    y: self.center(p.y, self.offset.y, stroke_width),
+                }
+            } else {
+                Point2D {
+                    x: self.center(p.x, self.offset.x, stroke_width),
+                    y: self.edge(p.y, self.offset.y),
+                }
+            }
+        };
+
+        if p0.y == p1.y {
+            (snap_end(p0, true), snap_end(p1, true))
+        } else if p0.x == p1.x {
+            (snap_end(p0, false), snap_end(p1, false))
+        } else {
+            (p0, p1)
+        }
+    }
+
+    /// Snaps the edges of a filled `rect` to the nearest device pixel
+    /// boundaries.
+    pub fn rect(&self, rect: Rect2D<f32>) -> Rect2D<f32> {
+        Rect2D {
+            left: self.edge(rect.left, self.offset.x),
+            top: self.edge(rect.top, self.offset.y),
+            right: self.edge(rect.right, self.offset.x),
+            bottom: self.edge(rect.bottom, self.offset.y),
+        }
+    }
+
+    /// Snaps the edges of a `rect` outlined with `stroke_width` such that each
+    /// side of the outline covers whole device pixels.
+    pub fn stroked_rect(&self, rect: Rect2D<f32>, stroke_width: f32) -> Rect2D<f32> {
+        Rect2D {
+            left: self.center(rect.left, self.offset.x, stroke_width),
+            top: self.center(rect.top, self.offset.y, stroke_width),
+            right: self.center(rect.right, self.offset.x, stroke_width),
+            bottom: self.center(rect.bottom, self.offset.y, stroke_width),
+        }
+    }
+
+    /// The width of a stroke in whole device pixels, of at least one.
+    fn device_width(&self, stroke_width: f32) -> f32 {
+        (stroke_width * self.scale).round().max(1.0)
+    }
+
+    /// Snaps coordinate `v`, translated by `offset`, to the nearest pixel
+    /// boundary.
+    fn edge(&self, v: f32, offset: f32) -> f32 {
+        ((v + offset) * self.scale).round() / self.scale - offset
+    }
+
+    /// Snaps coordinate `v`, translated by `offset`, to where a stroke of
+    /// `stroke_width` centered on it covers whole pixels.
+    fn center(&self, v: f32, offset: f32, stroke_width: f32) -> f32 {
+        let device = (v + offset) * self.scale;
+        let snapped = if self.device_width(stroke_width) as u32 % 2 == 1 {
+            device.floor() + 0.5
+        } else {
+            device.round()
+        };
+        snapped / self.scale - offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    const DPIS: [f32; 4] = [96.0, 120.0, 144.0, 192.0];
+    const WIDTHS: [f32; 6] = [0.25, 0.5, 1.0, 1.5, 2.0, 3.0];
+    const COORDINATES: [f32; 6] = [0.0, 0.3, 8.0, 8.5, 13.7, 100.0];
+
+    /// Rounds away the error of converting `v` to DIPs and back.
+    fn approx(v: f32) -> f32 {
+        (v * 1000.0).round() / 1000.0
+    }
+
+    /// Whether `v` is within rounding error of a whole number.
+    fn is_whole(v: f32) -> bool {
+        (v - v.round()).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_strokes_cover_whole_device_pixels() {
+        for dpi in DPIS {
+            let scale = dpi / 96.0;
+            for offset in [0.0, 0.4] {
+                let snap = PixelSnap::new(DPI::from(dpi)).with_offset(Point2D {
+                    x: offset,
+                    y: offset,
+                });
+                for stroke_width in WIDTHS {
+                    let width = snap.stroke_width(stroke_width) * scale;
+                    assert!(is_whole(width) && width >= 1.0, "{dpi} dpi: {width}px");
+
+                    for v in COORDINATES {
+                        let p = snap.point(Point2D { x: v, y: v }, stroke_width);
+                        let near_edge = (p.x + offset) * scale - width / 2.0;
+                        assert!(
+                            is_whole(near_edge),
+                            "{dpi} dpi, {stroke_width} wide at {v}: edge at {near_edge}px"
+                        );
+                        assert!((p.x - v).abs() * scale <= 1.0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fills_have_edges_on_pixel_boundaries() {
+        for dpi in DPIS {
+            let scale = dpi / 96.0;
+            let snap = PixelSnap::new(DPI::from(dpi));
+            let rect = snap.rect(Rect2D {
+                left: 0.3,
+                top: 8.0,
+                right: 13.7,
+                bottom: 100.0,
+            });
+
+            for edge in [rect.left, rect.top, rect.right, rect.bottom] {
+                assert!(is_whole(edge * scale), "{dpi} dpi: edge at {edge}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_hairlines_at_common_scales() {
+        let hairline_x = |dpi: f32| {
+            let snap = PixelSnap::new(DPI::from(dpi));
+            let (p0, _) = snap.line(Point2D { x: 8.0, y: 0.0 }, Point2D { x: 8.0, y: 10.0 }, 0.5);
+            approx(p0.x * dpi / 96.0)
+        };
+
+        assert_eq!(hairline_x(96.0), 8.5);
+        assert_eq!(hairline_x(120.0), 10.5);
+        assert_eq!(hairline_x(144.0), 12.5);
+        assert_eq!(hairline_x(192.0), 16.5);
+    }
+
+    #[test]
+    fn test_line_ends_snap_to_boundaries() {
+        let snap = PixelSnap::new(DPI::from(144.0));
+
+        let (p0, p1) = snap.line(Point2D { x: 0.3, y: 4.0 }, Point2D { x: 9.9, y: 4.0 }, 1.0);
+
+        assert_eq!((approx(p0.x * 1.5), approx(p1.x * 1.5)), (0.0, 15.0));
+        // A 1 DIP stroke is 1.5px, rounded to 2px, so it is centered on a
+        // boundary.
+        assert_eq!((approx(p0.y * 1.5), approx(p1.y * 1.5)), (6.0, 6.0));
+    }
+
+    #[test]
+    fn test_diagonal_lines_are_unchanged() {
+        let snap = PixelSnap::new(DPI::from(120.0));
+        let p0 = Point2D { x: 0.3, y: 0.7 };
+        let p1 = Point2D { x: 5.1, y: 9.2 };
+
+        assert_eq!(snap.line(p0, p1, 1.0), (p0, p1));
+    }
+}
@@ -0,0 +1,526 @@
+//! Golden-image snapshot testing of rendered frames.
+//!
+//! A snapshot test records a scene into a [`DrawList`], renders it with a
+//! [`SoftwareRenderTarget`] and compares the result against a PNG stored
+//! alongside the tests. Because rendering happens on the CPU, snapshot tests
+//! run on any platform.
+//!
+//! When a frame differs from its snapshot by more than the tolerance, the
+//! actual and expected frames are written to a `failures` directory next to
+//! the snapshots, along with a diff image highlighting mismatched pixels in
+//! red.
+//!
+//! To create or update snapshots, run the tests with the environment variable
+//! [`BLESS_ENV_VAR`] set to `1`. Each test then writes its frame as the new
+//! snapshot rather than comparing against it.
+
+use crate::{
+    draw_list::DrawList,
+    imaging::{Image, ImageError},
+    software::SoftwareRenderTarget,
+};
+use ::std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+use ::win_geom::d2::Size2D;
+
+/// The environment variable which, when set to `1`, writes rendered frames as
+/// new snapshots instead of comparing against the stored ones.
+pub const BLESS_ENV_VAR: &str = "D2D_BLESS_SNAPSHOTS";
+
+/// The default maximum difference allowed in any channel of any pixel.
+const DEFAULT_TOLERANCE: u8 = 2;
+
+/// Errors which can occur when checking a frame against its snapshot.
+#[derive(::thiserror::Error, Debug)]
+pub enum SnapshotError {
+    /// No snapshot has been stored for the frame yet.
+    #[error("snapshot {path} does not exist; re-run with {BLESS_ENV_VAR}=1 to create it")]
+    Missing {
+        /// The path at which the snapshot was expected.
+        path: PathBuf,
+    },
+    /// The frame has a different size to its snapshot.
+    #[error("snapshot '{name}' is {expected:?} but the frame is {actual:?}")]
+    SizeMismatch {
+        /// The name of the snapshot.
+        name: String,
+        /// The size of the stored snapshot.
+        expected: Size2D<u32>,
+        /// The size of the rendered frame.
+        actual: Size2D<u32>,
+    },
+    /// Pixels of the frame differ from the snapshot by more than the
+    /// tolerance.
+    #[error(
+        "snapshot '{name}' differs in {mismatched} pixels (by up to {max_difference}); see {}",
+        failures.display()
+    )]
+    Mismatch {
+        /// The name of the snapshot.
+        name: String,
+        /// The number of pixels which differ by more than the tolerance.
+        mismatched: usize,
+        /// The largest difference in any channel of any pixel.
+        max_difference: u8,
+        /// The directory to which the actual, expected and diff images were
+        /// written.
+        failures: PathBuf,
+    },
+    /// A snapshot could not be read or written.
+    #[error("snapshot I/O failed: {0}")]
+    Io(#[from] io::Error),
+    /// A snapshot could not be encoded or decoded.
+    #[error(transparent)]
+    Image(#[from] ImageError),
+}
+
+/// A directory of stored snapshots against which rendered frames are checked.
+///
+/// # Example
+///
+/// ```no_run
+/// use ::d2d::{snapshot::Snapshots, Color};
+/// use ::win_geom::d2::{Rect2D, Size2D};
+///
+/// let snapshots = Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots"));
+/// snapshots.assert("red_square", Size2D { width: 32, height: 32 }, |list| {
+///     list.clear(Color::white());
+///     list.fill_rect(Rect2D { left: 8.0, top: 8.0, right: 24.0, bottom: 24.0 }, Color::red());
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct Snapshots {
+    dir: PathBuf,
+    tolerance: u8,
+}
+
+impl Snapshots {
+    /// Constructs a set of snapshots stored as `<name>.png` within `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Sets the maximum difference allowed in any channel of any pixel before
+    /// the pixel is considered mismatched.
+    pub fn with_tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// The directory in which snapshots are stored.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Renders the frame recorded by `draw` at the given size and checks it
+    /// against the snapshot `name`.
+    pub fn check(
+        &self,
+        name: &str,
+        size: Size2D<u32>,
+        draw: impl FnOnce(&mut DrawList),
+    ) -> Result<(), SnapshotError> {
+        self.check_image(name, &render(size, draw))
+    }
+
+    /// Checks an already rendered frame against the snapshot `name`.
+    pub fn check_image(&self, name: &str, actual: &Image) -> Result<(), SnapshotError> {
+        self.check_or_bless(name, actual, blessing())
+    }
+
+    /// Like [`check`](Self::check), but panics with a description of the
+    /// failure. Intended for use within tests.
+    #[track_caller]
+    pub fn assert(&self, name: &str, size: Size2D<u32>, draw: impl FnOnce(&mut DrawList)) {
+        if let Err(e) = self.check(name, size, draw) {
+            panic!("{e}");
+        }
+    }
+
+    fn check_or_bless(&self, name: &str, actual: &Image, bless: bool) -> Result<(), SnapshotError> {
+        let path = self.dir.join(format!("{name}.png"));
+        let png = actual.encode_png()?;
+
+        if bless {
+            fs::create_dir_all(&self.dir)?;
+            fs::write(&path, &png)?;
+            return Ok(());
+        }
+
+        let expected = match fs::read(&path) {
+            Ok(bytes) => Image::decode(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(SnapshotError::Missing { path });
+            }
+            Err(e) => return Err(e.into()),
+        };
+        // Compare the frame as it would be stored, so that an unchanged frame
+        // survives the PNG round trip exactly.
+        let actual = Image::decode(&png)?;
+
+        if expected.size() != actual.size() {
+            return Err(SnapshotError::SizeMismatch {
+                name: name.to_owned(),
+                expected: expected.size(),
+                actual: actual.size(),
+            });
+        }
+
+        let diff = Diff::new(&expected, &actual, self.tolerance);
+        if diff.mismatched == 0 {
+            return Ok(());
+        }
+
+        let failures = self.dir.join("failures");
+        fs::create_dir_all(&failures)?;
+        fs::write(failures.join(format!("{name}.actual.png")), &png)?;
+        fs::write(
+            failures.join(format!("{name}.expected.png")),
+            expected.encode_png()?,
+        )?;
+        fs::write(
+            failures.join(format!("{name}.diff.png")),
+            diff.image.encode_png()?,
+        )?;
+
+        Err(SnapshotError::Mismatch {
+            name: name.to_owned(),
+            mismatched: diff.mismatched,
+            max_difference: diff.max_difference,
+            failures,
+        })
+    }
+}
+
+/// Renders the frame recorded by `draw` into an image of the given size.
+pub fn render(size: Size2D<u32>, draw: impl FnOnce(&mut DrawList)) -> Image {
+    let mut list = DrawList::new();
+    draw(&mut list);
+
+    let mut target = SoftwareRenderTarget::new(size);
+    target.render(&list);
+    target.to_image()
+}
+
+/// Whether snapshots should be written rather than checked.
+fn blessing() -> bool {
+    env::var_os(BLESS_ENV_VAR).is_some_and(|value| value == "1")
+}
+
+/// The pixel-wise comparison of two equally sized images.
+struct Diff {
+    mismatched: usize,
+    max_difference: u8,
+    /// Mismatched pixels in red over a faded copy of the expected image.
+    image: Image,
+}
+
+impl Diff {
+    fn new(expected: &Image, actual: &Image, tolerance: u8) -> Self {
+        let mut mismatched = 0;
+        let mut max_difference = 0;
+        let mut rgba = Vec::with_capacity(expected.pixels().len());
+
+        for (e, a) in expected
+            .pixels()
+            .chunks_exact(4)
+            .zip(actual.pixels().chunks_exact(4))
+        {
+            let difference = e.iter().zip(a).map(|(e, a)| e.abs_diff(*a)).max();
+            let difference = difference.unwrap_or_default();
+            max_difference = max_difference.max(difference);
+
+            if difference > tolerance {
+                mismatched += 1;
+                rgba.extend_from_slice(&[255, 0, 0, 255]);
+            } else {
+                let luma = (e[0] as u32 + e[1] as u32 + e[2] as u32) / 3;
+                let faded = (192 + luma / 4) as u8;
+                rgba.extend_from_slice(&[faded, faded, faded, 255]);
+            }
+        }
+
+        Self {
+            mismatched,
+            max_difference,
+            image: Image::from_rgba(expected.size(), &rgba).expect("one pixel per pixel"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        color::Color,
+        draw_list::LayerSpec,
+        gradient::Gradient,
+        layer::AntialiasMode,
+        path::{ArcSegment, ArcSize, FillMode, Path, SweepDirection},
+        stroke::{CapStyle, DashStyle, LineJoin, StrokeStyleProperties},
+        BrushSpec,
+    };
+    use ::pretty_assertions::assert_eq;
+    use ::win_geom::d2::{Ellipse2D, Point2D, Rect2D, RoundedRect2D, Transform2D};
+
+    const SIZE: Size2D<u32> = Size2D {
+        width: 64,
+        height: 64,
+    };
+
+    /// The snapshots of this crate's own scenes.
+    fn snapshots() -> Snapshots {
+        Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots"))
+    }
+
+    /// A fresh, empty snapshot directory.
+    fn scratch(name: &str) -> Snapshots {
+        let dir = env::temp_dir().join(format!("d2d-snapshot-{name}-{}", ::std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Snapshots::new(dir)
+    }
+
+    fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rect2D<f32> {
+        Rect2D {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    fn square(color: Color) -> Image {
+        render(SIZE, |list| {
+            list.clear(Color::white());
+            list.fill_rect(rect(16.0, 16.0, 48.0, 48.0), color);
+        })
+    }
+
+    #[test]
+    fn test_missing_snapshots_are_reported() {
+        let snapshots = scratch("missing");
+
+        let err = snapshots
+            .check_or_bless("square", &square(Color::red()), false)
+            .unwrap_err();
+        assert!(matches!(err, SnapshotError::Missing { .. }));
+    }
+
+    #[test]
+    fn test_blessed_snapshots_match() {
+        let snapshots = scratch("blessed");
+
+        snapshots
+            .check_or_bless("square", &square(Color::red()), true)
+            .unwrap();
+        snapshots
+            .check_or_bless("square", &square(Color::red()), false)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_mismatches_write_failure_images() {
+        let snapshots = scratch("mismatch");
+        snapshots
+            .check_or_bless("square", &square(Color::red()), true)
+            .unwrap();
+
+        let err = snapshots
+            .check_or_bless("square", &square(Color::blue()), false)
+            .unwrap_err();
+        let SnapshotError::Mismatch {
+            mismatched,
+            max_difference,
+            failures,
+            ..
+        } = err
+        else {
+            panic!("expected a mismatch, got {err:?}");
+        };
+        assert_eq!(mismatched, 32 * 32);
+        assert_eq!(max_difference, 255);
+        for suffix in ["actual", "expected", "diff"] {
+            assert!(failures.join(format!("square.{suffix}.png")).exists());
+        }
+    }
+
+    #[test]
+    fn test_differences_within_the_tolerance_match() {
+        let snapshots = scratch("tolerance").with_tolerance(4);
+        snapshots
+            .check_or_bless("square", &square(Color::new_argb(0xFF808080)), true)
+            .unwrap();
+
+        snapshots
+            .check_or_bless("square", &square(Color::new_argb(0xFF838383)), false)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_primitives_match_snapshot() {
+        snapshots().assert("primitives", SIZE, |list| {
+            list.clear(Color::white());
+            list.draw_line(
+                Point2D { x: 4.0, y: 4.0 },
+                Point2D { x: 60.0, y: 20.0 },
+                2.0,
+                Color::black(),
+                None,
+            );
+            list.fill_rect(rect(4.0, 24.0, 28.0, 40.0), Color::cornflower_blue());
+            list.stroke_rect(rect(36.5, 24.5, 59.5, 39.5), Color::crimson(), 1.0, None);
+            list.fill_rounded_rect(
+                RoundedRect2D {
+                    rect: rect(4.0, 44.0, 28.0, 60.0),
+                    radius_x: 6.0,
+                    radius_y: 6.0,
+                },
+                Color::dark_slate_gray(),
+            );
+            list.stroke_ellipse(
+                Ellipse2D {
+                    center: Point2D { x: 47.0, y: 52.0 },
+                    radius_x: 12.0,
+                    radius_y: 7.0,
+                },
+                Color::green(),
+                3.0,
+                None,
+            );
+        });
+    }
+
+    #[test]
+    fn test_stroke_styles_match_snapshot() {
+        let mut zigzag = Path::new();
+        zigzag
+            .move_to(Point2D { x: 8.0, y: 12.0 })
+            .line_to(Point2D { x: 24.0, y: 4.0 })
+            .line_to(Point2D { x: 40.0, y: 12.0 })
+            .line_to(Point2D { x: 56.0, y: 4.0 });
+
+        snapshots().assert("stroke_styles", SIZE, |list| {
+            list.clear(Color::white());
+            for (i, line_join) in [LineJoin::Miter, LineJoin::Bevel, LineJoin::Round]
+                .into_iter()
+                .enumerate()
+            {
+                list.push_transform(Transform2D::translation(0.0, i as f32 * 14.0));
+                list.stroke_path(
+                    zigzag.clone(),
+                    Color::black(),
+                    4.0,
+                    Some(StrokeStyleProperties {
+                        start_cap: CapStyle::Round,
+                        end_cap: CapStyle::Triangle,
+                        line_join,
+                        ..Default::default()
+                    }),
+                );
+                list.pop_transform();
+            }
+            list.draw_line(
+                Point2D { x: 4.0, y: 52.0 },
+                Point2D { x: 60.0, y: 52.0 },
+                3.0,
+                Color::crimson(),
+                Some(StrokeStyleProperties {
+                    dash_style: DashStyle::DashDot,
+                    dash_cap: CapStyle::Round,
+                    ..Default::default()
+                }),
+            );
+        });
+    }
+
+    #[test]
+    fn test_paths_and_brushes_match_snapshot() {
+        let mut star = Path::new().with_fill_mode(FillMode::Alternate);
+        star.move_to(Point2D { x: 32.0, y: 4.0 });
+        for i in 1..5 {
+            let angle = i as f32 * 4.0 * ::std::f32::consts::PI / 5.0;
+            star.line_to(Point2D {
+                x: 32.0 + 28.0 * angle.sin(),
+                y: 32.0 - 28.0 * angle.cos(),
+            });
+        }
+        star.close();
+
+        let mut arc = Path::new();
+        arc.move_to(Point2D { x: 8.0, y: 60.0 }).arc_to(ArcSegment {
+            to: Point2D { x: 56.0, y: 60.0 },
+            radius: Size2D {
+                width: 24.0,
+                height: 12.0,
+            },
+            rotation: 0.0,
+            sweep: SweepDirection::Clockwise,
+            arc_size: ArcSize::Small,
+        });
+
+        snapshots().assert("paths_and_brushes", SIZE, |list| {
+            list.clear(Color::white());
+            list.fill_path(
+                star,
+                BrushSpec::LinearGradient {
+                    gradient: Gradient::two_stop(Color::gold(), Color::crimson()),
+                    start: Point2D { x: 0.0, y: 0.0 },
+                    end: Point2D { x: 64.0, y: 64.0 },
+                },
+            );
+            list.stroke_path(
+                arc,
+                BrushSpec::RadialGradient {
+                    gradient: Gradient::two_stop(Color::blue(), Color::green()),
+                    ellipse: Ellipse2D {
+                        center: Point2D { x: 32.0, y: 60.0 },
+                        radius_x: 24.0,
+                        radius_y: 12.0,
+                    },
+                    origin_offset: Point2D { x: 0.0, y: 0.0 },
+                },
+                3.0,
+                None,
+            );
+        });
+    }
+
+    #[test]
+    fn test_transforms_clips_and_layers_match_snapshot() {
+        snapshots().assert("transforms_clips_and_layers", SIZE, |list| {
+            list.clear(Color::white());
+
+            list.push_transform(Transform2D::rotation_about(
+                ::std::f32::consts::FRAC_PI_4,
+                Point2D { x: 32.0, y: 32.0 },
+            ));
+            list.fill_rect(rect(20.0, 20.0, 44.0, 44.0), Color::cornflower_blue());
+            list.pop_transform();
+
+            list.push_clip_rect(rect(0.0, 0.0, 32.0, 64.0), AntialiasMode::Aliased);
+            list.fill_ellipse(
+                Ellipse2D {
+                    center: Point2D { x: 32.0, y: 32.0 },
+                    radius_x: 10.0,
+                    radius_y: 10.0,
+                },
+                Color::crimson(),
+            );
+            list.pop_clip();
+
+            list.push_layer(LayerSpec {
+                opacity: 0.5,
+                ..Default::default()
+            });
+            list.fill_rect(rect(4.0, 48.0, 40.0, 60.0), Color::black());
+            list.fill_rect(rect(24.0, 52.0, 60.0, 62.0), Color::black());
+            list.pop_layer();
+        });
+    }
+}
@@ -0,0 +1,339 @@
+//! Flattening of shapes and paths into polylines.
+
+use super::vec2::Vec2;
+use crate::path::{ArcSegment, ArcSize, Path, PathSegment, SweepDirection};
+use ::std::f32::consts::{FRAC_PI_2, PI, TAU};
+use ::win_geom::d2::{Ellipse2D, Rect2D, RoundedRect2D};
+
+/// The maximum distance, in pixels, between a flattened curve and the true
+/// curve.
+const TOLERANCE: f32 = 0.01;
+
+/// A sequence of connected line segments.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub(super) struct Polyline {
+    pub(super) points: Vec<Vec2>,
+    /// Whether the last point connects back to the first.
+    pub(super) closed: bool,
+}
+
+impl Polyline {
+    /// Appends a point, skipping it if it duplicates the previous point.
+    fn push(&mut self, point: Vec2) {
+        if self.points.last() != Some(&point) {
+            self.points.push(point);
+        }
+    }
+}
+
+/// The number of segments needed to flatten an arc of the given radius and
+/// sweep, where `scale` converts from local units to pixels.
+pub(super) fn arc_segments(radius: f32, sweep: f32, scale: f32) -> usize {
+    let radius = radius * scale;
+    if radius <= TOLERANCE {
+        return 1;
+    }
+    let step = 2.0 * (1.0 - TOLERANCE / radius).acos();
+    ((sweep.abs() / step).ceil() as usize).clamp(1, 1024)
+}
+
+/// Appends the points of an elliptical arc, excluding its start point.
+fn push_arc(
+    line: &mut Polyline,
+    center: Vec2,
+    radii: Vec2,
+    rotation: f32,
+    start: f32,
+    sweep: f32,
+    scale: f32,
+) {
+    let (sin, cos) = rotation.sin_cos();
+    let n = arc_segments(radii.x.max(radii.y), sweep, scale);
+    for i in 1..=n {
+        let angle = start + sweep * (i as f32 / n as f32);
+        let p = Vec2::new(radii.x * angle.cos(), radii.y * angle.sin());
+        line.push(center + Vec2::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos));
+    }
+}
+
+/// The outline of a rectangle, clockwise from its top-left corner.
+pub(super) fn rect(rect: Rect2D<f32>) -> Polyline {
+    Polyline {
+        points: vec![
+            Vec2::new(rect.left, rect.top),
+            Vec2::new(rect.right, rect.top),
+            Vec2::new(rect.right, rect.bottom),
+            Vec2::new(rect.left, rect.bottom),
+        ],
+        closed: true,
+    }
+}
+
+/// The outline of a rounded rectangle. Radii are clamped to half the
+/// rectangle's size, as Direct2D does.
+pub(super) fn rounded_rect(rect: RoundedRect2D<f32>, scale: f32) -> Polyline {
+    let r = rect.rect;
+    let rx = rect.radius_x.abs().min((r.right - r.left).abs() / 2.0);
+    let ry = rect.radius_y.abs().min((r.bottom - r.top).abs() / 2.0);
+    if rx <= 0.0 || ry <= 0.0 {
+        return self::rect(r);
+    }
+
+    let radii = Vec2::new(rx, ry);
+    let mut line = Polyline {
+        points: Vec::new(),
+        closed: true,
+    };
+    let corners = [
+        (Vec2::new(r.left + rx, r.top + ry), PI),
+        (Vec2::new(r.right - rx, r.top + ry), -FRAC_PI_2),
+        (Vec2::new(r.right - rx, r.bottom - ry), 0.0),
+        (Vec2::new(r.left + rx, r.bottom - ry), FRAC_PI_2),
+    ];
+    for (center, start) in corners {
+        line.push(center + Vec2::new(rx * start.cos(), ry * start.sin()));
+        push_arc(&mut line, center, radii, 0.0, start, FRAC_PI_2, scale);
+    }
+    line
+}
+
+/// The outline of an ellipse, clockwise from its rightmost point.
+pub(super) fn ellipse(ellipse: Ellipse2D<f32>, scale: f32) -> Polyline {
+    let center = Vec2::from(ellipse.center);
+    let radii = Vec2::new(ellipse.radius_x.abs(), ellipse.radius_y.abs());
+    let mut line = Polyline {
+        points: vec![center + Vec2::new(radii.x, 0.0)],
+        closed: true,
+    };
+    push_arc(&mut line, center, radii, 0.0, 0.0, TAU, scale);
+    // The final point duplicates the first.
+    line.points.pop();
+    line
+}
+
+/// Flattens each figure of a path.
+pub(super) fn path(path: &Path, scale: f32) -> Vec<Polyline> {
+    let mut figures = Vec::new();
+    let mut line = Polyline::default();
+
+    for segment in path.segments() {
+        let current = line.points.last().copied().unwrap_or_default();
+        match *segment {
+            PathSegment::MoveTo(to) => {
+                if !line.points.is_empty() {
+                    figures.push(::std::mem::take(&mut line));
+                }
+                line.points.push(to.into());
+            }
+            PathSegment::LineTo(to) => line.push(to.into()),
+            PathSegment::QuadTo { ctrl, to } => {
+                // Elevate to a cubic with the same shape.
+                let (ctrl, to) = (Vec2::from(ctrl), Vec2::from(to));
+                let ctrl1 = current + (ctrl - current) * (2.0 / 3.0);
+                let ctrl2 = to + (ctrl - to) * (2.0 / 3.0);
+                push_cubic(&mut line, [current, ctrl1, ctrl2, to], scale);
+            }
+            PathSegment::CubicTo { ctrl1, ctrl2, to } => {
+                push_cubic(
+                    &mut line,
+                    [current, ctrl1.into(), ctrl2.into(), to.into()],
+                    scale,
+                );
+            }
+            PathSegment::ArcTo(arc) => push_arc_segment(&mut line, current, arc, scale),
+            PathSegment::Close => {
+                line.closed = true;
+                let start = line.points[0];
+                if line.points.len() > 1 && line.points.last() == Some(&start) {
+                    line.points.pop();
+                }
+                figures.push(::std::mem::take(&mut line));
+            }
+        }
+    }
+
+    if !line.points.is_empty() {
+        figures.push(line);
+    }
+    figures
+}
+
+/// Appends the points of a cubic Bézier curve, excluding its start point.
+fn push_cubic(line: &mut Polyline, [p0, p1, p2, p3]: [Vec2; 4], scale: f32) {
+    // The second differences bound how far the curve strays from its chords.
+    let dd = (p0 - p1 * 2.0 + p2)
+        .length()
+        .max((p1 - p2 * 2.0 + p3).length())
+        * scale;
+    let n = ((0.75 * dd / TOLERANCE).sqrt().ceil() as usize).clamp(1, 1024);
+
+    for i in 1..=n {
+        let t = i as f32 / n as f32;
+        let u = 1.0 - t;
+        line.push(
+            p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t),
+        );
+    }
+}
+
+/// Appends the points of an arc segment, excluding its start point. Follows
+/// the endpoint to center conversion of SVG's implementation notes.
+fn push_arc_segment(line: &mut Polyline, from: Vec2, arc: ArcSegment, scale: f32) {
+    let to = Vec2::from(arc.to);
+    let (mut rx, mut ry) = (arc.radius.width.abs(), arc.radius.height.abs());
+    if from == to {
+        return;
+    }
+    if rx == 0.0 || ry == 0.0 {
+        line.push(to);
+        return;
+    }
+
+    let (sin, cos) = arc.rotation.sin_cos();
+    let half = (from - to) * 0.5;
+    let p = Vec2::new(cos * half.x + sin * half.y, -sin * half.x + cos * half.y);
+
+    // Scale up radii which are too small to span the end points.
+    let lambda = (p.x * p.x) / (rx * rx) + (p.y * p.y) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let clockwise = arc.sweep == SweepDirection::Clockwise;
+    let large = arc.arc_size == ArcSize::Large;
+    let num = rx * rx * ry * ry - rx * rx * p.y * p.y - ry * ry * p.x * p.x;
+    let den = rx * rx * p.y * p.y + ry * ry * p.x * p.x;
+    let mut k = (num / den).max(0.0).sqrt();
+    if large == clockwise {
+        k = -k;
+    }
+    let c = Vec2::new(k * rx * p.y / ry, -k * ry * p.x / rx);
+    let center = Vec2::new(cos * c.x - sin * c.y, sin * c.x + cos * c.y) + (from + to) * 0.5;
+
+    let angle = |v: Vec2| v.y.atan2(v.x);
+    let start = angle(Vec2::new((p.x - c.x) / rx, (p.y - c.y) / ry));
+    let end = angle(Vec2::new((-p.x - c.x) / rx, (-p.y - c.y) / ry));
+    let mut sweep = end - start;
+    if clockwise && sweep < 0.0 {
+        sweep += TAU;
+    } else if !clockwise && sweep > 0.0 {
+        sweep -= TAU;
+    }
+
+    push_arc(
+        line,
+        center,
+        Vec2::new(rx, ry),
+        arc.rotation,
+        start,
+        sweep,
+        scale,
+    );
+    // Land exactly on the end point despite rounding.
+    line.points.pop();
+    line.push(to);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::win_geom::d2::{Point2D, Size2D};
+
+    fn assert_near(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-3, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_ellipse_points_lie_on_the_ellipse() {
+        let line = ellipse(
+            Ellipse2D {
+                center: Point2D { x: 10.0, y: 20.0 },
+                radius_x: 8.0,
+                radius_y: 4.0,
+            },
+            1.0,
+        );
+
+        assert!(line.closed);
+        assert!(line.points.len() >= 16);
+        for p in &line.points {
+            let (dx, dy) = ((p.x - 10.0) / 8.0, (p.y - 20.0) / 4.0);
+            assert!((dx * dx + dy * dy - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_path_figures_are_split_and_closed() {
+        let mut p = Path::new();
+        p.move_to(Point2D { x: 0.0, y: 0.0 })
+            .line_to(Point2D { x: 10.0, y: 0.0 })
+            .line_to(Point2D { x: 0.0, y: 0.0 })
+            .close()
+            .move_to(Point2D { x: 5.0, y: 5.0 })
+            .line_to(Point2D { x: 6.0, y: 5.0 });
+
+        let figures = path(&p, 1.0);
+
+        assert_eq!(figures.len(), 2);
+        assert!(figures[0].closed);
+        assert_eq!(figures[0].points.len(), 2);
+        assert!(!figures[1].closed);
+    }
+
+    #[test]
+    fn test_arcs_follow_the_requested_sweep() {
+        let arc = |sweep| ArcSegment {
+            to: Point2D { x: 10.0, y: 0.0 },
+            radius: Size2D {
+                width: 5.0,
+                height: 5.0,
+            },
+            rotation: 0.0,
+            sweep,
+            arc_size: ArcSize::Small,
+        };
+
+        // A half circle from (0, 0) to (10, 0) bulges upwards when swept
+        // clockwise, since y points down.
+        let mut line = Polyline::default();
+        push_arc_segment(
+            &mut line,
+            Vec2::default(),
+            arc(SweepDirection::Clockwise),
+            1.0,
+        );
+        let highest = line.points.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        assert!((highest + 5.0).abs() <= TOLERANCE);
+        assert_near(*line.points.last().unwrap(), Vec2::new(10.0, 0.0));
+
+        let mut line = Polyline::default();
+        push_arc_segment(
+            &mut line,
+            Vec2::default(),
+            arc(SweepDirection::CounterClockwise),
+            1.0,
+        );
+        let lowest = line.points.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        assert!((lowest - 5.0).abs() <= TOLERANCE);
+    }
+
+    #[test]
+    fn test_cubic_ends_at_its_end_point() {
+        let mut line = Polyline::default();
+        push_cubic(
+            &mut line,
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(0.0, 10.0),
+                Vec2::new(10.0, 10.0),
+                Vec2::new(10.0, 0.0),
+            ],
+            1.0,
+        );
+
+        assert!(line.points.len() > 4);
+        assert_near(*line.points.last().unwrap(), Vec2::new(10.0, 0.0));
+    }
+}
@@ -0,0 +1,707 @@
+//! A pure-Rust CPU rasterizer which renders [`DrawList`]s into memory.
+//!
+//! The [`SoftwareRenderTarget`] draws the same primitives as a Direct2D
+//! [`Context`](crate::Context), with antialiased edges, but without touching
+//! Direct2D. It can therefore run on any platform, e.g. for golden-image tests
+//! of a scene on a CI host or for rendering thumbnails headlessly.
+//!
+//! Output is close to, but not bit-identical with, Direct2D's. Comparisons
+//! against images rendered by Direct2D should allow a small tolerance.
+
+mod flatten;
+mod paint;
+mod raster;
+mod stroker;
+mod vec2;
+
+use self::{
+    paint::{premultiply, ImagePaint, Paint, Rgba},
+    raster::{rasterize, Coverage},
+    vec2::{max_scale, Vec2},
+};
+use crate::{
+    context::TransformStack,
+    draw_list::{BrushSpec, DrawCommand, DrawList, LayerSpec, ScopeKind, ScopeStack},
+    gradient::ExtendMode,
+    imaging::Image,
+    layer::AntialiasMode,
+    path::FillMode,
+    stroke::StrokeStyleProperties,
+};
+use ::win_geom::d2::{Rect2D, Size2D, Transform2D};
+
+/// A render target which rasterizes [`DrawList`]s on the CPU into an in-memory
+/// RGBA buffer.
+///
+/// The target starts out transparent and accumulates everything rendered into
+/// it until it is [cleared](DrawCommand::Clear). All brushes, stroke styles,
+/// paths, clips and layers are supported.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{Color, DrawList, SoftwareRenderTarget};
+/// use ::win_geom::d2::{Rect2D, Size2D};
+///
+/// let mut list = DrawList::new();
+/// list.clear(Color::white());
+/// list.fill_rect(Rect2D { left: 0.0, top: 0.0, right: 2.0, bottom: 2.0 }, Color::red());
+///
+/// let mut target = SoftwareRenderTarget::new(Size2D { width: 4, height: 4 });
+/// target.render(&list);
+///
+/// let rgba = target.to_image().to_rgba();
+/// assert_eq!(rgba[..4], [255, 0, 0, 255]);
+/// assert_eq!(rgba[12..16], [255, 255, 255, 255]);
+/// ```
+#[derive(Debug)]
+pub struct SoftwareRenderTarget {
+    size: Size2D<u32>,
+    /// The target's pixels, followed by the pixels of each pushed layer.
+    surfaces: Vec<Vec<Rgba>>,
+    transforms: TransformStack,
+    /// The clip currently in effect, in device space.
+    clip: Option<ClipRect>,
+    /// The clips and layers currently pushed, innermost last.
+    scopes: ScopeStack<Scope>,
+}
+
+/// An axis-aligned clip in device space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ClipRect {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+impl ClipRect {
+    /// The fraction of the pixel at `(x, y)` within the clip.
+    fn coverage(&self, x: usize, y: usize) -> f32 {
+        let (x, y) = (x as f32, y as f32);
+        let overlap = |lo: f32, hi: f32, p: f32| (hi.min(p + 1.0) - lo.max(p)).clamp(0.0, 1.0);
+        overlap(self.left, self.right, x) * overlap(self.top, self.bottom, y)
+    }
+
+    fn intersect(&self, other: &ClipRect) -> ClipRect {
+        ClipRect {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        }
+    }
+}
+
+/// A pushed clip or layer, holding whatever is needed to pop it.
+#[derive(Debug)]
+enum Scope {
+    /// Restores the clip which was in effect before the push.
+    Clip(Option<ClipRect>),
+    Layer(Layer),
+}
+
+/// A pushed layer, whose content is drawn into its own surface and composited
+/// onto the surface beneath when popped.
+#[derive(Debug)]
+struct Layer {
+    opacity: f32,
+    /// Coverage masks multiplied against the layer's content. A `None` mask
+    /// covers no pixels at all.
+    masks: Vec<Option<Coverage>>,
+    /// The opacity brush and the world transform it was pushed with.
+    opacity_brush: Option<(BrushSpec, Transform2D)>,
+}
+
+impl SoftwareRenderTarget {
+    /// Constructs a new, transparent render target of the given size in
+    /// pixels. As with a Direct2D target at 96 DPI, one device-independent
+    /// pixel maps to one pixel.
+    pub fn new(size: Size2D<u32>) -> Self {
+        Self {
+            size,
+            surfaces: vec![vec![[0.0; 4]; size.width as usize * size.height as usize]],
+            transforms: TransformStack::default(),
+            clip: None,
+            scopes: ScopeStack::default(),
+        }
+    }
+
+    /// The size of the target, in pixels.
+    pub fn size(&self) -> Size2D<u32> {
+        self.size
+    }
+
+    /// Renders all commands in `list` on top of the target's current content.
+    ///
+    /// Unbalanced [clips and layers](DrawList#clips-and-layers) are handled as
+    /// for any other backend. The world transform is reset to the identity
+    /// afterwards, as at the end of a Direct2D frame.
+    pub fn render(&mut self, list: &DrawList) {
+        for command in list.commands() {
+            self.execute(command);
+        }
+
+        while let Some(scope) = self.scopes.pop_innermost() {
+            self.close_scope(scope);
+        }
+        self.transforms = TransformStack::default();
+    }
+
+    /// Copies the target's content into an [`Image`], e.g. to encode it or
+    /// compare it against a reference.
+    pub fn to_image(&self) -> Image {
+        let to_byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let bgra = self.surfaces[0]
+            .iter()
+            .flat_map(|&[r, g, b, a]| [to_byte(b), to_byte(g), to_byte(r), to_byte(a)])
+            .collect();
+
+        Image::from_premultiplied_bgra(self.size, bgra)
+            .expect("surface has one pixel per pixel of the target")
+    }
+
+    fn width(&self) -> usize {
+        self.size.width as usize
+    }
+
+    fn height(&self) -> usize {
+        self.size.height as usize
+    }
+
+    fn execute(&mut self, command: &DrawCommand) {
+        let scale = max_scale(&self.transforms.current());
+        match command {
+            DrawCommand::Clear(color) => self.clear(premultiply(*color)),
+            DrawCommand::DrawLine {
+                p0,
+                p1,
+                stroke_width,
+                brush,
+                style,
+            } => {
+                let line = flatten::Polyline {
+                    points: vec![(*p0).into(), (*p1).into()],
+                    closed: false,
+                };
+                self.stroke(&[line], *stroke_width, style.as_ref(), brush);
+            }
+            DrawCommand::FillRect { rect, brush } => {
+                self.fill(&[flatten::rect(*rect)], FillMode::Winding, brush);
+            }
+            DrawCommand::StrokeRect {
+                rect,
+                brush,
+                stroke_width,
+                style,
+            } => self.stroke(
+                &[flatten::rect(*rect)],
+                *stroke_width,
+                style.as_ref(),
+                brush,
+            ),
+            DrawCommand::FillRoundedRect { rect, brush } => {
+                let line = flatten::rounded_rect(*rect, scale);
+                self.fill(&[line], FillMode::Winding, brush);
+            }
+            DrawCommand::StrokeRoundedRect {
+                rect,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let line = flatten::rounded_rect(*rect, scale);
+                self.stroke(&[line], *stroke_width, style.as_ref(), brush);
+            }
+            DrawCommand::FillEllipse { ellipse, brush } => {
+                let line = flatten::ellipse(*ellipse, scale);
+                self.fill(&[line], FillMode::Winding, brush);
+            }
+            DrawCommand::StrokeEllipse {
+                ellipse,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let line = flatten::ellipse(*ellipse, scale);
+                self.stroke(&[line], *stroke_width, style.as_ref(), brush);
+            }
+            DrawCommand::FillPath { path, brush } => {
+                let lines = flatten::path(path, scale);
+                self.fill(&lines, path.fill_mode(), brush);
+            }
+            DrawCommand::StrokePath {
+                path,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let lines = flatten::path(path, scale);
+                self.stroke(&lines, *stroke_width, style.as_ref(), brush);
+            }
+            DrawCommand::DrawBitmap {
+                image,
+                dest,
+                opacity,
+                interpolation,
+                source,
+            } => {
+                let size = image.size();
+                let source = source.unwrap_or(Rect2D {
+                    left: 0.0,
+                    top: 0.0,
+                    right: size.width as f32,
+                    bottom: size.height as f32,
+                });
+                let (dest_width, dest_height) = (dest.right - dest.left, dest.bottom - dest.top);
+                if dest_width == 0.0 || dest_height == 0.0 {
+                    return;
+                }
+
+                let dest_to_source = Transform2D::translation(-dest.left, -dest.top)
+                    .then(Transform2D::scale(
+                        (source.right - source.left) / dest_width,
+                        (source.bottom - source.top) / dest_height,
+                    ))
+                    .then(Transform2D::translation(source.left, source.top));
+                let Some(inverse) = self.transforms.current().invert() else {
+                    return;
+                };
+                let paint = Paint::Image(ImagePaint {
+                    image,
+                    inverse: inverse.then(dest_to_source),
+                    extend: (ExtendMode::Clamp, ExtendMode::Clamp),
+                    interpolation: *interpolation,
+                    opacity: *opacity,
+                });
+                let polygons = self.to_device(&[flatten::rect(*dest)]);
+                self.fill_with(&polygons, FillMode::Winding, &paint);
+            }
+            DrawCommand::SetTransform(transform) => self.transforms.set(*transform),
+            DrawCommand::PushTransform(transform) => self.transforms.push(*transform),
+            DrawCommand::PopTransform => {
+                self.transforms.pop();
+            }
+            DrawCommand::PushClipRect { rect, antialias } => self.push_clip(*rect, *antialias),
+            DrawCommand::PushLayer(layer) => self.push_layer(layer),
+            DrawCommand::PopClip => self.pop_scope(ScopeKind::Clip),
+            DrawCommand::PopLayer => self.pop_scope(ScopeKind::Layer),
+        }
+    }
+
+    /// Transforms local polylines into device space polygons.
+    fn to_device(&self, lines: &[flatten::Polyline]) -> Vec<Vec<Vec2>> {
+        let world = self.transforms.current();
+        lines
+            .iter()
+            .map(|line| line.points.iter().map(|p| p.transform(&world)).collect())
+            .collect()
+    }
+
+    /// Replaces the content within the current clip with `color`, ignoring the
+    /// world transform as Direct2D does.
+    fn clear(&mut self, color: Rgba) {
+        let (width, clip) = (self.width(), self.clip);
+        let surface = self.surfaces.last_mut().expect("target has a surface");
+        for (i, pixel) in surface.iter_mut().enumerate() {
+            let c = clip.map_or(1.0, |clip| clip.coverage(i % width, i / width));
+            *pixel = ::std::array::from_fn(|j| color[j] * c + pixel[j] * (1.0 - c));
+        }
+    }
+
+    fn fill(&mut self, lines: &[flatten::Polyline], fill_mode: FillMode, brush: &BrushSpec) {
+        let polygons = self.to_device(lines);
+        let paint = Paint::new(brush, self.transforms.current());
+        self.fill_with(&polygons, fill_mode, &paint);
+    }
+
+    fn stroke(
+        &mut self,
+        lines: &[flatten::Polyline],
+        width: f32,
+        style: Option<&StrokeStyleProperties>,
+        brush: &BrushSpec,
+    ) {
+        let default_style = StrokeStyleProperties::default();
+        let style = style.unwrap_or(&default_style);
+        let scale = max_scale(&self.transforms.current());
+        let outline = stroker::stroke(lines, width, style, scale);
+
+        let world = self.transforms.current();
+        let polygons: Vec<Vec<Vec2>> = outline
+            .into_iter()
+            .map(|polygon| polygon.into_iter().map(|p| p.transform(&world)).collect())
+            .collect();
+        let paint = Paint::new(brush, world);
+        self.fill_with(&polygons, FillMode::Winding, &paint);
+    }
+
+    /// Composites `paint` over the current surface wherever the device space
+    /// polygons cover it.
+    fn fill_with(&mut self, polygons: &[Vec<Vec2>], fill_mode: FillMode, paint: &Paint<'_>) {
+        let Some(coverage) = rasterize(polygons, fill_mode, self.width(), self.height()) else {
+            return;
+        };
+
+        let (width, clip) = (self.width(), self.clip);
+        let surface = self.surfaces.last_mut().expect("target has a surface");
+        coverage.for_each(|x, y, c| {
+            let c = c * clip.map_or(1.0, |clip| clip.coverage(x, y));
+            if c > 0.0 {
+                let color = paint.sample(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+                blend(&mut surface[y * width + x], color, c);
+            }
+        });
+    }
+
+    fn push_clip(&mut self, rect: Rect2D<f32>, antialias: AntialiasMode) {
+        // As in Direct2D, a transformed clip is replaced by its bounds.
+        let corners = flatten::rect(rect).points;
+        let world = self.transforms.current();
+        let device: Vec<Vec2> = corners.iter().map(|p| p.transform(&world)).collect();
+        let mut clip = ClipRect {
+            left: device.iter().map(|p| p.x).fold(f32::INFINITY, f32::min),
+            top: device.iter().map(|p| p.y).fold(f32::INFINITY, f32::min),
+            right: device.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max),
+            bottom: device.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max),
+        };
+        if antialias == AntialiasMode::Aliased {
+            clip = ClipRect {
+                left: clip.left.round(),
+                top: clip.top.round(),
+                right: clip.right.round(),
+                bottom: clip.bottom.round(),
+            };
+        }
+
+        let previous = self.clip;
+        self.clip = Some(previous.map_or(clip, |previous| previous.intersect(&clip)));
+        self.scopes.push(ScopeKind::Clip, Scope::Clip(previous));
+    }
+
+    fn push_layer(&mut self, spec: &LayerSpec) {
+        let world = self.transforms.current();
+        let (width, height) = (self.width(), self.height());
+        let mut masks = Vec::new();
+
+        if let Some(bounds) = spec.content_bounds {
+            let polygons = self.to_device(&[flatten::rect(bounds)]);
+            masks.push(rasterize(&polygons, FillMode::Winding, width, height));
+        }
+        if let Some(path) = &spec.mask {
+            let transform = spec.mask_transform.then(world);
+            let polygons: Vec<Vec<Vec2>> = flatten::path(path, max_scale(&transform))
+                .into_iter()
+                .map(|line| {
+                    line.points
+                        .iter()
+                        .map(|p| p.transform(&transform))
+                        .collect()
+                })
+                .collect();
+            let mut mask = rasterize(&polygons, path.fill_mode(), width, height);
+            if spec.mask_antialias == AntialiasMode::Aliased {
+                if let Some(mask) = &mut mask {
+                    mask.threshold();
+                }
+            }
+            masks.push(mask);
+        }
+
+        self.scopes.push(
+            ScopeKind::Layer,
+            Scope::Layer(Layer {
+                opacity: spec.opacity,
+                masks,
+                opacity_brush: spec.opacity_brush.clone().map(|brush| (brush, world)),
+            }),
+        );
+        self.surfaces.push(vec![[0.0; 4]; width * height]);
+    }
+
+    /// Pops the innermost scope if it is of `kind`.
+    fn pop_scope(&mut self, kind: ScopeKind) {
+        if let Some(scope) = self.scopes.pop(kind) {
+            self.close_scope(scope);
+        }
+    }
+
+    fn close_scope(&mut self, scope: Scope) {
+        match scope {
+            Scope::Clip(previous) => self.clip = previous,
+            Scope::Layer(layer) => {
+                let content = self.surfaces.pop().expect("layer has a surface");
+                let paint = layer
+                    .opacity_brush
+                    .as_ref()
+                    .map(|(brush, world)| Paint::new(brush, *world));
+
+                let width = self.width();
+                let surface = self.surfaces.last_mut().expect("target has a surface");
+                for (i, (pixel, color)) in surface.iter_mut().zip(content).enumerate() {
+                    if color[3] <= 0.0 {
+                        continue;
+                    }
+                    let (x, y) = (i % width, i / width);
+                    let mut f = layer.opacity;
+                    for mask in &layer.masks {
+                        f *= mask.as_ref().map_or(0.0, |mask| mask.get(x, y));
+                    }
+                    if let Some(paint) = &paint {
+                        f *= paint.sample(Vec2::new(x as f32 + 0.5, y as f32 + 0.5))[3];
+                    }
+                    blend(pixel, color, f);
+                }
+            }
+        }
+    }
+}
+
+/// Composites `color`, scaled by `coverage`, over `pixel`.
+fn blend(pixel: &mut Rgba, color: Rgba, coverage: f32) {
+    let alpha = color[3] * coverage;
+    *pixel = ::std::array::from_fn(|i| color[i] * coverage + pixel[i] * (1.0 - alpha));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        color::Color,
+        path::Path,
+        stroke::{CapStyle, DashStyle},
+    };
+    use ::pretty_assertions::assert_eq;
+    use ::std::rc::Rc;
+    use ::win_geom::d2::{Ellipse2D, Point2D, RoundedRect2D};
+
+    fn render(width: u32, height: u32, list: &DrawList) -> Vec<[u8; 4]> {
+        let mut target = SoftwareRenderTarget::new(Size2D { width, height });
+        target.render(list);
+        target
+            .to_image()
+            .to_rgba()
+            .chunks(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect()
+    }
+
+    fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rect2D<f32> {
+        Rect2D {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+    #[test]
+    fn test_clear_fills_the_target() {
+        let mut list = DrawList::new();
+        list.clear(Color::red());
+
+        assert!(render(3, 2, &list).iter().all(|&p| p == RED));
+    }
+
+    #[test]
+    fn test_rects_are_antialiased_at_fractional_edges() {
+        let mut list = DrawList::new();
+        list.fill_rect(rect(1.0, 0.0, 2.5, 1.0), Color::red());
+
+        let pixels = render(4, 1, &list);
+        assert_eq!(pixels[0], CLEAR);
+        assert_eq!(pixels[1], RED);
+        assert_eq!(pixels[2], [255, 0, 0, 128]);
+        assert_eq!(pixels[3], CLEAR);
+    }
+
+    #[test]
+    fn test_strokes_are_centred_on_the_outline() {
+        let mut list = DrawList::new();
+        list.clear(Color::white());
+        list.stroke_rect(rect(2.0, 2.0, 8.0, 8.0), Color::red(), 2.0, None);
+
+        let pixels = render(10, 10, &list);
+        // Miter joins fill the corners.
+        assert_eq!(pixels[10 + 1], RED);
+        assert_eq!(pixels[2 * 10 + 2], RED);
+        assert_eq!(pixels[5 * 10 + 5], WHITE);
+        assert_eq!(pixels[5 * 10 + 8], RED);
+        assert_eq!(pixels[5 * 10 + 9], WHITE);
+    }
+
+    #[test]
+    fn test_ellipses_cover_their_area() {
+        let mut list = DrawList::new();
+        list.fill_ellipse(
+            Ellipse2D {
+                center: Point2D { x: 16.0, y: 16.0 },
+                radius_x: 10.0,
+                radius_y: 10.0,
+            },
+            Color::red(),
+        );
+
+        let pixels = render(32, 32, &list);
+        let alpha: f32 = pixels.iter().map(|p| p[3] as f32 / 255.0).sum();
+        assert!((alpha - ::std::f32::consts::PI * 100.0).abs() < 1.0);
+        assert_eq!(pixels[16 * 32 + 16], RED);
+        assert_eq!(pixels[16 * 32 + 5], CLEAR);
+    }
+
+    #[test]
+    fn test_rounded_rect_corners_are_cut() {
+        let mut list = DrawList::new();
+        list.fill_rounded_rect(
+            RoundedRect2D {
+                rect: rect(0.0, 0.0, 10.0, 10.0),
+                radius_x: 4.0,
+                radius_y: 4.0,
+            },
+            Color::red(),
+        );
+
+        let pixels = render(10, 10, &list);
+        assert_eq!(pixels[0], CLEAR);
+        assert_eq!(pixels[5], RED);
+        assert_eq!(pixels[5 * 10], RED);
+    }
+
+    #[test]
+    fn test_transforms_apply_to_shapes() {
+        let mut list = DrawList::new();
+        list.push_transform(Transform2D::translation(2.0, 1.0));
+        list.push_transform(Transform2D::scale(2.0, 2.0));
+        list.fill_rect(rect(0.0, 0.0, 1.0, 1.0), Color::red());
+        list.pop_transform();
+        list.pop_transform();
+        list.fill_rect(rect(0.0, 0.0, 1.0, 1.0), Color::white());
+
+        let pixels = render(4, 4, &list);
+        assert_eq!(pixels[0], WHITE);
+        assert_eq!(pixels[4 + 1], CLEAR);
+        assert_eq!(pixels[4 + 2], RED);
+        assert_eq!(pixels[2 * 4 + 3], RED);
+        assert_eq!(pixels[3 * 4 + 3], CLEAR);
+    }
+
+    #[test]
+    fn test_clips_restrict_drawing_until_popped() {
+        let mut list = DrawList::new();
+        list.push_clip_rect(rect(0.0, 0.0, 2.0, 4.0), AntialiasMode::Aliased);
+        list.clear(Color::red());
+        list.pop_clip();
+        list.fill_rect(rect(3.0, 0.0, 4.0, 1.0), Color::white());
+
+        let pixels = render(4, 1, &list);
+        assert_eq!(pixels, [RED, RED, CLEAR, WHITE]);
+    }
+
+    #[test]
+    fn test_layers_are_composited_with_their_opacity() {
+        let mut list = DrawList::new();
+        list.clear(Color::white());
+        list.push_layer(LayerSpec {
+            opacity: 0.5,
+            content_bounds: Some(rect(0.0, 0.0, 1.0, 1.0)),
+            ..Default::default()
+        });
+        // Overlapping fills within a layer do not show through each other.
+        list.fill_rect(rect(0.0, 0.0, 2.0, 1.0), Color::red());
+        list.fill_rect(rect(0.0, 0.0, 2.0, 1.0), Color::red());
+        list.pop_layer();
+
+        let pixels = render(2, 1, &list);
+        assert_eq!(pixels, [[255, 128, 128, 255], WHITE]);
+    }
+
+    #[test]
+    fn test_pops_of_the_wrong_kind_are_ignored() {
+        let mut list = DrawList::new();
+        list.push_clip_rect(rect(0.0, 0.0, 3.0, 1.0), AntialiasMode::Aliased);
+        list.push_layer(LayerSpec::default());
+        // The innermost scope is a layer, so this does not end the clip.
+        list.pop_clip();
+        list.fill_rect(rect(0.0, 0.0, 2.0, 1.0), Color::red());
+        list.pop_layer();
+        list.fill_rect(rect(2.0, 0.0, 4.0, 1.0), Color::white());
+        list.pop_clip();
+
+        let pixels = render(4, 1, &list);
+        assert_eq!(pixels, [RED, RED, WHITE, CLEAR]);
+    }
+
+    #[test]
+    fn test_even_odd_paths_leave_holes() {
+        let mut path = Path::new();
+        for r in [rect(0.0, 0.0, 3.0, 1.0), rect(1.0, 0.0, 2.0, 1.0)] {
+            path.move_to(Point2D {
+                x: r.left,
+                y: r.top,
+            })
+            .line_to(Point2D {
+                x: r.right,
+                y: r.top,
+            })
+            .line_to(Point2D {
+                x: r.right,
+                y: r.bottom,
+            })
+            .line_to(Point2D {
+                x: r.left,
+                y: r.bottom,
+            })
+            .close();
+        }
+        let mut list = DrawList::new();
+        list.fill_path(Rc::new(path), Color::red());
+
+        assert_eq!(render(3, 1, &list), [RED, CLEAR, RED]);
+    }
+
+    #[test]
+    fn test_dashed_lines_alternate() {
+        let mut list = DrawList::new();
+        list.draw_line(
+            Point2D { x: 0.0, y: 0.5 },
+            Point2D { x: 8.0, y: 0.5 },
+            1.0,
+            Color::red(),
+            Some(StrokeStyleProperties {
+                dash_style: DashStyle::Dash,
+                dash_cap: CapStyle::Flat,
+                ..Default::default()
+            }),
+        );
+
+        let pixels = render(8, 1, &list);
+        assert_eq!(pixels, [RED, RED, CLEAR, CLEAR, RED, RED, CLEAR, CLEAR]);
+    }
+
+    #[test]
+    fn test_bitmaps_are_scaled_into_their_destination() {
+        let image = Image::from_rgba(
+            Size2D {
+                width: 2,
+                height: 1,
+            },
+            &[255, 0, 0, 255, 255, 255, 255, 255],
+        )
+        .unwrap();
+        let mut list = DrawList::new();
+        list.draw_bitmap(
+            Rc::new(image),
+            rect(0.0, 0.0, 4.0, 1.0),
+            1.0,
+            crate::bitmaps::InterpolationMode::NearestNeighbor,
+            None,
+        );
+
+        assert_eq!(render(4, 1, &list), [RED, RED, WHITE, WHITE]);
+    }
+}
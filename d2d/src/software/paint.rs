@@ -0,0 +1,270 @@
+//! Evaluation of brushes at individual pixels.
+
+use super::vec2::Vec2;
+use crate::{
+    bitmaps::InterpolationMode, color::Color, draw_list::BrushSpec, gradient::ExtendMode,
+    gradient::Gradient, imaging::Image,
+};
+use ::win_geom::d2::Transform2D;
+
+/// A premultiplied RGBA color with components between 0.0 and 1.0.
+pub(super) type Rgba = [f32; 4];
+
+/// Premultiplies a color's components by its alpha.
+pub(super) fn premultiply(color: Color) -> Rgba {
+    let a = color.alpha.clamp(0.0, 1.0);
+    [color.red * a, color.green * a, color.blue * a, a]
+}
+
+/// A brush prepared for sampling in device space.
+pub(super) enum Paint<'a> {
+    /// Paints nothing, e.g. because the world transform is singular.
+    None,
+    Solid(Rgba),
+    Linear {
+        gradient: &'a Gradient,
+        /// Maps from device space to brush space.
+        inverse: Transform2D,
+        start: Vec2,
+        /// The gradient axis, scaled by its inverse squared length.
+        axis: Vec2,
+    },
+    Radial {
+        gradient: &'a Gradient,
+        /// Maps from device space to the unit circle of the gradient ellipse.
+        inverse: Transform2D,
+        /// The gradient origin within the unit circle.
+        origin: Vec2,
+    },
+    Image(ImagePaint<'a>),
+}
+
+impl<'a> Paint<'a> {
+    /// Prepares a brush for drawing under the world transform `world`.
+    pub(super) fn new(brush: &'a BrushSpec, world: Transform2D) -> Self {
+        let Some(inverse) = world.invert() else {
+            return Self::None;
+        };
+
+        match brush {
+            BrushSpec::Solid(color) => Self::Solid(premultiply(*color)),
+            BrushSpec::LinearGradient {
+                gradient,
+                start,
+                end,
+            } => {
+                let start = Vec2::from(*start);
+                let axis = Vec2::from(*end) - start;
+                let len2 = axis.dot(axis);
+                Self::Linear {
+                    gradient,
+                    inverse,
+                    start,
+                    axis: if len2 > 0.0 {
+                        axis * (1.0 / len2)
+                    } else {
+                        axis
+                    },
+                }
+            }
+            BrushSpec::RadialGradient {
+                gradient,
+                ellipse,
+                origin_offset,
+            } => {
+                if ellipse.radius_x == 0.0 || ellipse.radius_y == 0.0 {
+                    return Self::None;
+                }
+                let (rx, ry) = (ellipse.radius_x, ellipse.radius_y);
+                let to_unit = Transform2D::translation(-ellipse.center.x, -ellipse.center.y)
+                    .then(Transform2D::scale(1.0 / rx, 1.0 / ry));
+                Self::Radial {
+                    gradient,
+                    inverse: inverse.then(to_unit),
+                    origin: Vec2::new(origin_offset.x / rx, origin_offset.y / ry),
+                }
+            }
+            BrushSpec::Bitmap { image, properties } => match properties.transform.invert() {
+                Some(to_image) => Self::Image(ImagePaint {
+                    image,
+                    inverse: inverse.then(to_image),
+                    extend: (properties.extend_mode_x, properties.extend_mode_y),
+                    interpolation: properties.interpolation,
+                    opacity: properties.opacity,
+                }),
+                None => Self::None,
+            },
+        }
+    }
+
+    /// The color at the device pixel center `p`.
+    pub(super) fn sample(&self, p: Vec2) -> Rgba {
+        match self {
+            Self::None => [0.0; 4],
+            Self::Solid(color) => *color,
+            Self::Linear {
+                gradient,
+                inverse,
+                start,
+                axis,
+            } => {
+                let t = (p.transform(inverse) - *start).dot(*axis);
+                premultiply(gradient.sample(t))
+            }
+            Self::Radial {
+                gradient,
+                inverse,
+                origin,
+            } => premultiply(gradient.sample(radial_position(p.transform(inverse), *origin))),
+            Self::Image(image) => image.sample(p),
+        }
+    }
+}
+
+/// The gradient position of `p` within a radial gradient whose ellipse is the
+/// unit circle and whose origin is at `origin`.
+///
+/// Each position `t` corresponds to the circle of radius `t` centred at
+/// `origin * (1 - t)`, so this solves `|p - origin * (1 - t)| = t` for `t`.
+fn radial_position(p: Vec2, origin: Vec2) -> f32 {
+    if origin == Vec2::default() {
+        return p.length();
+    }
+
+    let d = p - origin;
+    let a = origin.dot(origin) - 1.0;
+    let b = 2.0 * d.dot(origin);
+    let c = d.dot(d);
+    if a.abs() < 1e-6 {
+        return if b != 0.0 { -c / b } else { 0.0 };
+    }
+
+    let disc = (b * b - 4.0 * a * c).max(0.0).sqrt();
+    let (t0, t1) = ((-b + disc) / (2.0 * a), (-b - disc) / (2.0 * a));
+    t0.max(t1)
+}
+
+/// An image sampled as by a bitmap brush or
+/// [`DrawBitmap`](crate::DrawCommand::DrawBitmap).
+pub(super) struct ImagePaint<'a> {
+    pub(super) image: &'a Image,
+    /// Maps from device space to image pixel space.
+    pub(super) inverse: Transform2D,
+    /// How the image extends horizontally and vertically.
+    pub(super) extend: (ExtendMode, ExtendMode),
+    pub(super) interpolation: InterpolationMode,
+    pub(super) opacity: f32,
+}
+
+impl ImagePaint<'_> {
+    fn sample(&self, p: Vec2) -> Rgba {
+        let size = self.image.size();
+        if size.width == 0 || size.height == 0 {
+            return [0.0; 4];
+        }
+
+        let q = p.transform(&self.inverse);
+        let color = match self.interpolation {
+            InterpolationMode::NearestNeighbor => {
+                self.texel(q.x.floor() as i64, q.y.floor() as i64)
+            }
+            InterpolationMode::Linear => {
+                let (x, y) = (q.x - 0.5, q.y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let lerp = |a: Rgba, b: Rgba, f: f32| -> Rgba {
+                    ::std::array::from_fn(|i| a[i] + (b[i] - a[i]) * f)
+                };
+                let top = lerp(self.texel(x0, y0), self.texel(x0 + 1, y0), fx);
+                let bottom = lerp(self.texel(x0, y0 + 1), self.texel(x0 + 1, y0 + 1), fx);
+                lerp(top, bottom, fy)
+            }
+        };
+
+        color.map(|c| c * self.opacity)
+    }
+
+    /// The texel at integer coordinates, after applying the extend modes.
+    fn texel(&self, x: i64, y: i64) -> Rgba {
+        let size = self.image.size();
+        let x = extend(x, size.width as i64, self.extend.0);
+        let y = extend(y, size.height as i64, self.extend.1);
+        let i = (y * size.width as i64 + x) as usize * 4;
+        let bgra = &self.image.pixels()[i..i + 4];
+        [bgra[2], bgra[1], bgra[0], bgra[3]].map(|c| c as f32 / 255.0)
+    }
+}
+
+/// Maps a texel coordinate into `0..len` according to an extend mode.
+fn extend(i: i64, len: i64, mode: ExtendMode) -> i64 {
+    match mode {
+        ExtendMode::Clamp => i.clamp(0, len - 1),
+        ExtendMode::Wrap => i.rem_euclid(len),
+        ExtendMode::Mirror => {
+            let i = i.rem_euclid(2 * len);
+            if i < len {
+                i
+            } else {
+                2 * len - 1 - i
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+    use ::win_geom::d2::{Ellipse2D, Point2D};
+
+    #[test]
+    fn test_linear_gradients_follow_their_axis() {
+        let brush = BrushSpec::LinearGradient {
+            gradient: Gradient::two_stop(Color::black(), Color::white()),
+            start: Point2D { x: 0.0, y: 0.0 },
+            end: Point2D { x: 10.0, y: 0.0 },
+        };
+        let paint = Paint::new(&brush, Transform2D::scale(2.0, 2.0));
+
+        let color = paint.sample(Vec2::new(10.0, 3.0));
+        assert!((color[0] - 0.5).abs() < 1e-6);
+        assert_eq!(color[3], 1.0);
+    }
+
+    #[test]
+    fn test_radial_gradients_reach_the_final_stop_at_the_ellipse() {
+        let ellipse = Ellipse2D {
+            center: Point2D { x: 10.0, y: 10.0 },
+            radius_x: 10.0,
+            radius_y: 5.0,
+        };
+        let brush = BrushSpec::RadialGradient {
+            gradient: Gradient::two_stop(Color::black(), Color::white()),
+            ellipse,
+            origin_offset: Point2D { x: 0.0, y: 0.0 },
+        };
+        let paint = Paint::new(&brush, Transform2D::identity());
+
+        assert_eq!(paint.sample(Vec2::new(10.0, 10.0)), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(paint.sample(Vec2::new(10.0, 15.0)), [1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_offset_origins_start_the_gradient() {
+        let origin = Vec2::new(0.5, 0.0);
+
+        assert_eq!(radial_position(origin, origin), 0.0);
+        assert!((radial_position(Vec2::new(1.0, 0.0), origin) - 1.0).abs() < 1e-6);
+        assert!((radial_position(Vec2::new(-1.0, 0.0), origin) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_extend_modes_map_texel_coordinates() {
+        assert_eq!(extend(-1, 4, ExtendMode::Clamp), 0);
+        assert_eq!(extend(5, 4, ExtendMode::Wrap), 1);
+        assert_eq!(extend(4, 4, ExtendMode::Mirror), 3);
+        assert_eq!(extend(-1, 4, ExtendMode::Mirror), 0);
+    }
+}
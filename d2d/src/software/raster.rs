@@ -0,0 +1,280 @@
+//! Scanline rasterization of polygons into antialiased coverage masks.
+
+use super::vec2::Vec2;
+use crate::path::FillMode;
+
+/// The number of sub-scanlines sampled within each pixel row. Horizontal
+/// coverage is computed exactly, so this only bounds the precision of
+/// antialiasing along near-horizontal edges.
+const SUBSAMPLES: usize = 16;
+
+/// The fraction of a pixel covered by a shape, for each pixel within a
+/// rectangular region of the render target.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Coverage {
+    /// The leftmost column of the region.
+    pub(super) left: usize,
+    /// The topmost row of the region.
+    pub(super) top: usize,
+    /// The width of the region, in pixels.
+    pub(super) width: usize,
+    /// The height of the region, in pixels.
+    pub(super) height: usize,
+    /// Coverage values between 0.0 and 1.0, row by row.
+    values: Vec<f32>,
+}
+
+impl Coverage {
+    /// The coverage of the pixel at `(x, y)`, which is zero outside of the
+    /// region.
+    pub(super) fn get(&self, x: usize, y: usize) -> f32 {
+        if x < self.left || y < self.top {
+            return 0.0;
+        }
+        let (x, y) = (x - self.left, y - self.top);
+        if x >= self.width || y >= self.height {
+            return 0.0;
+        }
+        self.values[y * self.width + x]
+    }
+
+    /// Rounds partial coverage to full or none, for aliased edges.
+    pub(super) fn threshold(&mut self) {
+        for value in &mut self.values {
+            *value = if *value >= 0.5 { 1.0 } else { 0.0 };
+        }
+    }
+
+    /// Visits each pixel with non-zero coverage.
+    pub(super) fn for_each(&self, mut f: impl FnMut(usize, usize, f32)) {
+        for row in 0..self.height {
+            let values = &self.values[row * self.width..(row + 1) * self.width];
+            for (col, &value) in values.iter().enumerate() {
+                if value > 0.0 {
+                    f(self.left + col, self.top + row, value);
+                }
+            }
+        }
+    }
+}
+
+/// A non-horizontal polygon edge, oriented from top to bottom.
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    top: Vec2,
+    bottom: Vec2,
+    /// +1 if the polygon runs downwards along this edge, -1 if upwards.
+    winding: i32,
+}
+
+impl Edge {
+    fn new(a: Vec2, b: Vec2) -> Option<Self> {
+        if a.y == b.y || !(a.x.is_finite() && a.y.is_finite() && b.x.is_finite() && b.y.is_finite())
+        {
+            None
+        } else if a.y < b.y {
+            Some(Self {
+                top: a,
+                bottom: b,
+                winding: 1,
+            })
+        } else {
+            Some(Self {
+                top: b,
+                bottom: a,
+                winding: -1,
+            })
+        }
+    }
+
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        self.top.x + (self.bottom.x - self.top.x) * t
+    }
+}
+
+/// Rasterizes a set of closed polygons, which together form a single shape,
+/// into a render target of `width` x `height` pixels. Returns `None` if the
+/// shape covers no pixels.
+pub(super) fn rasterize(
+    polygons: &[Vec<Vec2>],
+    fill_mode: FillMode,
+    width: usize,
+    height: usize,
+) -> Option<Coverage> {
+    let mut edges: Vec<Edge> = polygons
+        .iter()
+        .flat_map(|polygon| {
+            let n = polygon.len();
+            (0..n).filter_map(move |i| Edge::new(polygon[i], polygon[(i + 1) % n]))
+        })
+        .collect();
+    if edges.is_empty() {
+        return None;
+    }
+
+    let (mut min, mut max) = (edges[0].top, edges[0].bottom);
+    for edge in &edges {
+        min.x = min.x.min(edge.top.x).min(edge.bottom.x);
+        max.x = max.x.max(edge.top.x).max(edge.bottom.x);
+        min.y = min.y.min(edge.top.y);
+        max.y = max.y.max(edge.bottom.y);
+    }
+
+    let left = min.x.floor().clamp(0.0, width as f32) as usize;
+    let right = max.x.ceil().clamp(0.0, width as f32) as usize;
+    let top = min.y.floor().clamp(0.0, height as f32) as usize;
+    let bottom = max.y.ceil().clamp(0.0, height as f32) as usize;
+    if left >= right || top >= bottom {
+        return None;
+    }
+
+    let mut coverage = Coverage {
+        left,
+        top,
+        width: right - left,
+        height: bottom - top,
+        values: vec![0.0; (right - left) * (bottom - top)],
+    };
+
+    edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+    let mut next_edge = 0;
+    let mut active: Vec<Edge> = Vec::new();
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    // Whole-pixel runs are accumulated as differences and summed per row.
+    let mut runs = vec![0.0f32; coverage.width + 1];
+    let weight = 1.0 / SUBSAMPLES as f32;
+
+    for row in top..bottom {
+        let row_top = row as f32;
+        let row_bottom = row_top + 1.0;
+
+        active.retain(|edge| edge.bottom.y > row_top);
+        while next_edge < edges.len() && edges[next_edge].top.y < row_bottom {
+            if edges[next_edge].bottom.y > row_top {
+                active.push(edges[next_edge]);
+            }
+            next_edge += 1;
+        }
+
+        let start = (row - top) * coverage.width;
+        let values = &mut coverage.values[start..start + coverage.width];
+        runs.fill(0.0);
+
+        for sample in 0..SUBSAMPLES {
+            let y = row_top + (sample as f32 + 0.5) * weight;
+
+            crossings.clear();
+            crossings.extend(
+                active
+                    .iter()
+                    .filter(|edge| edge.top.y <= y && y < edge.bottom.y)
+                    .map(|edge| (edge.x_at(y) - left as f32, edge.winding)),
+            );
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match fill_mode {
+                    FillMode::Alternate => winding % 2 != 0,
+                    FillMode::Winding => winding != 0,
+                };
+                if inside {
+                    add_span(values, &mut runs, pair[0].0, pair[1].0, weight);
+                }
+            }
+        }
+
+        let mut run = 0.0;
+        for (value, delta) in values.iter_mut().zip(&runs) {
+            run += delta;
+            *value = (*value + run).min(1.0);
+        }
+    }
+
+    Some(coverage)
+}
+
+/// Adds `weight` times the horizontal coverage of the span `[a, b)` to a row,
+/// where `a` and `b` are relative to the left of the row.
+fn add_span(values: &mut [f32], runs: &mut [f32], a: f32, b: f32, weight: f32) {
+    let width = values.len() as f32;
+    let (a, b) = (a.clamp(0.0, width), b.clamp(0.0, width));
+    if b <= a {
+        return;
+    }
+
+    let (first, last) = (a.floor(), b.floor());
+    let (i, j) = (first as usize, last as usize);
+    if i == j {
+        values[i] += (b - a) * weight;
+        return;
+    }
+
+    values[i] += (first + 1.0 - a) * weight;
+    runs[i + 1] += weight;
+    runs[j] -= weight;
+    if j < values.len() {
+        values[j] += (b - last) * weight;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Vec<Vec2> {
+        vec![
+            Vec2::new(left, top),
+            Vec2::new(right, top),
+            Vec2::new(right, bottom),
+            Vec2::new(left, bottom),
+        ]
+    }
+
+    #[test]
+    fn test_pixel_aligned_rect_is_fully_covered() {
+        let coverage = rasterize(&[rect(1.0, 1.0, 3.0, 2.0)], FillMode::Winding, 4, 4).unwrap();
+
+        assert_eq!((coverage.left, coverage.top), (1, 1));
+        assert_eq!((coverage.width, coverage.height), (2, 1));
+        assert_eq!(coverage.get(1, 1), 1.0);
+        assert_eq!(coverage.get(2, 1), 1.0);
+        assert_eq!(coverage.get(0, 1), 0.0);
+        assert_eq!(coverage.get(1, 2), 0.0);
+    }
+
+    #[test]
+    fn test_fractional_edges_are_partially_covered() {
+        let coverage = rasterize(&[rect(0.25, 0.5, 2.5, 1.0)], FillMode::Winding, 4, 4).unwrap();
+
+        assert_eq!(coverage.get(0, 0), 0.75 * 0.5);
+        assert_eq!(coverage.get(1, 0), 0.5);
+        assert_eq!(coverage.get(2, 0), 0.25);
+    }
+
+    #[test]
+    fn test_shapes_are_clipped_to_the_target() {
+        let coverage =
+            rasterize(&[rect(-10.0, -10.0, 10.0, 10.0)], FillMode::Winding, 4, 2).unwrap();
+
+        assert_eq!((coverage.width, coverage.height), (4, 2));
+        assert!(rasterize(&[rect(5.0, 5.0, 6.0, 6.0)], FillMode::Winding, 4, 4).is_none());
+    }
+
+    #[test]
+    fn test_fill_mode_determines_overlapping_coverage() {
+        // Two rects wound the same way, the second within the first.
+        let polygons = [rect(0.0, 0.0, 4.0, 1.0), rect(1.0, 0.0, 3.0, 1.0)];
+
+        let winding = rasterize(&polygons, FillMode::Winding, 4, 1).unwrap();
+        let alternate = rasterize(&polygons, FillMode::Alternate, 4, 1).unwrap();
+
+        assert_eq!(winding.get(1, 0), 1.0);
+        assert_eq!(alternate.get(0, 0), 1.0);
+        assert_eq!(alternate.get(1, 0), 0.0);
+    }
+}
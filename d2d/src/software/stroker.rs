@@ -0,0 +1,401 @@
+//! Conversion of stroked polylines into fillable polygons.
+//!
+//! Each segment, join and cap is emitted as a separate polygon, all wound the
+//! same way, so that filling them together with [`FillMode::Winding`] paints
+//! their union.
+//!
+//! [`FillMode::Winding`]: crate::path::FillMode::Winding
+
+use super::{
+    flatten::{arc_segments, Polyline},
+    vec2::{signed_area, Vec2},
+};
+use crate::stroke::{CapStyle, DashStyle, LineJoin, StrokeStyleProperties};
+
+/// The half-length of the segment used to give zero-length dashes, i.e. dots,
+/// a direction for their caps.
+const DOT_EPSILON: f32 = 1e-3;
+
+/// Converts polylines stroked with the given width and style into polygons.
+/// `scale` converts from local units to pixels, for flattening round joins and
+/// caps.
+pub(super) fn stroke(
+    lines: &[Polyline],
+    width: f32,
+    style: &StrokeStyleProperties,
+    scale: f32,
+) -> Vec<Vec<Vec2>> {
+    let mut stroker = Stroker {
+        half_width: width.abs() / 2.0,
+        style,
+        scale,
+        polygons: Vec::new(),
+    };
+    if stroker.half_width == 0.0 {
+        return Vec::new();
+    }
+
+    let pattern = dash_pattern(&style.dash_style, width.abs());
+    for line in lines {
+        match &pattern {
+            None => {
+                let (start_cap, end_cap) = (style.start_cap, style.end_cap);
+                stroker.polyline(&line.points, line.closed, start_cap, end_cap);
+            }
+            Some(pattern) => {
+                let mut points = line.points.clone();
+                if line.closed {
+                    points.extend(line.points.first().copied());
+                }
+                let offset = style.dash_offset * width.abs();
+                for dash in split_dashes(&points, pattern, offset) {
+                    // Caps at the ends of an open figure take precedence.
+                    let start_cap = match dash.at_start && !line.closed {
+                        true => style.start_cap,
+                        false => style.dash_cap,
+                    };
+                    let end_cap = match dash.at_end && !line.closed {
+                        true => style.end_cap,
+                        false => style.dash_cap,
+                    };
+                    stroker.polyline(&dash.points, false, start_cap, end_cap);
+                }
+            }
+        }
+    }
+
+    stroker.polygons
+}
+
+/// The lengths of alternating dashes and gaps for a dash style, in local
+/// units, or `None` for a solid stroke.
+fn dash_pattern(style: &DashStyle, width: f32) -> Option<Vec<f32>> {
+    let pattern: &[f32] = match style {
+        DashStyle::Solid => return None,
+        DashStyle::Dash => &[2.0, 2.0],
+        DashStyle::Dot => &[0.0, 2.0],
+        DashStyle::DashDot => &[2.0, 2.0, 0.0, 2.0],
+        DashStyle::DashDotDot => &[2.0, 2.0, 0.0, 2.0, 0.0, 2.0],
+        DashStyle::Custom(dashes) => dashes,
+    };
+
+    let mut pattern: Vec<f32> = pattern.iter().map(|len| len.max(0.0) * width).collect();
+    // An odd number of lengths repeats to alternate dashes and gaps.
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_within(..);
+    }
+    (pattern.iter().sum::<f32>() > 0.0).then_some(pattern)
+}
+
+/// A single dash split from a polyline.
+struct Dash {
+    points: Vec<Vec2>,
+    /// Whether the dash begins at the start of the polyline.
+    at_start: bool,
+    /// Whether the dash finishes at the end of the polyline.
+    at_end: bool,
+}
+
+/// Splits an open polyline into dashes.
+fn split_dashes(points: &[Vec2], pattern: &[f32], offset: f32) -> Vec<Dash> {
+    let mut dashes = Vec::new();
+    let Some(&first) = points.first() else {
+        return dashes;
+    };
+
+    // Find where the offset lands within the pattern.
+    let total: f32 = pattern.iter().sum();
+    let mut index = 0;
+    let mut phase = offset.rem_euclid(total);
+    while phase > 0.0 && phase >= pattern[index] {
+        phase -= pattern[index];
+        index = (index + 1) % pattern.len();
+    }
+    let mut remaining = pattern[index] - phase;
+    let mut on = index % 2 == 0;
+
+    let mut current = Dash {
+        points: if on { vec![first] } else { Vec::new() },
+        at_start: on,
+        at_end: false,
+    };
+    let mut dir = Vec2::default();
+
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let len = (b - a).length();
+        if len == 0.0 {
+            continue;
+        }
+        dir = (b - a) * (1.0 / len);
+
+        let mut pos = 0.0;
+        while len - pos > remaining {
+            pos += remaining;
+            let point = a + dir * pos;
+            if on {
+                current.points.push(point);
+                let next = Dash {
+                    points: Vec::new(),
+                    at_start: false,
+                    at_end: false,
+                };
+                dashes.push(with_direction(::std::mem::replace(&mut current, next), dir));
+            } else {
+                current.points = vec![point];
+            }
+            on = !on;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+        remaining -= len - pos;
+        if on {
+            current.points.push(b);
+        }
+    }
+
+    if on && !current.points.is_empty() {
+        current.at_end = true;
+        dashes.push(with_direction(current, dir));
+    }
+    dashes
+}
+
+/// Gives a zero-length dash a tiny extent along `dir` so that its caps are
+/// drawn in the right orientation.
+fn with_direction(mut dash: Dash, dir: Vec2) -> Dash {
+    let first = dash.points[0];
+    if dash.points.iter().all(|&p| p == first) {
+        dash.points = vec![first - dir * DOT_EPSILON, first + dir * DOT_EPSILON];
+    }
+    dash
+}
+
+struct Stroker<'a> {
+    half_width: f32,
+    style: &'a StrokeStyleProperties,
+    scale: f32,
+    polygons: Vec<Vec<Vec2>>,
+}
+
+impl Stroker<'_> {
+    /// Adds a polygon, wound clockwise, unless it is degenerate.
+    fn push(&mut self, mut polygon: Vec<Vec2>) {
+        let area = signed_area(&polygon);
+        if area < 0.0 {
+            polygon.reverse();
+        }
+        if area != 0.0 {
+            self.polygons.push(polygon);
+        }
+    }
+
+    fn polyline(&mut self, points: &[Vec2], closed: bool, start_cap: CapStyle, end_cap: CapStyle) {
+        let mut points = points.to_vec();
+        points.dedup();
+        if closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 2 {
+            return;
+        }
+
+        let mut segments: Vec<(Vec2, Vec2)> = points.windows(2).map(|p| (p[0], p[1])).collect();
+        if closed {
+            segments.push((points[points.len() - 1], points[0]));
+        }
+
+        let hw = self.half_width;
+        for &(a, b) in &segments {
+            let n = (b - a).normalize().perp() * hw;
+            self.push(vec![a + n, b + n, b - n, a - n]);
+        }
+
+        for pair in segments.windows(2) {
+            self.join(pair[0].1, pair[0].1 - pair[0].0, pair[1].1 - pair[1].0);
+        }
+        if closed {
+            let (last, first) = (segments[segments.len() - 1], segments[0]);
+            self.join(first.0, last.1 - last.0, first.1 - first.0);
+        } else {
+            let (first, last) = (segments[0], segments[segments.len() - 1]);
+            self.cap(first.0, (first.0 - first.1).normalize(), start_cap);
+            self.cap(last.1, (last.1 - last.0).normalize(), end_cap);
+        }
+    }
+
+    /// Fills the gap on the outside of the corner at `p`, between a segment
+    /// running along `d_in` and the next running along `d_out`.
+    fn join(&mut self, p: Vec2, d_in: Vec2, d_out: Vec2) {
+        let (d_in, d_out) = (d_in.normalize(), d_out.normalize());
+        let cross = d_in.cross(d_out);
+        if cross.abs() < 1e-6 && d_in.dot(d_out) > 0.0 {
+            return;
+        }
+
+        // The outside of the corner is opposite the direction of the turn.
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let hw = self.half_width;
+        let (n_in, n_out) = (d_in.perp() * side, d_out.perp() * side);
+        let (a, b) = (p + n_in * hw, p + n_out * hw);
+
+        match self.style.line_join {
+            LineJoin::Bevel => self.push(vec![p, a, b]),
+            LineJoin::Round => {
+                let sweep = n_in.cross(n_out).atan2(n_in.dot(n_out));
+                let mut polygon = vec![p];
+                polygon.extend(self.arc(p, n_in, sweep));
+                self.push(polygon);
+            }
+            // Miters beyond the limit are bevelled, which is exact for
+            // `MiterOrBevel` and a close approximation of the clipped miter
+            // drawn by Direct2D for `Miter`.
+            LineJoin::Miter | LineJoin::MiterOrBevel => {
+                let mid = (n_in + n_out).normalize();
+                let cos_half = n_in.dot(mid);
+                if cos_half > 0.0 && 1.0 / cos_half <= self.style.miter_limit.max(1.0) {
+                    self.push(vec![p, a, p + mid * (hw / cos_half), b]);
+                } else {
+                    self.push(vec![p, a, b]);
+                }
+            }
+        }
+    }
+
+    /// Adds a cap at the end `p` of a polyline, where `d` points away from
+    /// the polyline.
+    fn cap(&mut self, p: Vec2, d: Vec2, cap: CapStyle) {
+        let hw = self.half_width;
+        let n = d.perp() * hw;
+        match cap {
+            CapStyle::Flat => {}
+            CapStyle::Square => self.push(vec![p + n, p + n + d * hw, p - n + d * hw, p - n]),
+            CapStyle::Triangle => self.push(vec![p + n, p + d * hw, p - n]),
+            CapStyle::Round => {
+                // Sweep from one side of the stroke to the other through `d`.
+                let polygon = self.arc(p, d.perp(), -::std::f32::consts::PI);
+                self.push(polygon);
+            }
+        }
+    }
+
+    /// Points on the circle of the stroke's half width around `center`,
+    /// sweeping from the unit direction `from` by `sweep` radians.
+    fn arc(&self, center: Vec2, from: Vec2, sweep: f32) -> Vec<Vec2> {
+        let start = from.y.atan2(from.x);
+        let n = arc_segments(self.half_width, sweep, self.scale);
+        (0..=n)
+            .map(|i| {
+                let angle = start + sweep * (i as f32 / n as f32);
+                center + Vec2::new(angle.cos(), angle.sin()) * self.half_width
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    fn line(points: &[(f32, f32)], closed: bool) -> Polyline {
+        Polyline {
+            points: points.iter().map(|&(x, y)| Vec2::new(x, y)).collect(),
+            closed,
+        }
+    }
+
+    fn area(polygons: &[Vec<Vec2>]) -> f32 {
+        polygons.iter().map(|p| signed_area(p) / 2.0).sum()
+    }
+
+    #[test]
+    fn test_polygons_are_wound_clockwise() {
+        let style = StrokeStyleProperties {
+            start_cap: CapStyle::Round,
+            end_cap: CapStyle::Triangle,
+            line_join: LineJoin::Round,
+            ..Default::default()
+        };
+        let polygons = stroke(
+            &[line(
+                &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 3.0)],
+                false,
+            )],
+            2.0,
+            &style,
+            1.0,
+        );
+
+        assert!(polygons.iter().all(|p| signed_area(p) > 0.0));
+    }
+
+    #[test]
+    fn test_square_caps_extend_the_line() {
+        let flat = stroke(
+            &[line(&[(0.0, 0.0), (10.0, 0.0)], false)],
+            2.0,
+            &StrokeStyleProperties::default(),
+            1.0,
+        );
+        let square = stroke(
+            &[line(&[(0.0, 0.0), (10.0, 0.0)], false)],
+            2.0,
+            &StrokeStyleProperties {
+                start_cap: CapStyle::Square,
+                end_cap: CapStyle::Square,
+                ..Default::default()
+            },
+            1.0,
+        );
+
+        assert_eq!(area(&flat), 20.0);
+        assert_eq!(area(&square), 24.0);
+    }
+
+    #[test]
+    fn test_miter_joins_fill_the_outer_corner() {
+        let polygons = stroke(
+            &[line(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], false)],
+            2.0,
+            &StrokeStyleProperties::default(),
+            1.0,
+        );
+
+        // Two segments plus a square miter of the half width on each side.
+        assert_eq!(polygons.len(), 3);
+        assert_eq!(area(&polygons[2..]), 1.0);
+    }
+
+    #[test]
+    fn test_dashes_follow_the_pattern() {
+        let points = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0)];
+        let dashes = split_dashes(&points, &[2.0, 2.0], 0.0);
+
+        assert_eq!(dashes.len(), 3);
+        assert_eq!(dashes[0].points, [Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0)]);
+        assert_eq!(
+            dashes[2].points,
+            [Vec2::new(8.0, 0.0), Vec2::new(10.0, 0.0)]
+        );
+        assert!(dashes[0].at_start && !dashes[0].at_end);
+        assert!(dashes[2].at_end);
+
+        let shifted = split_dashes(&points, &[2.0, 2.0], 1.0);
+        assert_eq!(
+            shifted[0].points,
+            [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_dots_are_given_a_direction() {
+        let points = [Vec2::new(0.0, 0.0), Vec2::new(0.0, 5.0)];
+        let dots = split_dashes(&points, &[0.0, 2.0], 0.0);
+
+        assert_eq!(dots.len(), 3);
+        assert!(dots.iter().all(|dot| dot.points.len() == 2));
+        assert!(dots[1].points[0].x == 0.0 && dots[1].points[0].y < 2.0);
+    }
+}
@@ -0,0 +1,110 @@
+//! Minimal 2D vector arithmetic used throughout the software rasterizer.
+
+use ::std::ops::{Add, Mul, Neg, Sub};
+use ::win_geom::d2::{Point2D, Transform2D};
+
+/// A 2D point or direction.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub(super) struct Vec2 {
+    pub(super) x: f32,
+    pub(super) y: f32,
+}
+
+impl Vec2 {
+    pub(super) const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub(super) fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The z component of the 3D cross product. Positive when `other` is
+    /// clockwise from `self` in the y-down coordinate space of a render target.
+    pub(super) fn cross(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub(super) fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// The unit vector in the same direction, or zero for a zero vector.
+    pub(super) fn normalize(self) -> Vec2 {
+        let len = self.length();
+        if len > 0.0 {
+            self * (1.0 / len)
+        } else {
+            Vec2::default()
+        }
+    }
+
+    /// The vector rotated by 90 degrees.
+    pub(super) fn perp(self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
+
+    /// Applies an affine transform to the point.
+    pub(super) fn transform(self, t: &Transform2D) -> Vec2 {
+        t.transform_point(self.into()).into()
+    }
+}
+
+impl From<Point2D<f32>> for Vec2 {
+    fn from(p: Point2D<f32>) -> Self {
+        Self::new(p.x, p.y)
+    }
+}
+
+impl From<Vec2> for Point2D<f32> {
+    fn from(v: Vec2) -> Self {
+        Point2D { x: v.x, y: v.y }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Vec2 {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+/// The largest factor by which `t` stretches any vector. Used to choose how
+/// finely curves are flattened so that they remain smooth once transformed.
+pub(super) fn max_scale(t: &Transform2D) -> f32 {
+    let x = Vec2::new(t.m11, t.m12).length();
+    let y = Vec2::new(t.m21, t.m22).length();
+    x.max(y)
+}
+
+/// Twice the signed area of a polygon. Positive for polygons wound clockwise
+/// in the y-down coordinate space of a render target.
+pub(super) fn signed_area(points: &[Vec2]) -> f32 {
+    let n = points.len();
+    (0..n).map(|i| points[i].cross(points[(i + 1) % n])).sum()
+}
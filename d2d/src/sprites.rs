@@ -0,0 +1,310 @@
+//! Batches of sprites drawn from a single texture atlas in one submission.
+
+use crate::{color::Color, errors::Result, target::RenderTarget};
+use ::std::{
+    fmt::{self, Debug},
+    mem::size_of,
+};
+use ::win32::invoke::check_res;
+use ::win_geom::d2::{Rect2D, Transform2D};
+use ::windows::{
+    Foundation::Numerics::Matrix3x2,
+    Win32::Graphics::Direct2D::{
+        Common::{D2D1_COLOR_F, D2D_RECT_F, D2D_RECT_U},
+        ID2D1DeviceContext3, ID2D1SpriteBatch,
+    },
+};
+
+/// A region of a texture atlas drawn into a destination rectangle, as added to
+/// a [`SpriteBatch`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprite {
+    /// The region of the atlas to draw, in pixels.
+    pub source: Rect2D<u32>,
+    /// The rectangle into which the region is drawn, in device-independent
+    /// pixels.
+    pub dest: Rect2D<f32>,
+    /// A color multiplied against the sprite's pixels. White leaves them
+    /// unchanged.
+    pub tint: Color,
+    /// A transform applied to the sprite before the context's world
+    /// transform, e.g. to rotate it about its center.
+    pub transform: Transform2D,
+}
+
+impl Sprite {
+    /// Constructs a sprite drawing the `source` region of the atlas into
+    /// `dest`, untinted and untransformed.
+    pub fn new(source: Rect2D<u32>, dest: Rect2D<f32>) -> Self {
+        Self {
+            source,
+            dest,
+            tint: Color::white(),
+            transform: Transform2D::identity(),
+        }
+    }
+
+    /// Sets the color multiplied against the sprite's pixels.
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Sets the transform applied to the sprite before the world transform.
+    pub fn with_transform(mut self, transform: Transform2D) -> Self {
+        self.transform = transform;
+        self
+    }
+}
+
+/// A list of [`Sprite`]s which are drawn from a single atlas
+/// [`Bitmap`](crate::bitmaps::Bitmap) with one call to
+/// [`Context::draw_sprites`](crate::Context::draw_sprites).
+///
+/// Drawing many small images one call at a time is slow. A sprite batch
+/// instead submits them all at once, using a Direct2D sprite batch where
+/// available (Windows 10 and newer). Otherwise each sprite is drawn in turn, in
+/// which case only the alpha of each tint is applied.
+///
+/// The batch is uploaded to the device when first drawn and only again after
+/// it changes, so a batch which is kept between frames is cheapest to draw.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ::windows::Win32::Foundation::HWND;
+/// # use ::d2d::D2DFactory;
+/// use ::d2d::{bitmaps::InterpolationMode, Color, Image, Sprite, SpriteBatch, TextureAtlas};
+/// use ::win_geom::d2::{Point2D, Rect2D, Size2D};
+///
+/// # let factory = D2DFactory::new()?;
+/// # let mut render_target = factory.make_render_target(
+/// #     HWND(0),
+/// #     Size2D { width: 100, height: 100 },
+/// # );
+/// # let tile = Image::decode(&[]).unwrap();
+/// let atlas = TextureAtlas::pack(&[&tile], Size2D { width: 256, height: 256 }, 1)
+///     .expect("tiles fit in the atlas");
+/// let mut bitmap = render_target.make_bitmap(atlas.image().clone())?;
+///
+/// let mut batch = SpriteBatch::new();
+/// for x in 0..10 {
+///     let dest = Rect2D::from_size_and_origin(
+///         Size2D { width: 16.0, height: 16.0 },
+///         Point2D { x: x as f32 * 16.0, y: 0.0 },
+///     );
+///     batch.push(Sprite::new(atlas.region(0), dest).with_tint(Color::light_blue()));
+/// }
+///
+/// let mut ctx = render_target.begin_draw()?;
+/// ctx.draw_sprites(&mut batch, &mut bitmap, InterpolationMode::NearestNeighbor);
+/// ctx.end_draw()?;
+/// # Ok::<(), ::d2d::Error>(())
+/// ```
+#[derive(Default)]
+pub struct SpriteBatch {
+    sprites: Vec<Sprite>,
+    /// The batch on the device, once created, and the generation of the render
+    /// target which created it.
+    device_batch: Option<(ID2D1SpriteBatch, usize)>,
+    /// Whether the sprites changed since they were last uploaded.
+    dirty: bool,
+}
+
+impl SpriteBatch {
+    /// Constructs an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `sprite` to be drawn after those already in the batch.
+    pub fn push(&mut self, sprite: Sprite) {
+        self.sprites.push(sprite);
+        self.dirty = true;
+    }
+
+    /// Removes all sprites, e.g. to re-use the batch for the next frame.
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+        self.dirty = true;
+    }
+
+    /// The sprites in the batch, in drawing order.
+    pub fn sprites(&self) -> &[Sprite] {
+        &self.sprites
+    }
+
+    /// Mutable access to the sprites, e.g. to move them between frames.
+    pub fn sprites_mut(&mut self) -> &mut [Sprite] {
+        self.dirty = true;
+        &mut self.sprites
+    }
+
+    /// The number of sprites in the batch.
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// Whether the batch has no sprites.
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// The batch on the device of `device_context`, (re-)created if the device
+    /// was lost and uploaded if the sprites changed.
+    pub(crate) fn device_batch(
+        &mut self,
+        device_context: &ID2D1DeviceContext3,
+        render_target: &mut RenderTarget,
+    ) -> Result<ID2D1SpriteBatch> {
+        let batch = match &self.device_batch {
+            Some((batch, generation)) if *generation == render_target.generation() => batch.clone(),
+            stale => {
+                if stale.is_some() {
+                    render_target.count_recreated_resource();
+                }
+                let batch = check_res(
+                    || unsafe { device_context.CreateSpriteBatch() },
+                    "CreateSpriteBatch",
+                )?;
+                render_target.count_created_resource();
+                self.device_batch = Some((batch.clone(), render_target.generation()));
+                self.dirty = true;
+                batch
+            }
+        };
+
+        if self.dirty {
+            let sprites = DeviceSprites::new(&self.sprites);
+            unsafe { batch.Clear() };
+            check_res(
+                || unsafe {
+                    batch.AddSprites(
+                        self.sprites.len() as u32,
+                        sprites.dests.as_ptr(),
+                        Some(sprites.sources.as_ptr()),
+                        Some(sprites.tints.as_ptr()),
+                        Some(sprites.transforms.as_ptr()),
+                        size_of::<D2D_RECT_F>() as u32,
+                        size_of::<D2D_RECT_U>() as u32,
+                        size_of::<D2D1_COLOR_F>() as u32,
+                        size_of::<Matrix3x2>() as u32,
+                    )
+                },
+                "AddSprites",
+            )?;
+            self.dirty = false;
+        }
+
+        Ok(batch)
+    }
+}
+
+impl Debug for SpriteBatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpriteBatch")
+            .field("sprites", &self.sprites)
+            .field("generation", &self.device_batch.as_ref().map(|(_, g)| g))
+            .field("dirty", &self.dirty)
+            .finish()
+    }
+}
+
+/// The properties of sprites as the separate arrays Direct2D expects.
+struct DeviceSprites {
+    dests: Vec<D2D_RECT_F>,
+    sources: Vec<D2D_RECT_U>,
+    tints: Vec<D2D1_COLOR_F>,
+    transforms: Vec<Matrix3x2>,
+}
+
+impl DeviceSprites {
+    fn new(sprites: &[Sprite]) -> Self {
+        Self {
+            dests: sprites.iter().map(|s| s.dest.into()).collect(),
+            sources: sprites.iter().map(|s| source_rect(s.source)).collect(),
+            tints: sprites.iter().map(|s| s.tint.into()).collect(),
+            transforms: sprites
+                .iter()
+                .map(|s| Matrix3x2::from(s.transform))
+                .collect(),
+        }
+    }
+}
+
+/// Converts a region of pixels into a Direct2D `D2D_RECT_U`.
+fn source_rect(rect: Rect2D<u32>) -> D2D_RECT_U {
+    D2D_RECT_U {
+        left: rect.left,
+        top: rect.top,
+        right: rect.right,
+        bottom: rect.bottom,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    fn sprite() -> Sprite {
+        Sprite::new(
+            Rect2D {
+                left: 16,
+                top: 0,
+                right: 32,
+                bottom: 16,
+            },
+            Rect2D {
+                left: 0.0,
+                top: 0.0,
+                right: 32.0,
+                bottom: 32.0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_device_sprites_are_parallel_arrays() {
+        let sprites = [
+            sprite(),
+            sprite()
+                .with_tint(Color::red())
+                .with_transform(Transform2D::translation(4.0, 8.0)),
+        ];
+
+        let device = DeviceSprites::new(&sprites);
+
+        assert_eq!(device.dests.len(), 2);
+        assert_eq!(
+            device.sources[1],
+            D2D_RECT_U {
+                left: 16,
+                top: 0,
+                right: 32,
+                bottom: 16
+            }
+        );
+        assert_eq!(device.tints[0], Color::white().into());
+        assert_eq!(device.tints[1], Color::red().into());
+        assert_eq!(
+            (device.transforms[1].M31, device.transforms[1].M32),
+            (4.0, 8.0)
+        );
+    }
+
+    #[test]
+    fn test_changes_mark_batch_dirty() {
+        let mut batch = SpriteBatch::new();
+        assert!(!batch.dirty);
+
+        batch.push(sprite());
+        assert!(batch.dirty);
+
+        batch.dirty = false;
+        batch.sprites_mut()[0].tint = Color::blue();
+        assert!(batch.dirty);
+        assert_eq!(batch.len(), 1);
+    }
+}
@@ -0,0 +1,220 @@
+//! Per-frame rendering statistics, also emitted as [`tracing`](::tracing)
+//! events.
+
+use ::std::time::{Duration, Instant};
+use ::tracing::span::EnteredSpan;
+use ::windows::Win32::Graphics::Direct2D::ID2D1Brush;
+
+/// Statistics gathered while drawing a single frame, as returned by
+/// [`Context::end_draw`](crate::Context::end_draw).
+///
+/// Each frame is also traced. Drawing takes place within a `DEBUG` level
+/// `frame` span, in which the call to `EndDraw` has its own `TRACE` level
+/// `end_draw` span, and these statistics are emitted as a `DEBUG` event when
+/// the frame ends. Profile frames by enabling them for the `d2d` target, e.g.
+/// with `RUST_LOG=d2d=debug`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct FrameStats {
+    /// The drawing calls made, by primitive.
+    pub draw_calls: DrawCalls,
+    /// The number of drawing calls which painted with a different brush to the
+    /// previous call. Each switch has a small cost, so batching drawing by
+    /// brush can help.
+    pub brush_switches: usize,
+    /// The time from beginning to end of the frame, including `EndDraw`.
+    pub frame_time: Duration,
+    /// The time spent in `EndDraw`, during which Direct2D processes the
+    /// batched drawing calls.
+    pub end_draw_time: Duration,
+    /// Device render targets created for the frame, e.g. after device loss.
+    pub targets_created: usize,
+    /// Device resources created during the frame, including those re-created.
+    pub resources_created: usize,
+    /// Device resources re-created during the frame because the device was
+    /// lost since they were created.
+    pub resources_recreated: usize,
+}
+
+/// Counts of the drawing calls made during a frame, by primitive. Fills and
+/// strokes of a primitive are counted together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct DrawCalls {
+    /// Calls to [`clear`](crate::Context::clear).
+    pub clears: usize,
+    /// Calls to [`draw_line`](crate::Context::draw_line).
+    pub lines: usize,
+    /// Rectangles filled or stroked.
+    pub rects: usize,
+    /// Rounded rectangles filled or stroked.
+    pub rounded_rects: usize,
+    /// Ellipses filled or stroked.
+    pub ellipses: usize,
+    /// Geometries filled or stroked.
+    pub geometries: usize,
+    /// Bitmaps and images drawn.
+    pub bitmaps: usize,
+    /// Calls to [`draw_sprites`](crate::Context::draw_sprites), each drawing
+    /// a whole batch.
+    pub sprite_batches: usize,
+    /// Strings and text layouts drawn.
+    pub text: usize,
+}
+
+impl DrawCalls {
+    /// The total number of drawing calls.
+    pub fn total(&self) -> usize {
+        self.clears
+            + self.lines
+            + self.rects
+            + self.rounded_rects
+            + self.ellipses
+            + self.geometries
+            + self.bitmaps
+            + self.sprite_batches
+            + self.text
+    }
+}
+
+/// Running totals of the device resources created by a render target, from
+/// which the share of each frame is found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub(crate) struct ResourceCounters {
+    /// Device render targets created, including the first.
+    pub(crate) targets_created: usize,
+    /// Device resources created, including those re-created.
+    pub(crate) resources_created: usize,
+    /// Device resources re-created after their generation expired.
+    pub(crate) resources_recreated: usize,
+}
+
+impl ResourceCounters {
+    /// The counts accumulated since `earlier`.
+    fn since(self, earlier: Self) -> Self {
+        Self {
+            targets_created: self.targets_created - earlier.targets_created,
+            resources_created: self.resources_created - earlier.resources_created,
+            resources_recreated: self.resources_recreated - earlier.resources_recreated,
+        }
+    }
+}
+
+/// Gathers the [`FrameStats`] of a frame while it is drawn, within the frame's
+/// tracing span.
+pub(crate) struct FrameRecorder {
+    started: Instant,
+    /// The render target's counters when the frame began.
+    counters: ResourceCounters,
+    draw_calls: DrawCalls,
+    brush_switches: usize,
+    /// The brush of the previous drawing call, to count brush switches.
+    last_brush: Option<ID2D1Brush>,
+    span: EnteredSpan,
+}
+
+impl FrameRecorder {
+    /// Begins recording a frame drawn into `surface`, given the render
+    /// target's counters before any of the frame's resources were created.
+    pub(crate) fn start(counters: ResourceCounters, surface: &'static str) -> Self {
+        Self {
+            started: Instant::now(),
+            counters,
+            draw_calls: DrawCalls::default(),
+            brush_switches: 0,
+            last_brush: None,
+            span: ::tracing::debug_span!("frame", surface).entered(),
+        }
+    }
+
+    /// The drawing calls made so far, to be counted by each call.
+    pub(crate) fn draw_calls(&mut self) -> &mut DrawCalls {
+        &mut self.draw_calls
+    }
+
+    /// Notes that a drawing call painted with `brush`.
+    pub(crate) fn use_brush(&mut self, brush: &ID2D1Brush) {
+        if self.last_brush.as_ref() != Some(brush) {
+            self.brush_switches += 1;
+            self.last_brush = Some(brush.clone());
+        }
+    }
+
+    /// Ends the frame, given the render target's counters once drawing ended
+    /// and the time spent in `EndDraw`, and emits its statistics.
+    pub(crate) fn finish(self, counters: ResourceCounters, end_draw_time: Duration) -> FrameStats {
+        let created = counters.since(self.counters);
+        let stats = FrameStats {
+            draw_calls: self.draw_calls,
+            brush_switches: self.brush_switches,
+            frame_time: self.started.elapsed(),
+            end_draw_time,
+            targets_created: created.targets_created,
+            resources_created: created.resources_created,
+            resources_recreated: created.resources_recreated,
+        };
+
+        ::tracing::debug!(
+            draw_calls = stats.draw_calls.total(),
+            by_primitive = ?stats.draw_calls,
+            brush_switches = stats.brush_switches,
+            frame_time = ?stats.frame_time,
+            end_draw_time = ?stats.end_draw_time,
+            targets_created = stats.targets_created,
+            resources_created = stats.resources_created,
+            resources_recreated = stats.resources_recreated,
+            "Frame drawn"
+        );
+        drop(self.span);
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_total_draw_calls() {
+        let calls = DrawCalls {
+            clears: 1,
+            rects: 3,
+            bitmaps: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(calls.total(), 6);
+    }
+
+    #[test]
+    fn test_frame_counts_only_its_own_resources() {
+        let before = ResourceCounters {
+            targets_created: 1,
+            resources_created: 10,
+            resources_recreated: 4,
+        };
+        let mut frame = FrameRecorder::start(before, "window");
+        frame.draw_calls().lines += 2;
+
+        let stats = frame.finish(
+            ResourceCounters {
+                targets_created: 2,
+                resources_created: 13,
+                resources_recreated: 7,
+            },
+            Duration::from_millis(1),
+        );
+
+        assert_eq!(stats.draw_calls.lines, 2);
+        assert_eq!(
+            (
+                stats.targets_created,
+                stats.resources_created,
+                stats.resources_recreated
+            ),
+            (1, 3, 3)
+        );
+        assert_eq!(stats.end_draw_time, Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,250 @@
+//! Stroke styles which describe the dashes, caps and joins of stroked lines and
+//! outlines.
+//!
+//! Unlike brushes, a [`StrokeStyle`] is a factory-level resource. It is created
+//! by a [`D2DFactory`](crate::D2DFactory), is not tied to any one render target
+//! and survives device loss.
+
+use ::windows::{
+    core::InParam,
+    Win32::Graphics::Direct2D::{
+        ID2D1StrokeStyle, D2D1_CAP_STYLE, D2D1_CAP_STYLE_FLAT, D2D1_CAP_STYLE_ROUND,
+        D2D1_CAP_STYLE_SQUARE, D2D1_CAP_STYLE_TRIANGLE, D2D1_DASH_STYLE, D2D1_DASH_STYLE_CUSTOM,
+        D2D1_DASH_STYLE_DASH, D2D1_DASH_STYLE_DASH_DOT, D2D1_DASH_STYLE_DASH_DOT_DOT,
+        D2D1_DASH_STYLE_DOT, D2D1_DASH_STYLE_SOLID, D2D1_LINE_JOIN, D2D1_LINE_JOIN_BEVEL,
+        D2D1_LINE_JOIN_MITER, D2D1_LINE_JOIN_MITER_OR_BEVEL, D2D1_LINE_JOIN_ROUND,
+        D2D1_STROKE_STYLE_PROPERTIES,
+    },
+};
+
+/// The shape at the end of a line or segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum CapStyle {
+    /// A cap that does not extend past the last point of the line.
+    #[default]
+    Flat,
+    /// Half of a square that has a length equal to the line thickness.
+    Square,
+    /// A semicircle that has a diameter equal to the line thickness.
+    Round,
+    /// An isosceles right triangle whose hypotenuse is equal in length to the
+    /// thickness of the line.
+    Triangle,
+}
+
+impl From<CapStyle> for D2D1_CAP_STYLE {
+    fn from(cap: CapStyle) -> Self {
+        match cap {
+            CapStyle::Flat => D2D1_CAP_STYLE_FLAT,
+            CapStyle::Square => D2D1_CAP_STYLE_SQUARE,
+            CapStyle::Round => D2D1_CAP_STYLE_ROUND,
+            CapStyle::Triangle => D2D1_CAP_STYLE_TRIANGLE,
+        }
+    }
+}
+
+/// The shape used to join two segments of an outline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum LineJoin {
+    /// Regular angular vertices, clipped to the miter limit.
+    #[default]
+    Miter,
+    /// Beveled vertices.
+    Bevel,
+    /// Rounded vertices.
+    Round,
+    /// Regular angular vertices unless the join would extend beyond the miter
+    /// limit, in which case the vertex is beveled.
+    MiterOrBevel,
+}
+
+impl From<LineJoin> for D2D1_LINE_JOIN {
+    fn from(join: LineJoin) -> Self {
+        match join {
+            LineJoin::Miter => D2D1_LINE_JOIN_MITER,
+            LineJoin::Bevel => D2D1_LINE_JOIN_BEVEL,
+            LineJoin::Round => D2D1_LINE_JOIN_ROUND,
+            LineJoin::MiterOrBevel => D2D1_LINE_JOIN_MITER_OR_BEVEL,
+        }
+    }
+}
+
+/// The pattern of dashes and gaps along a stroke.
+#[derive(Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum DashStyle {
+    /// A solid line with no breaks.
+    #[default]
+    Solid,
+    /// A dash followed by a gap of equal length.
+    Dash,
+    /// A dot followed by a longer gap.
+    Dot,
+    /// A dash, a gap, a dot, and a gap.
+    DashDot,
+    /// A dash, a gap, a dot, a gap, a dot, and a gap.
+    DashDotDot,
+    /// Alternating dash and gap lengths, in multiples of the stroke width. For
+    /// example `vec![4.0, 2.0]` gives dashes four times as long as the stroke
+    /// is wide, separated by gaps half that length.
+    Custom(Vec<f32>),
+}
+
+impl DashStyle {
+    /// The Direct2D preset dash style and, for custom styles, the dash lengths.
+    fn device_style(&self) -> (D2D1_DASH_STYLE, Option<&[f32]>) {
+        match self {
+            Self::Solid => (D2D1_DASH_STYLE_SOLID, None),
+            Self::Dash => (D2D1_DASH_STYLE_DASH, None),
+            Self::Dot => (D2D1_DASH_STYLE_DOT, None),
+            Self::DashDot => (D2D1_DASH_STYLE_DASH_DOT, None),
+            Self::DashDotDot => (D2D1_DASH_STYLE_DASH_DOT_DOT, None),
+            Self::Custom(dashes) => (D2D1_DASH_STYLE_CUSTOM, Some(dashes)),
+        }
+    }
+}
+
+/// The full description of a [`StrokeStyle`].
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{CapStyle, DashStyle, StrokeStyleProperties};
+///
+/// // A marching-ants selection marquee.
+/// let props = StrokeStyleProperties {
+///     dash_style: DashStyle::Custom(vec![3.0, 3.0]),
+///     dash_cap: CapStyle::Square,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct StrokeStyleProperties {
+    /// The shape at the start of an open figure.
+    pub start_cap: CapStyle,
+    /// The shape at the end of an open figure.
+    pub end_cap: CapStyle,
+    /// The shape at both ends of each dash.
+    pub dash_cap: CapStyle,
+    /// The shape used to join two segments of an outline.
+    pub line_join: LineJoin,
+    /// The limit on the ratio of the miter length to half the stroke width.
+    /// Values below 1.0 are treated as 1.0.
+    pub miter_limit: f32,
+    /// The pattern of dashes and gaps.
+    pub dash_style: DashStyle,
+    /// An offset into the dash pattern, in multiples of the stroke width. A
+    /// positive value shifts the pattern towards the start of the stroke, which
+    /// can be animated to produce "marching ants".
+    pub dash_offset: f32,
+}
+
+impl Default for StrokeStyleProperties {
+    fn default() -> Self {
+        Self {
+            start_cap: CapStyle::default(),
+            end_cap: CapStyle::default(),
+            dash_cap: CapStyle::default(),
+            line_join: LineJoin::default(),
+            miter_limit: 10.0,
+            dash_style: DashStyle::default(),
+            dash_offset: 0.0,
+        }
+    }
+}
+
+impl StrokeStyleProperties {
+    /// The properties in their Direct2D representation, along with the dash
+    /// lengths for custom dash styles.
+    pub(crate) fn device_properties(&self) -> (D2D1_STROKE_STYLE_PROPERTIES, Option<&[f32]>) {
+        let (dash_style, dashes) = self.dash_style.device_style();
+        let props = D2D1_STROKE_STYLE_PROPERTIES {
+            startCap: self.start_cap.into(),
+            endCap: self.end_cap.into(),
+            dashCap: self.dash_cap.into(),
+            lineJoin: self.line_join.into(),
+            miterLimit: self.miter_limit,
+            dashStyle: dash_style,
+            dashOffset: self.dash_offset,
+        };
+
+        (props, dashes)
+    }
+}
+
+/// Describes the dashes, caps and joins of a stroke. Pass to any of the
+/// [`Context`](crate::Context) stroke methods.
+///
+/// Stroke styles are immutable and cheap to clone. Because they are created by
+/// the factory rather than a render target, they never need to be re-created
+/// after device loss.
+#[derive(Clone, Debug)]
+pub struct StrokeStyle {
+    /// The properties with which the style was created.
+    props: StrokeStyleProperties,
+    /// The underlying Direct2D stroke style.
+    inner: ID2D1StrokeStyle,
+}
+
+impl StrokeStyle {
+    /// A crate-private constructor. Only a [`D2DFactory`](crate::D2DFactory)
+    /// should be able to create stroke styles.
+    pub(crate) fn new(props: StrokeStyleProperties, inner: ID2D1StrokeStyle) -> Self {
+        Self { props, inner }
+    }
+
+    /// The properties with which the style was created.
+    pub fn properties(&self) -> &StrokeStyleProperties {
+        &self.props
+    }
+
+    /// Accesses the underlying Direct2D stroke style.
+    pub(crate) fn device_style(&self) -> &ID2D1StrokeStyle {
+        &self.inner
+    }
+}
+
+/// Converts an optional stroke style into the parameter expected by the
+/// Direct2D stroke methods.
+pub(crate) fn stroke_style_param(style: Option<&StrokeStyle>) -> InParam<'_, ID2D1StrokeStyle> {
+    match style {
+        Some(style) => style.device_style().into(),
+        None => InParam::null(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_default_properties_match_direct2d() {
+        let props = StrokeStyleProperties::default();
+        let (props, dashes) = props.device_properties();
+
+        assert_eq!(props.startCap, D2D1_CAP_STYLE_FLAT);
+        assert_eq!(props.lineJoin, D2D1_LINE_JOIN_MITER);
+        assert_eq!(props.miterLimit, 10.0);
+        assert_eq!(props.dashStyle, D2D1_DASH_STYLE_SOLID);
+        assert_eq!(dashes, None);
+    }
+
+    #[test]
+    fn test_custom_dashes() {
+        let props = StrokeStyleProperties {
+            dash_style: DashStyle::Custom(vec![4.0, 2.0]),
+            dash_offset: 1.0,
+            ..Default::default()
+        };
+        let (device_props, dashes) = props.device_properties();
+
+        assert_eq!(device_props.dashStyle, D2D1_DASH_STYLE_CUSTOM);
+        assert_eq!(device_props.dashOffset, 1.0);
+        assert_eq!(dashes, Some([4.0, 2.0].as_slice()));
+    }
+}
@@ -0,0 +1,998 @@
+//! Writing recorded drawing as SVG documents.
+
+use crate::{
+    backend::Backend,
+    bitmaps::InterpolationMode,
+    brushes::BitmapBrushProperties,
+    color::Color,
+    context::TransformStack,
+    draw_list::{BrushSpec, DrawCommand, DrawList, LayerSpec, ScopeKind, ScopeStack},
+    gradient::{ExtendMode, Gamma, Gradient},
+    imaging::Image,
+    path::{ArcSize, FillMode, Path, PathSegment, SweepDirection},
+    stroke::{CapStyle, DashStyle, LineJoin, StrokeStyleProperties},
+};
+use ::std::{
+    convert::Infallible,
+    fmt::{self, Display, Write},
+};
+use ::win_geom::d2::{Ellipse2D, Point2D, Rect2D, Size2D, Transform2D};
+
+/// An SVG document into which [`DrawList`]s are rendered, e.g. to inspect a
+/// frame in a browser or to diff vector output without Direct2D.
+///
+/// Each command becomes the equivalent SVG element. World transforms become
+/// `<g transform>` groups around the elements drawn with them, and clips and
+/// layers become groups clipped to their bounds. As with the
+/// [`SoftwareRenderTarget`](crate::SoftwareRenderTarget), rendering
+/// accumulates, so several lists may be rendered into one document.
+///
+/// Some drawing has no exact SVG equivalent and is approximated:
+///
+/// * Clearing paints over what was drawn before, so clearing with a translucent
+///   color blends rather than replaces.
+/// * Bitmap brushes always tile, whatever their extend modes.
+/// * Triangle caps are drawn flat, and a stroke has a single cap style: that of
+///   its dashes if it is dashed, otherwise that of its start.
+/// * Layer opacity brushes only contribute their opacity, and only if solid.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{Color, DrawList, SvgDocument};
+/// use ::win_geom::d2::{Rect2D, Size2D};
+///
+/// let mut list = DrawList::new();
+/// list.fill_rect(Rect2D { left: 1.0, top: 2.0, right: 5.0, bottom: 4.0 }, Color::red());
+///
+/// let mut document = SvgDocument::new(Size2D { width: 8.0, height: 8.0 });
+/// document.render(&list);
+///
+/// assert!(document
+///     .to_svg()
+///     .contains(r##"<rect x="1" y="2" width="4" height="2" fill="#FF0000"/>"##));
+/// ```
+#[derive(Debug)]
+pub struct SvgDocument {
+    size: Size2D<f32>,
+    /// The gradients, patterns and clip paths referenced by the body.
+    defs: String,
+    /// The drawn elements.
+    body: String,
+    transforms: TransformStack,
+    /// The clip and layer groups currently open in the body.
+    scopes: ScopeStack<()>,
+    /// The world transform of the open transform group, if any. It is always
+    /// innermost, as clip paths are in device space and so never nested
+    /// within a transform group.
+    transform_group: Option<Transform2D>,
+    /// The number of definitions made so far, from which unique ids are made.
+    next_id: usize,
+}
+
+impl SvgDocument {
+    /// Constructs an empty document of the given size in device-independent
+    /// pixels.
+    pub fn new(size: Size2D<f32>) -> Self {
+        Self {
+            size,
+            defs: String::new(),
+            body: String::new(),
+            transforms: TransformStack::default(),
+            scopes: ScopeStack::default(),
+            transform_group: None,
+            next_id: 0,
+        }
+    }
+
+    /// The size of the document, in device-independent pixels.
+    pub fn size(&self) -> Size2D<f32> {
+        self.size
+    }
+
+    /// Renders all commands in `list` on top of the document's current content.
+    ///
+    /// Unbalanced [clips and layers](DrawList#clips-and-layers) are handled as
+    /// for any other backend. The world transform is reset to the identity
+    /// afterwards.
+    pub fn render(&mut self, list: &DrawList) {
+        for command in list.commands() {
+            self.execute(command);
+        }
+
+        self.close_transform_group();
+        while self.scopes.pop_innermost().is_some() {
+            self.close_group();
+        }
+        self.transforms = TransformStack::default();
+    }
+
+    /// The complete SVG document.
+    pub fn to_svg(&self) -> String {
+        let (width, height) = (Num(self.size.width), Num(self.size.height));
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">\n"
+        );
+        if !self.defs.is_empty() {
+            svg.push_str("  <defs>\n");
+            svg.push_str(&self.defs);
+            svg.push_str("  </defs>\n");
+        }
+        svg.push_str(&self.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn execute(&mut self, command: &DrawCommand) {
+        match command {
+            DrawCommand::Clear(color) => {
+                // Clearing ignores the world transform, but not clips.
+                self.close_transform_group();
+                let paint = self.paint(&BrushSpec::Solid(*color), "fill");
+                let (width, height) = (Num(self.size.width), Num(self.size.height));
+                self.write(format_args!(
+                    "<rect width=\"{width}\" height=\"{height}\"{paint}/>"
+                ));
+            }
+            DrawCommand::DrawLine {
+                p0,
+                p1,
+                stroke_width,
+                brush,
+                style,
+            } => {
+                let stroke = self.stroke(brush, *stroke_width, style.as_ref());
+                self.element(format_args!(
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"{stroke}/>",
+                    Num(p0.x),
+                    Num(p0.y),
+                    Num(p1.x),
+                    Num(p1.y),
+                ));
+            }
+            DrawCommand::FillRect { rect, brush } => {
+                let paint = self.paint(brush, "fill");
+                self.element(format_args!("<rect{}{paint}/>", RectAttrs(*rect)));
+            }
+            DrawCommand::StrokeRect {
+                rect,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let stroke = self.stroke(brush, *stroke_width, style.as_ref());
+                self.element(format_args!(
+                    "<rect{} fill=\"none\"{stroke}/>",
+                    RectAttrs(*rect)
+                ));
+            }
+            DrawCommand::FillRoundedRect { rect, brush } => {
+                let paint = self.paint(brush, "fill");
+                self.element(format_args!(
+                    "<rect{} rx=\"{}\" ry=\"{}\"{paint}/>",
+                    RectAttrs(rect.rect),
+                    Num(rect.radius_x),
+                    Num(rect.radius_y),
+                ));
+            }
+            DrawCommand::StrokeRoundedRect {
+                rect,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let stroke = self.stroke(brush, *stroke_width, style.as_ref());
+                self.element(format_args!(
+                    "<rect{} rx=\"{}\" ry=\"{}\" fill=\"none\"{stroke}/>",
+                    RectAttrs(rect.rect),
+                    Num(rect.radius_x),
+                    Num(rect.radius_y),
+                ));
+            }
+            DrawCommand::FillEllipse { ellipse, brush } => {
+                let paint = self.paint(brush, "fill");
+                self.element(format_args!("<ellipse{}{paint}/>", EllipseAttrs(*ellipse)));
+            }
+            DrawCommand::StrokeEllipse {
+                ellipse,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let stroke = self.stroke(brush, *stroke_width, style.as_ref());
+                self.element(format_args!(
+                    "<ellipse{} fill=\"none\"{stroke}/>",
+                    EllipseAttrs(*ellipse)
+                ));
+            }
+            DrawCommand::FillPath { path, brush } => {
+                let paint = self.paint(brush, "fill");
+                self.element(format_args!(
+                    "<path d=\"{}\" fill-rule=\"{}\"{paint}/>",
+                    path_data(path),
+                    fill_rule(path.fill_mode()),
+                ));
+            }
+            DrawCommand::StrokePath {
+                path,
+                brush,
+                stroke_width,
+                style,
+            } => {
+                let stroke = self.stroke(brush, *stroke_width, style.as_ref());
+                self.element(format_args!(
+                    "<path d=\"{}\" fill=\"none\"{stroke}/>",
+                    path_data(path)
+                ));
+            }
+            DrawCommand::DrawBitmap {
+                image,
+                dest,
+                opacity,
+                interpolation,
+                source,
+            } => {
+                let Some(href) = data_uri(image) else {
+                    return;
+                };
+                let size = image.size().cast::<f32>();
+                let source = source.unwrap_or(Rect2D {
+                    left: 0.0,
+                    top: 0.0,
+                    right: size.width,
+                    bottom: size.height,
+                });
+                // A nested viewport crops the image to its source region.
+                self.element(format_args!(
+                    "<svg{} viewBox=\"{} {} {} {}\" preserveAspectRatio=\"none\"{}>\
+                     <image width=\"{}\" height=\"{}\"{} href=\"{href}\"/></svg>",
+                    RectAttrs(*dest),
+                    Num(source.left),
+                    Num(source.top),
+                    Num(source.width()),
+                    Num(source.height()),
+                    Opacity("opacity", *opacity),
+                    Num(size.width),
+                    Num(size.height),
+                    image_rendering(*interpolation),
+                ));
+            }
+            DrawCommand::SetTransform(transform) => self.transforms.set(*transform),
+            DrawCommand::PushTransform(transform) => self.transforms.push(*transform),
+            DrawCommand::PopTransform => {
+                self.transforms.pop();
+            }
+            DrawCommand::PushClipRect { rect, .. } => {
+                // Direct2D clips to the axis-aligned bounds of the transformed
+                // rectangle.
+                let world = self.transforms.current();
+                let corners = corners(*rect).map(|p| world.transform_point(p));
+                let bounds = Rect2D {
+                    left: corners.iter().map(|p| p.x).fold(f32::INFINITY, f32::min),
+                    top: corners.iter().map(|p| p.y).fold(f32::INFINITY, f32::min),
+                    right: corners
+                        .iter()
+                        .map(|p| p.x)
+                        .fold(f32::NEG_INFINITY, f32::max),
+                    bottom: corners
+                        .iter()
+                        .map(|p| p.y)
+                        .fold(f32::NEG_INFINITY, f32::max),
+                };
+                let clip = self.define_clip("", format_args!("<rect{}/>", RectAttrs(bounds)));
+                self.open_scope(
+                    ScopeKind::Clip,
+                    format_args!("<g clip-path=\"url(#{clip})\">"),
+                );
+            }
+            DrawCommand::PushLayer(layer) => self.push_layer(layer),
+            DrawCommand::PopClip => self.close_scope(ScopeKind::Clip),
+            DrawCommand::PopLayer => self.close_scope(ScopeKind::Layer),
+        }
+    }
+
+    /// Opens a group for `layer`, clipped to its content bounds and mask.
+    fn push_layer(&mut self, layer: &LayerSpec) {
+        let world = self.transforms.current();
+        let mut clip = None;
+        if let Some(bounds) = layer.content_bounds {
+            let points = corners(bounds)
+                .map(|p| world.transform_point(p))
+                .map(|p| format!("{},{}", Num(p.x), Num(p.y)))
+                .join(" ");
+            clip = Some(self.define_clip("", format_args!("<polygon points=\"{points}\"/>")));
+        }
+        if let Some(mask) = &layer.mask {
+            // Clipping the mask's clip path by the bounds' intersects them.
+            let bounds = clip
+                .map(|id| format!(" clip-path=\"url(#{id})\""))
+                .unwrap_or_default();
+            clip = Some(self.define_clip(
+                &bounds,
+                format_args!(
+                    "<path d=\"{}\" clip-rule=\"{}\"{}/>",
+                    path_data(mask),
+                    fill_rule(mask.fill_mode()),
+                    TransformAttr("transform", layer.mask_transform.then(world)),
+                ),
+            ));
+        }
+        let opacity = match &layer.opacity_brush {
+            Some(BrushSpec::Solid(color)) => layer.opacity * color.alpha,
+            _ => layer.opacity,
+        };
+
+        let clip = clip
+            .map(|id| format!(" clip-path=\"url(#{id})\""))
+            .unwrap_or_default();
+        self.open_scope(
+            ScopeKind::Layer,
+            format_args!("<g{clip}{}>", Opacity("opacity", opacity)),
+        );
+    }
+
+    /// Writes a drawn element, within a group for the current world transform.
+    fn element(&mut self, element: fmt::Arguments<'_>) {
+        let world = self.transforms.current();
+        if self.transform_group != Some(world) {
+            self.close_transform_group();
+            if !world.is_identity() {
+                self.write(format_args!("<g{}>", TransformAttr("transform", world)));
+                self.transform_group = Some(world);
+            }
+        }
+        self.write(element);
+    }
+
+    /// Opens a clip or layer group.
+    fn open_scope(&mut self, kind: ScopeKind, group: fmt::Arguments<'_>) {
+        self.close_transform_group();
+        self.write(group);
+        self.scopes.push(kind, ());
+    }
+
+    /// Closes the innermost clip or layer group if it is of `kind`. The
+    /// transform group is closed regardless, as it would be by any element.
+    fn close_scope(&mut self, kind: ScopeKind) {
+        self.close_transform_group();
+        if self.scopes.pop(kind).is_some() {
+            self.close_group();
+        }
+    }
+
+    /// Closes the transform group, if one is open.
+    fn close_transform_group(&mut self) {
+        if self.transform_group.take().is_some() {
+            self.close_group();
+        }
+    }
+
+    /// Writes the end of a group which has already been removed from the open
+    /// groups.
+    fn close_group(&mut self) {
+        self.write(format_args!("</g>"));
+    }
+
+    /// Writes a line of the body, indented by the open groups.
+    fn write(&mut self, line: fmt::Arguments<'_>) {
+        let indent = self.scopes.len() + usize::from(self.transform_group.is_some()) + 1;
+        let _ = writeln!(self.body, "{:indent$}{line}", "", indent = indent * 2);
+    }
+
+    /// Defines a clip path containing `content` and with the further
+    /// attributes `attrs`, returning its id.
+    fn define_clip(&mut self, attrs: &str, content: fmt::Arguments<'_>) -> String {
+        let id = self.make_id("clip");
+        let _ = writeln!(
+            self.defs,
+            "    <clipPath id=\"{id}\"{attrs}>{content}</clipPath>"
+        );
+        id
+    }
+
+    /// Makes a unique id for a definition, of the form `<kind><n>`.
+    fn make_id(&mut self, kind: &str) -> String {
+        self.next_id += 1;
+        format!("{kind}{}", self.next_id)
+    }
+
+    /// The attributes painting the `property` ("fill" or "stroke") of an
+    /// element with `brush`, defining any gradient or pattern it needs.
+    fn paint(&mut self, brush: &BrushSpec, property: &str) -> String {
+        match brush {
+            BrushSpec::Solid(color) => {
+                format!(
+                    " {property}=\"{}\"{}",
+                    rgb(*color),
+                    Opacity(&format!("{property}-opacity"), color.alpha)
+                )
+            }
+            BrushSpec::LinearGradient {
+                gradient,
+                start,
+                end,
+            } => {
+                let id = self.define_gradient(
+                    "linearGradient",
+                    format_args!(
+                        " x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"",
+                        Num(start.x),
+                        Num(start.y),
+                        Num(end.x),
+                        Num(end.y)
+                    ),
+                    gradient,
+                );
+                format!(" {property}=\"url(#{id})\"")
+            }
+            BrushSpec::RadialGradient {
+                gradient,
+                ellipse,
+                origin_offset,
+            } => {
+                let center = ellipse.center;
+                // SVG gradients are circular, so are scaled vertically into
+                // the ellipse.
+                let scale_y = if ellipse.radius_x == 0.0 {
+                    1.0
+                } else {
+                    ellipse.radius_y / ellipse.radius_x
+                };
+                let transform = Transform2D::scale_about(1.0, scale_y, center);
+                let focus = Point2D {
+                    x: center.x + origin_offset.x,
+                    y: center.y + origin_offset.y / scale_y,
+                };
+                let id = self.define_gradient(
+                    "radialGradient",
+                    format_args!(
+                        " cx=\"{}\" cy=\"{}\" r=\"{}\" fx=\"{}\" fy=\"{}\"{}",
+                        Num(center.x),
+                        Num(center.y),
+                        Num(ellipse.radius_x),
+                        Num(focus.x),
+                        Num(focus.y),
+                        TransformAttr("gradientTransform", transform),
+                    ),
+                    gradient,
+                );
+                format!(" {property}=\"url(#{id})\"")
+            }
+            BrushSpec::Bitmap { image, properties } => match self.define_pattern(image, properties)
+            {
+                Some(id) => format!(
+                    " {property}=\"url(#{id})\"{}",
+                    Opacity(&format!("{property}-opacity"), properties.opacity)
+                ),
+                None => format!(" {property}=\"none\""),
+            },
+        }
+    }
+
+    /// The attributes stroking an element with `brush`.
+    fn stroke(
+        &mut self,
+        brush: &BrushSpec,
+        stroke_width: f32,
+        style: Option<&StrokeStyleProperties>,
+    ) -> String {
+        let mut attrs = self.paint(brush, "stroke");
+        let _ = write!(attrs, " stroke-width=\"{}\"", Num(stroke_width));
+        let style = style.cloned().unwrap_or_default();
+
+        let dashes = dash_array(&style.dash_style);
+        let cap = if dashes.is_some() {
+            style.dash_cap
+        } else {
+            style.start_cap
+        };
+        match cap {
+            CapStyle::Flat | CapStyle::Triangle => {}
+            CapStyle::Square => attrs.push_str(" stroke-linecap=\"square\""),
+            CapStyle::Round => attrs.push_str(" stroke-linecap=\"round\""),
+        }
+        match style.line_join {
+            // SVG miters fall back to bevels beyond the miter limit.
+            LineJoin::Miter | LineJoin::MiterOrBevel => {
+                let _ = write!(attrs, " stroke-miterlimit=\"{}\"", Num(style.miter_limit));
+            }
+            LineJoin::Bevel => attrs.push_str(" stroke-linejoin=\"bevel\""),
+            LineJoin::Round => attrs.push_str(" stroke-linejoin=\"round\""),
+        }
+        if let Some(dashes) = dashes {
+            // Direct2D dashes are in multiples of the stroke width.
+            let dashes = dashes
+                .iter()
+                .map(|dash| Num(dash * stroke_width).to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = write!(attrs, " stroke-dasharray=\"{dashes}\"");
+            if style.dash_offset != 0.0 {
+                let offset = Num(style.dash_offset * stroke_width);
+                let _ = write!(attrs, " stroke-dashoffset=\"{offset}\"");
+            }
+        }
+        attrs
+    }
+
+    /// Defines a gradient element of `tag` with the geometry in `attrs`,
+    /// returning its id.
+    fn define_gradient(
+        &mut self,
+        tag: &str,
+        attrs: fmt::Arguments<'_>,
+        gradient: &Gradient,
+    ) -> String {
+        let spread = match gradient.extend_mode() {
+            ExtendMode::Clamp => "",
+            ExtendMode::Wrap => " spreadMethod=\"repeat\"",
+            ExtendMode::Mirror => " spreadMethod=\"reflect\"",
+        };
+        let interpolation = match gradient.gamma() {
+            Gamma::Srgb => "",
+            Gamma::Linear => " color-interpolation=\"linearRGB\"",
+        };
+        let stops = gradient
+            .stops()
+            .iter()
+            .map(|stop| {
+                format!(
+                    "<stop offset=\"{}\" stop-color=\"{}\"{}/>",
+                    Num(stop.position),
+                    rgb(stop.color),
+                    Opacity("stop-opacity", stop.color.alpha)
+                )
+            })
+            .collect::<String>();
+
+        let id = self.make_id("gradient");
+        let _ = writeln!(
+            self.defs,
+            "    <{tag} id=\"{id}\" gradientUnits=\"userSpaceOnUse\"{attrs}{spread}\
+             {interpolation}>{stops}</{tag}>"
+        );
+        id
+    }
+
+    /// Defines a pattern tiling `image`, returning its id or `None` if the
+    /// image could not be encoded.
+    fn define_pattern(&mut self, image: &Image, props: &BitmapBrushProperties) -> Option<String> {
+        let href = data_uri(image)?;
+        let size = image.size().cast::<f32>();
+        let (width, height) = (Num(size.width), Num(size.height));
+
+        let id = self.make_id("pattern");
+        let _ = writeln!(
+            self.defs,
+            "    <pattern id=\"{id}\" patternUnits=\"userSpaceOnUse\" width=\"{width}\" \
+             height=\"{height}\"{}><image width=\"{width}\" height=\"{height}\"{} \
+             href=\"{href}\"/></pattern>",
+            TransformAttr("patternTransform", props.transform),
+            image_rendering(props.interpolation),
+        );
+        Some(id)
+    }
+}
+
+impl Backend for SvgDocument {
+    type Error = Infallible;
+
+    fn render(&mut self, list: &DrawList) -> Result<(), Self::Error> {
+        SvgDocument::render(self, list);
+        Ok(())
+    }
+}
+
+/// Formats `path` as SVG path data.
+pub(crate) fn path_data(path: &Path) -> String {
+    let mut data = String::new();
+    for segment in path.segments() {
+        if !data.is_empty() {
+            data.push(' ');
+        }
+        let _ = match *segment {
+            PathSegment::MoveTo(p) => write!(data, "M{} {}", Num(p.x), Num(p.y)),
+            PathSegment::LineTo(p) => write!(data, "L{} {}", Num(p.x), Num(p.y)),
+            PathSegment::QuadTo { ctrl, to } => write!(
+                data,
+                "Q{} {} {} {}",
+                Num(ctrl.x),
+                Num(ctrl.y),
+                Num(to.x),
+                Num(to.y)
+            ),
+            PathSegment::CubicTo { ctrl1, ctrl2, to } => write!(
+                data,
+                "C{} {} {} {} {} {}",
+                Num(ctrl1.x),
+                Num(ctrl1.y),
+                Num(ctrl2.x),
+                Num(ctrl2.y),
+                Num(to.x),
+                Num(to.y)
+            ),
+            PathSegment::ArcTo(arc) => write!(
+                data,
+                "A{} {} {} {} {} {} {}",
+                Num(arc.radius.width),
+                Num(arc.radius.height),
+                Num(arc.rotation.to_degrees()),
+                u8::from(arc.arc_size == ArcSize::Large),
+                u8::from(arc.sweep == SweepDirection::Clockwise),
+                Num(arc.to.x),
+                Num(arc.to.y)
+            ),
+            PathSegment::Close => write!(data, "Z"),
+        };
+    }
+    data
+}
+
+/// The SVG fill rule equivalent to `mode`.
+fn fill_rule(mode: FillMode) -> &'static str {
+    match mode {
+        FillMode::Alternate => "evenodd",
+        FillMode::Winding => "nonzero",
+    }
+}
+
+/// The lengths of the dashes and gaps of `style`, in multiples of the stroke
+/// width, or `None` for a solid stroke. The presets match Direct2D's.
+fn dash_array(style: &DashStyle) -> Option<Vec<f32>> {
+    match style {
+        DashStyle::Solid => None,
+        DashStyle::Dash => Some(vec![2.0, 2.0]),
+        DashStyle::Dot => Some(vec![0.0, 2.0]),
+        DashStyle::DashDot => Some(vec![2.0, 2.0, 0.0, 2.0]),
+        DashStyle::DashDotDot => Some(vec![2.0, 2.0, 0.0, 2.0, 0.0, 2.0]),
+        DashStyle::Custom(dashes) if dashes.is_empty() => None,
+        DashStyle::Custom(dashes) => Some(dashes.clone()),
+    }
+}
+
+/// The attribute with which an image is sampled, if not smoothly.
+fn image_rendering(interpolation: InterpolationMode) -> &'static str {
+    match interpolation {
+        InterpolationMode::NearestNeighbor => " image-rendering=\"pixelated\"",
+        _ => "",
+    }
+}
+
+/// The corners of `rect`, clockwise from the top-left.
+fn corners(rect: Rect2D<f32>) -> [Point2D<f32>; 4] {
+    [
+        Point2D {
+            x: rect.left,
+            y: rect.top,
+        },
+        Point2D {
+            x: rect.right,
+            y: rect.top,
+        },
+        Point2D {
+            x: rect.right,
+            y: rect.bottom,
+        },
+        Point2D {
+            x: rect.left,
+            y: rect.bottom,
+        },
+    ]
+}
+
+/// The color as an opaque `#RRGGBB` value, its alpha being given separately.
+fn rgb(color: Color) -> String {
+    let [r, g, b, _] = color.to_bytes();
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
+
+/// `image` encoded as a PNG `data:` URI, or `None` if it could not be
+/// encoded.
+fn data_uri(image: &Image) -> Option<String> {
+    let png = image.encode_png().ok()?;
+    Some(format!("data:image/png;base64,{}", base64(&png)))
+}
+
+/// Encodes `bytes` as padded standard base64.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Formats a number compactly and stably, to three decimal places.
+#[derive(Clone, Copy)]
+struct Num(f32);
+
+impl Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rounded = (f64::from(self.0) * 1000.0).round() / 1000.0;
+        // Avoid printing negative zero as "-0".
+        write!(f, "{}", rounded + 0.0)
+    }
+}
+
+/// Formats an opacity attribute named by the first field, omitted if fully
+/// opaque.
+struct Opacity<'a>(&'a str, f32);
+
+impl Display for Opacity<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            opacity if opacity >= 1.0 => Ok(()),
+            opacity => write!(f, " {}=\"{}\"", self.0, Num(opacity.max(0.0))),
+        }
+    }
+}
+
+/// Formats the position and size attributes of a rectangle.
+struct RectAttrs(Rect2D<f32>);
+
+impl Display for RectAttrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rect = self.0;
+        write!(
+            f,
+            " x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+            Num(rect.left.min(rect.right)),
+            Num(rect.top.min(rect.bottom)),
+            Num(rect.width().abs()),
+            Num(rect.height().abs()),
+        )
+    }
+}
+
+/// Formats the center and radii attributes of an ellipse.
+struct EllipseAttrs(Ellipse2D<f32>);
+
+impl Display for EllipseAttrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ellipse = self.0;
+        write!(
+            f,
+            " cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\"",
+            Num(ellipse.center.x),
+            Num(ellipse.center.y),
+            Num(ellipse.radius_x),
+            Num(ellipse.radius_y),
+        )
+    }
+}
+
+/// Formats a transform attribute named by the first field, e.g. `transform`,
+/// as an SVG `matrix(..)`. It is omitted if the identity.
+struct TransformAttr(&'static str, Transform2D);
+
+impl Display for TransformAttr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = self.1;
+        if t.is_identity() {
+            return Ok(());
+        }
+        write!(
+            f,
+            " {}=\"matrix({} {} {} {} {} {})\"",
+            self.0,
+            Num(t.m11),
+            Num(t.m12),
+            Num(t.m21),
+            Num(t.m22),
+            Num(t.dx),
+            Num(t.dy)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{layer::AntialiasMode, stroke::DashStyle};
+    use ::pretty_assertions::assert_eq;
+    use ::std::rc::Rc;
+    use ::win_geom::d2::RoundedRect2D;
+
+    fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rect2D<f32> {
+        Rect2D {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    fn size(width: f32, height: f32) -> Size2D<f32> {
+        Size2D { width, height }
+    }
+
+    #[test]
+    fn test_exports_shapes() {
+        let mut list = DrawList::new();
+        list.clear(Color::white());
+        list.draw_line(
+            Point2D { x: 0.0, y: 1.5 },
+            Point2D { x: 10.0, y: 1.5 },
+            3.0,
+            Color::black(),
+            None,
+        );
+        list.fill_rect(rect(1.0, 2.0, 5.0, 4.0), Color::red().with_alpha(0.5));
+        list.stroke_rounded_rect(
+            RoundedRect2D {
+                rect: rect(2.0, 2.0, 8.0, 8.0),
+                radius_x: 1.0,
+                radius_y: 2.0,
+            },
+            Color::blue(),
+            0.5,
+            Some(StrokeStyleProperties {
+                dash_style: DashStyle::Dash,
+                ..Default::default()
+            }),
+        );
+        list.fill_ellipse(
+            Ellipse2D {
+                center: Point2D { x: 5.0, y: 5.0 },
+                radius_x: 3.0,
+                radius_y: 1.0 / 3.0,
+            },
+            Color::green(),
+        );
+
+        assert_eq!(
+            list.to_svg(size(10.0, 10.0)),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10" viewBox="0 0 10 10">
+  <rect width="10" height="10" fill="#FFFFFF"/>
+  <line x1="0" y1="1.5" x2="10" y2="1.5" stroke="#000000" stroke-width="3" stroke-miterlimit="10"/>
+  <rect x="1" y="2" width="4" height="2" fill="#FF0000" fill-opacity="0.5"/>
+  <rect x="2" y="2" width="6" height="6" rx="1" ry="2" fill="none" stroke="#0000FF" stroke-width="0.5" stroke-miterlimit="10" stroke-dasharray="1 1"/>
+  <ellipse cx="5" cy="5" rx="3" ry="0.333" fill="#008000"/>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn test_transforms_and_clips_map_to_groups() {
+        let mut list = DrawList::new();
+        list.push_transform(Transform2D::translation(5.0, 0.0));
+        list.fill_rect(rect(0.0, 0.0, 1.0, 1.0), Color::red());
+        list.fill_rect(rect(1.0, 1.0, 2.0, 2.0), Color::red());
+        list.push_clip_rect(rect(0.0, 0.0, 4.0, 4.0), AntialiasMode::Aliased);
+        list.fill_rect(rect(0.0, 0.0, 1.0, 1.0), Color::red());
+        list.pop_transform();
+        list.fill_rect(rect(0.0, 0.0, 1.0, 1.0), Color::red());
+        // Left open, and closed by the end of the list.
+
+        assert_eq!(
+            list.to_svg(size(10.0, 10.0)),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10" viewBox="0 0 10 10">
+  <defs>
+    <clipPath id="clip1"><rect x="5" y="0" width="4" height="4"/></clipPath>
+  </defs>
+  <g transform="matrix(1 0 0 1 5 0)">
+    <rect x="0" y="0" width="1" height="1" fill="#FF0000"/>
+    <rect x="1" y="1" width="1" height="1" fill="#FF0000"/>
+  </g>
+  <g clip-path="url(#clip1)">
+    <g transform="matrix(1 0 0 1 5 0)">
+      <rect x="0" y="0" width="1" height="1" fill="#FF0000"/>
+    </g>
+    <rect x="0" y="0" width="1" height="1" fill="#FF0000"/>
+  </g>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn test_pops_of_the_wrong_kind_are_ignored() {
+        let mut list = DrawList::new();
+        list.push_clip_rect(rect(0.0, 0.0, 4.0, 4.0), AntialiasMode::Aliased);
+        list.pop_layer();
+        list.fill_rect(rect(0.0, 0.0, 1.0, 1.0), Color::red());
+        list.pop_clip();
+        list.fill_rect(rect(1.0, 1.0, 2.0, 2.0), Color::red());
+
+        assert_eq!(
+            list.to_svg(size(4.0, 4.0)),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4" viewBox="0 0 4 4">
+  <defs>
+    <clipPath id="clip1"><rect x="0" y="0" width="4" height="4"/></clipPath>
+  </defs>
+  <g clip-path="url(#clip1)">
+    <rect x="0" y="0" width="1" height="1" fill="#FF0000"/>
+  </g>
+  <rect x="1" y="1" width="1" height="1" fill="#FF0000"/>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn test_layers_intersect_bounds_and_mask() {
+        let mut mask = Path::new();
+        mask.move_to(Point2D { x: 0.0, y: 0.0 })
+            .line_to(Point2D { x: 4.0, y: 0.0 })
+            .line_to(Point2D { x: 0.0, y: 4.0 })
+            .close();
+
+        let mut list = DrawList::new();
+        list.push_layer(LayerSpec {
+            content_bounds: Some(rect(0.0, 0.0, 2.0, 2.0)),
+            mask: Some(Rc::new(mask)),
+            opacity: 0.5,
+            opacity_brush: Some(BrushSpec::Solid(Color::black().with_alpha(0.5))),
+            ..Default::default()
+        });
+        list.fill_rect(rect(0.0, 0.0, 4.0, 4.0), Color::red());
+        list.pop_layer();
+        // Unmatched, so ignored.
+        list.pop_clip();
+
+        assert_eq!(
+            list.to_svg(size(4.0, 4.0)),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4" viewBox="0 0 4 4">
+  <defs>
+    <clipPath id="clip1"><polygon points="0,0 2,0 2,2 0,2"/></clipPath>
+    <clipPath id="clip2" clip-path="url(#clip1)"><path d="M0 0 L4 0 L0 4 Z" clip-rule="evenodd"/></clipPath>
+  </defs>
+  <g clip-path="url(#clip2)" opacity="0.25">
+    <rect x="0" y="0" width="4" height="4" fill="#FF0000"/>
+  </g>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn test_gradients_are_defined_separately() {
+        let mut list = DrawList::new();
+        let brush = BrushSpec::LinearGradient {
+            gradient: Gradient::two_stop(Color::red(), Color::blue()),
+            start: Point2D { x: 0.0, y: 0.0 },
+            end: Point2D { x: 4.0, y: 0.0 },
+        };
+        list.fill_rect(rect(0.0, 0.0, 4.0, 4.0), brush);
+
+        let svg = list.to_svg(size(4.0, 4.0));
+
+        assert!(svg.contains(
+            r##"<linearGradient id="gradient1" gradientUnits="userSpaceOnUse" x1="0" y1="0" x2="4" y2="0"><stop offset="0" stop-color="#FF0000"/><stop offset="1" stop-color="#0000FF"/></linearGradient>"##
+        ));
+        assert!(svg.contains(r##"fill="url(#gradient1)""##));
+    }
+
+    #[test]
+    fn test_numbers_are_rounded_without_negative_zero() {
+        assert_eq!(Num(-0.0).to_string(), "0");
+        assert_eq!(Num(-0.0001).to_string(), "0");
+        assert_eq!(Num(0.1).to_string(), "0.1");
+        assert_eq!(Num(2.0 / 3.0).to_string(), "0.667");
+        assert_eq!(Num(-12.5).to_string(), "-12.5");
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -0,0 +1,942 @@
+//! Importing simple SVG documents, such as icons, as draw lists.
+
+use super::path_data::{parse_number_list, parse_path_data, PathDataError};
+use crate::{
+    color::Color,
+    draw_list::{BrushSpec, DrawList},
+    path::{FillMode, Path},
+    stroke::{CapStyle, DashStyle, LineJoin, StrokeStyleProperties},
+};
+use ::roxmltree::{Document, Node};
+use ::std::rc::Rc;
+use ::win_geom::d2::{Ellipse2D, Point2D, Rect2D, RoundedRect2D, Size2D, Transform2D};
+
+/// Errors which can occur when importing an SVG document with
+/// [`SvgDrawing::parse`].
+#[derive(::thiserror::Error, Debug)]
+pub enum SvgImportError {
+    /// The document is not well-formed XML.
+    #[error("failed to parse SVG document: {0}")]
+    Xml(#[from] ::roxmltree::Error),
+    /// The root element of the document is not `<svg>`.
+    #[error("root element is <{0}>, expected <svg>")]
+    NotSvg(String),
+    /// The document has neither a width and height nor a `viewBox`, so its
+    /// size is unknown.
+    #[error("document has neither a size nor a viewBox")]
+    MissingSize,
+    /// An attribute which the document's size depends on, such as the root's
+    /// `viewBox`, has a value which could not be parsed.
+    #[error("invalid {name} of <{element}>: {value:?}")]
+    InvalidAttribute {
+        /// The name of the element.
+        element: String,
+        /// The name of the attribute.
+        name: String,
+        /// The value which could not be parsed.
+        value: String,
+    },
+    /// The path data of a `<path>` is invalid.
+    #[error(transparent)]
+    PathData(#[from] PathDataError),
+}
+
+/// A drawing imported from an SVG document, e.g. an icon drawn by a designer.
+///
+/// Only the basic shapes are imported: `<rect>`, `<circle>`, `<ellipse>`,
+/// `<line>`, `<polyline>`, `<polygon>` and `<path>`, which may be nested in
+/// `<g>` groups and positioned with `transform` attributes. They are painted
+/// with solid colors from their `fill`, `stroke` and `color` properties, given
+/// either as presentation attributes or in `style` attributes, along with the
+/// usual stroke properties and opacities.
+///
+/// Anything else is ignored, including text, images, `<use>` references,
+/// `<style>` sheets and the contents of `<defs>`. As in SVG, attributes and
+/// properties with invalid or unsupported values are ignored too, e.g. lengths
+/// in units other than pixels. Paint with a gradient or pattern is treated as
+/// `none`, and group opacity is applied to each shape in the group separately
+/// rather than to the group as a whole.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{Color, DrawCommand, SvgDrawing};
+/// use ::win_geom::d2::Size2D;
+///
+/// let icon = SvgDrawing::parse(
+///     r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" width="48" height="48">
+///         <circle cx="12" cy="12" r="10" fill="crimson"/>
+///     </svg>"#,
+/// )?;
+///
+/// assert_eq!(icon.size, Size2D { width: 48.0, height: 48.0 });
+/// // The view box is scaled to fit the size.
+/// assert!(matches!(icon.list.commands()[0], DrawCommand::PushTransform(_)));
+/// # Ok::<(), ::d2d::SvgImportError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgDrawing {
+    /// The size of the drawing: the document's width and height if it has
+    /// them, and otherwise the size of its `viewBox`.
+    pub size: Size2D<f32>,
+    /// The commands which draw the document into a rectangle of `size` at the
+    /// origin.
+    pub list: DrawList,
+}
+
+impl SvgDrawing {
+    /// Imports the shapes of the SVG document `doc`.
+    pub fn parse(doc: &str) -> Result<Self, SvgImportError> {
+        let doc = Document::parse(doc)?;
+        let root = doc.root_element();
+        if root.tag_name().name() != "svg" {
+            return Err(SvgImportError::NotSvg(root.tag_name().name().to_owned()));
+        }
+
+        let view_box = root
+            .attribute("viewBox")
+            .map(|value| {
+                match parse_number_list(value).as_deref() {
+                    Ok(&[x, y, width, height]) if width > 0.0 && height > 0.0 => Some(
+                        Rect2D::from_size_and_origin(Size2D { width, height }, Point2D { x, y }),
+                    ),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid(root, "viewBox", value))
+            })
+            .transpose()?;
+        // Sizes in other units, such as percentages, fall back to the view box.
+        let length = |name| root.attribute(name).and_then(parse_length);
+        let size = match (length("width"), length("height"), view_box) {
+            (Some(width), Some(height), _) => Size2D { width, height },
+            (width, height, Some(view_box)) => Size2D {
+                width: width.unwrap_or(view_box.width()),
+                height: height.unwrap_or(view_box.height()),
+            },
+            _ => return Err(SvgImportError::MissingSize),
+        };
+
+        let mut list = DrawList::new();
+        if let Some(view_box) = view_box {
+            let align = root.attribute("preserveAspectRatio").unwrap_or_default();
+            let transform = view_box_transform(view_box, size, align);
+            if !transform.is_identity() {
+                list.push_transform(transform);
+            }
+        }
+        let mut importer = Importer { list };
+        // The root's own properties are inherited by the whole document.
+        let mut style = Style::default();
+        if style.apply(root) {
+            importer.children(root, &style)?;
+        }
+
+        Ok(Self {
+            size,
+            list: importer.list,
+        })
+    }
+}
+
+/// Builds the draw list of a document as its elements are visited.
+struct Importer {
+    list: DrawList,
+}
+
+impl Importer {
+    /// Imports the children of `node`, which inherit `style`.
+    fn children(&mut self, node: Node<'_, '_>, style: &Style) -> Result<(), SvgImportError> {
+        for child in node.children().filter(Node::is_element) {
+            self.element(child, style)?;
+        }
+        Ok(())
+    }
+
+    /// Imports `node` and any children, given the style it inherits.
+    fn element(&mut self, node: Node<'_, '_>, inherited: &Style) -> Result<(), SvgImportError> {
+        let name = node.tag_name().name();
+        if !matches!(
+            name,
+            "g" | "a" | "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon" | "path"
+        ) {
+            return Ok(());
+        }
+        let mut style = inherited.clone();
+        if !style.apply(node) {
+            return Ok(());
+        }
+
+        let transform = node
+            .attribute("transform")
+            .and_then(|value| {
+                let transform = parse_transform(value);
+                if transform.is_none() {
+                    ignore_invalid(node, "transform", value);
+                }
+                transform
+            })
+            .unwrap_or_else(Transform2D::identity);
+        let is_transformed = !transform.is_identity();
+        if is_transformed {
+            self.list.push_transform(transform);
+        }
+
+        match name {
+            "g" | "a" => self.children(node, &style)?,
+            _ => {
+                if let Some(shape) = Shape::parse(node, &style)? {
+                    self.draw(shape, &style);
+                }
+            }
+        }
+
+        if is_transformed {
+            self.list.pop_transform();
+        }
+        Ok(())
+    }
+
+    /// Fills and then strokes `shape`.
+    fn draw(&mut self, shape: Shape, style: &Style) {
+        if let Some(brush) = style.fill_brush() {
+            match &shape {
+                Shape::Rect(rect) => self.list.fill_rect(*rect, brush),
+                Shape::RoundedRect(rect) => self.list.fill_rounded_rect(*rect, brush),
+                Shape::Ellipse(ellipse) => self.list.fill_ellipse(*ellipse, brush),
+                Shape::Line(..) => {}
+                Shape::Path(path) => self.list.fill_path(path.clone(), brush),
+            }
+        }
+
+        let Some(brush) = style.stroke_brush() else {
+            return;
+        };
+        let (width, stroke) = (style.stroke_width, Some(style.stroke_style()));
+        match shape {
+            Shape::Rect(rect) => self.list.stroke_rect(rect, brush, width, stroke),
+            Shape::RoundedRect(rect) => self.list.stroke_rounded_rect(rect, brush, width, stroke),
+            Shape::Ellipse(ellipse) => self.list.stroke_ellipse(ellipse, brush, width, stroke),
+            Shape::Line(p0, p1) => self.list.draw_line(p0, p1, width, brush, stroke),
+            Shape::Path(path) => self.list.stroke_path(path, brush, width, stroke),
+        }
+    }
+}
+
+/// The geometry of a basic shape element.
+enum Shape {
+    Rect(Rect2D<f32>),
+    RoundedRect(RoundedRect2D<f32>),
+    Ellipse(Ellipse2D<f32>),
+    Line(Point2D<f32>, Point2D<f32>),
+    Path(Rc<Path>),
+}
+
+impl Shape {
+    /// The geometry of `node`, or `None` if it has none and so is not drawn,
+    /// e.g. a `<rect>` without a width.
+    fn parse(node: Node<'_, '_>, style: &Style) -> Result<Option<Self>, SvgImportError> {
+        let length = |name| -> Option<f32> {
+            let value = node.attribute(name)?;
+            let length = parse_length(value);
+            if length.is_none() {
+                ignore_invalid(node, name, value);
+            }
+            length
+        };
+        let point = |x, y| Point2D {
+            x: length(x).unwrap_or_default(),
+            y: length(y).unwrap_or_default(),
+        };
+
+        let shape = match node.tag_name().name() {
+            "rect" => {
+                let size = Size2D {
+                    width: length("width").unwrap_or_default(),
+                    height: length("height").unwrap_or_default(),
+                };
+                if size.width <= 0.0 || size.height <= 0.0 {
+                    return Ok(None);
+                }
+                let rect = Rect2D::from_size_and_origin(size, point("x", "y"));
+                // A radius missing from either axis is taken from the other.
+                let (rx, ry) = match (length("rx"), length("ry")) {
+                    (Some(rx), None) => (rx, rx),
+                    (None, Some(ry)) => (ry, ry),
+                    (rx, ry) => (rx.unwrap_or_default(), ry.unwrap_or_default()),
+                };
+                let radius_x = rx.clamp(0.0, size.width / 2.0);
+                let radius_y = ry.clamp(0.0, size.height / 2.0);
+                if radius_x > 0.0 && radius_y > 0.0 {
+                    Self::RoundedRect(RoundedRect2D {
+                        rect,
+                        radius_x,
+                        radius_y,
+                    })
+                } else {
+                    Self::Rect(rect)
+                }
+            }
+            "circle" => {
+                let r = length("r").unwrap_or_default();
+                if r <= 0.0 {
+                    return Ok(None);
+                }
+                Self::Ellipse(Ellipse2D {
+                    center: point("cx", "cy"),
+                    radius_x: r,
+                    radius_y: r,
+                })
+            }
+            "ellipse" => {
+                let radius_x = length("rx").unwrap_or_default();
+                let radius_y = length("ry").unwrap_or_default();
+                if radius_x <= 0.0 || radius_y <= 0.0 {
+                    return Ok(None);
+                }
+                Self::Ellipse(Ellipse2D {
+                    center: point("cx", "cy"),
+                    radius_x,
+                    radius_y,
+                })
+            }
+            "line" => Self::Line(point("x1", "y1"), point("x2", "y2")),
+            name @ ("polyline" | "polygon") => {
+                let value = node.attribute("points").unwrap_or_default();
+                let Ok(numbers) = parse_number_list(value) else {
+                    ignore_invalid(node, "points", value);
+                    return Ok(None);
+                };
+                // An odd coordinate out is ignored.
+                let mut points = numbers
+                    .chunks_exact(2)
+                    .map(|p| Point2D { x: p[0], y: p[1] });
+                let Some(start) = points.next() else {
+                    return Ok(None);
+                };
+
+                let mut path = Path::new().with_fill_mode(style.fill_rule);
+                path.move_to(start);
+                for point in points {
+                    path.line_to(point);
+                }
+                if name == "polygon" {
+                    path.close();
+                }
+                Self::Path(Rc::new(path))
+            }
+            "path" => {
+                let path = parse_path_data(node.attribute("d").unwrap_or_default())?;
+                if path.is_empty() {
+                    return Ok(None);
+                }
+                Self::Path(Rc::new(path.with_fill_mode(style.fill_rule)))
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(shape))
+    }
+}
+
+/// Paint given by a `fill` or `stroke` property.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SvgPaint {
+    None,
+    Color(Color),
+    /// The value of the `color` property.
+    CurrentColor,
+}
+
+/// The properties with which a shape is painted, as inherited from its
+/// ancestors and overridden by its own attributes.
+#[derive(Clone, Debug)]
+struct Style {
+    color: Color,
+    fill: SvgPaint,
+    fill_opacity: f32,
+    fill_rule: FillMode,
+    stroke: SvgPaint,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    stroke_style: StrokeStyleProperties,
+    /// The lengths of the stroke's dashes and gaps, or `None` if it is solid.
+    dashes: Option<Vec<f32>>,
+    dash_offset: f32,
+    /// The product of the opacities of the element and its ancestors.
+    opacity: f32,
+}
+
+impl Default for Style {
+    /// SVG's initial values.
+    fn default() -> Self {
+        Self {
+            color: Color::black(),
+            fill: SvgPaint::Color(Color::black()),
+            fill_opacity: 1.0,
+            fill_rule: FillMode::Winding,
+            stroke: SvgPaint::None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            stroke_style: StrokeStyleProperties {
+                // SVG miters are beveled beyond the miter limit.
+                line_join: LineJoin::MiterOrBevel,
+                miter_limit: 4.0,
+                ..Default::default()
+            },
+            dashes: None,
+            dash_offset: 0.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Style {
+    /// Applies the presentation attributes of `node`, and then the
+    /// declarations of its `style` attribute, which take precedence. Invalid
+    /// values are ignored. Returns `false` if the element is not displayed.
+    fn apply(&mut self, node: Node<'_, '_>) -> bool {
+        let attributes = node
+            .attributes()
+            .filter(|attr| attr.namespace().is_none())
+            .map(|attr| (attr.name(), attr.value()));
+        let declarations = node
+            .attribute("style")
+            .unwrap_or_default()
+            .split(';')
+            .filter_map(|declaration| declaration.split_once(':'))
+            .map(|(name, value)| (name.trim(), value.trim()));
+
+        let mut displayed = true;
+        for (name, value) in attributes.chain(declarations) {
+            if value == "inherit" {
+                continue;
+            }
+            match name {
+                "display" => displayed = value != "none",
+                _ => {
+                    if self.set(name, value).is_none() {
+                        ignore_invalid(node, name, value);
+                    }
+                }
+            }
+        }
+        displayed
+    }
+
+    /// Sets the property `name` to `value`, returning `None` if the value is
+    /// invalid. Properties which are not supported are ignored.
+    fn set(&mut self, name: &str, value: &str) -> Option<()> {
+        match name {
+            "color" => self.color = parse_color(value)?,
+            "fill" => self.fill = parse_paint(value)?,
+            "fill-opacity" => self.fill_opacity = parse_opacity(value)?,
+            "fill-rule" => {
+                self.fill_rule = match value {
+                    "nonzero" => FillMode::Winding,
+                    "evenodd" => FillMode::Alternate,
+                    _ => return None,
+                }
+            }
+            "stroke" => self.stroke = parse_paint(value)?,
+            "stroke-opacity" => self.stroke_opacity = parse_opacity(value)?,
+            "stroke-width" => self.stroke_width = parse_length(value)?.max(0.0),
+            "stroke-linecap" => {
+                let cap = match value {
+                    "butt" => CapStyle::Flat,
+                    "round" => CapStyle::Round,
+                    "square" => CapStyle::Square,
+                    _ => return None,
+                };
+                self.stroke_style.start_cap = cap;
+                self.stroke_style.end_cap = cap;
+                self.stroke_style.dash_cap = cap;
+            }
+            "stroke-linejoin" => {
+                self.stroke_style.line_join = match value {
+                    "miter" => LineJoin::MiterOrBevel,
+                    "round" => LineJoin::Round,
+                    "bevel" => LineJoin::Bevel,
+                    _ => return None,
+                }
+            }
+            "stroke-miterlimit" => self.stroke_style.miter_limit = parse_length(value)?.max(1.0),
+            "stroke-dasharray" => {
+                self.dashes = match value {
+                    "none" => None,
+                    _ => {
+                        let dashes = parse_number_list(value).ok()?;
+                        if dashes.iter().any(|dash| *dash < 0.0) {
+                            return None;
+                        }
+                        // No length at all is a solid stroke.
+                        (dashes.iter().sum::<f32>() > 0.0).then_some(dashes)
+                    }
+                }
+            }
+            "stroke-dashoffset" => self.dash_offset = parse_length(value)?,
+            "opacity" => self.opacity *= parse_opacity(value)?,
+            _ => {}
+        }
+        Some(())
+    }
+
+    /// The brush with which to fill shapes, if they are filled.
+    fn fill_brush(&self) -> Option<BrushSpec> {
+        self.brush(self.fill, self.fill_opacity)
+    }
+
+    /// The brush with which to stroke shapes, if they are stroked.
+    fn stroke_brush(&self) -> Option<BrushSpec> {
+        if self.stroke_width == 0.0 {
+            return None;
+        }
+        self.brush(self.stroke, self.stroke_opacity)
+    }
+
+    fn brush(&self, paint: SvgPaint, opacity: f32) -> Option<BrushSpec> {
+        let color = match paint {
+            SvgPaint::None => return None,
+            SvgPaint::Color(color) => color,
+            SvgPaint::CurrentColor => self.color,
+        };
+        let alpha = color.alpha * opacity * self.opacity;
+        Some(BrushSpec::Solid(color.with_alpha(alpha)))
+    }
+
+    /// The style with which to stroke shapes.
+    fn stroke_style(&self) -> StrokeStyleProperties {
+        let mut style = self.stroke_style.clone();
+        // Direct2D dashes are in multiples of the stroke width. An odd number
+        // of lengths is repeated to make an even number, as in SVG.
+        if let Some(dashes) = &self.dashes {
+            let width = self.stroke_width;
+            let repeats = if dashes.len() % 2 == 0 { 1 } else { 2 };
+            style.dash_style = DashStyle::Custom(
+                dashes
+                    .iter()
+                    .cycle()
+                    .take(dashes.len() * repeats)
+                    .map(|dash| dash / width)
+                    .collect(),
+            );
+            style.dash_offset = self.dash_offset / width;
+        }
+        style
+    }
+}
+
+/// Parses paint: `none`, `currentColor` or a color. Paint servers, such as
+/// gradients, are not supported and so are taken as `none`.
+fn parse_paint(value: &str) -> Option<SvgPaint> {
+    match value {
+        "none" => Some(SvgPaint::None),
+        "currentColor" => Some(SvgPaint::CurrentColor),
+        _ if value.starts_with("url(") => Some(SvgPaint::None),
+        _ => parse_color(value).map(SvgPaint::Color),
+    }
+}
+
+/// Parses a CSS color: `#RGB`, `#RRGGBB`, `#RRGGBBAA`, `rgb(..)`, `rgba(..)`
+/// or a keyword such as `red`.
+fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        return match hex.len() {
+            // Each digit of the short form is doubled.
+            3 => Color::from_hex(&hex.chars().flat_map(|c| [c, c]).collect::<String>()),
+            _ => Color::from_hex(hex),
+        };
+    }
+    let function = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("));
+    let Some(args) = function.and_then(|rest| rest.strip_suffix(')')) else {
+        return Color::from_name(value);
+    };
+
+    let args = args
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .collect::<Vec<_>>();
+    let channel = |arg: &str| -> Option<f32> {
+        let channel = match arg.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+            None => arg.parse::<f32>().ok()? / 255.0,
+        };
+        Some(channel.clamp(0.0, 1.0))
+    };
+    let (red, green, blue, alpha) = match args[..] {
+        [r, g, b] => (channel(r)?, channel(g)?, channel(b)?, 1.0),
+        [r, g, b, a] => (channel(r)?, channel(g)?, channel(b)?, parse_opacity(a)?),
+        _ => return None,
+    };
+    Some(Color {
+        red,
+        green,
+        blue,
+        alpha,
+    })
+}
+
+/// Parses an opacity, either a number or a percentage, clamped to 0.0 - 1.0.
+fn parse_opacity(value: &str) -> Option<f32> {
+    let opacity = match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+        None => value.parse().ok()?,
+    };
+    Some(opacity.clamp(0.0, 1.0))
+}
+
+/// Parses a length in user units, which may be given in pixels. Other units
+/// are not supported.
+fn parse_length(value: &str) -> Option<f32> {
+    let value = value.trim();
+    value.strip_suffix("px").unwrap_or(value).parse().ok()
+}
+
+/// Parses a list of transform functions, e.g. `translate(10) rotate(45)`.
+fn parse_transform(value: &str) -> Option<Transform2D> {
+    let mut transform = Transform2D::identity();
+    let mut rest = value.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    while !rest.is_empty() {
+        let (name, args) = rest.split_once('(')?;
+        let (args, after) = args.split_once(')')?;
+        let args = parse_number_list(args).ok()?;
+
+        let function = match (name.trim(), args.as_slice()) {
+            ("matrix", &[m11, m12, m21, m22, dx, dy]) => Transform2D {
+                m11,
+                m12,
+                m21,
+                m22,
+                dx,
+                dy,
+            },
+            ("translate", &[x]) => Transform2D::translation(x, 0.0),
+            ("translate", &[x, y]) => Transform2D::translation(x, y),
+            ("scale", &[s]) => Transform2D::scale(s, s),
+            ("scale", &[sx, sy]) => Transform2D::scale(sx, sy),
+            ("rotate", &[angle]) => Transform2D::rotation(angle.to_radians()),
+            ("rotate", &[angle, x, y]) => {
+                Transform2D::rotation_about(angle.to_radians(), Point2D { x, y })
+            }
+            ("skewX", &[angle]) => Transform2D {
+                m21: angle.to_radians().tan(),
+                ..Transform2D::identity()
+            },
+            ("skewY", &[angle]) => Transform2D {
+                m12: angle.to_radians().tan(),
+                ..Transform2D::identity()
+            },
+            _ => return None,
+        };
+        // Each function applies within those to its left.
+        transform = function.then(transform);
+        rest = after.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+    Some(transform)
+}
+
+/// The transform which maps `view_box` into a viewport of `size` at the
+/// origin, aligned by the `preserveAspectRatio` attribute `align`.
+fn view_box_transform(view_box: Rect2D<f32>, size: Size2D<f32>, align: &str) -> Transform2D {
+    let mut words = align.split_whitespace();
+    let align = words.next().unwrap_or("xMidYMid");
+    let slice = words.next() == Some("slice");
+
+    let (mut sx, mut sy) = (
+        size.width / view_box.width(),
+        size.height / view_box.height(),
+    );
+    let (mut x, mut y) = (0.0, 0.0);
+    if align != "none" {
+        sx = if slice { sx.max(sy) } else { sx.min(sy) };
+        sy = sx;
+        let offset = |name: &str, free: f32| {
+            if align.contains(&format!("{name}Mid")) {
+                free / 2.0
+            } else if align.contains(&format!("{name}Max")) {
+                free
+            } else {
+                0.0
+            }
+        };
+        x = offset("x", size.width - view_box.width() * sx);
+        y = offset("Y", size.height - view_box.height() * sy);
+    }
+
+    Transform2D::translation(-view_box.left, -view_box.top)
+        .then(Transform2D::scale(sx, sy))
+        .then(Transform2D::translation(x, y))
+}
+
+/// Logs that the attribute or style property `name` of `node` is ignored
+/// because its `value` is invalid or not supported.
+fn ignore_invalid(node: Node<'_, '_>, name: &str, value: &str) {
+    ::tracing::debug!(
+        element = node.tag_name().name(),
+        name,
+        value,
+        "Ignoring invalid SVG attribute"
+    );
+}
+
+/// An error for the attribute `name` of `node`.
+fn invalid(node: Node<'_, '_>, name: &str, value: &str) -> SvgImportError {
+    SvgImportError::InvalidAttribute {
+        element: node.tag_name().name().to_owned(),
+        name: name.to_owned(),
+        value: value.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::draw_list::DrawCommand;
+    use ::pretty_assertions::assert_eq;
+
+    fn import(body: &str) -> Vec<DrawCommand> {
+        let doc = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">{body}</svg>"#
+        );
+        SvgDrawing::parse(&doc).unwrap().list.commands().to_vec()
+    }
+
+    fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rect2D<f32> {
+        Rect2D {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    #[test]
+    fn test_imports_basic_shapes() {
+        let commands = import(
+            r##"
+            <rect x="1" y="2" width="3" height="4" fill="#F00"/>
+            <rect width="4" height="4" rx="1" fill="none" stroke="blue" stroke-width="2"/>
+            <circle cx="5" cy="5" r="2" style="fill: rgb(0, 100%, 0); fill-opacity: 50%"/>
+            <line x1="0" y1="0" x2="10" y2="10" stroke="black"/>
+            <polygon points="0,0 4,0 4,4"/>
+            <rect width="0" height="4"/>
+            "##,
+        );
+
+        let stroke = StrokeStyleProperties {
+            line_join: LineJoin::MiterOrBevel,
+            miter_limit: 4.0,
+            ..Default::default()
+        };
+        let mut triangle = Path::new().with_fill_mode(FillMode::Winding);
+        triangle
+            .move_to(Point2D { x: 0.0, y: 0.0 })
+            .line_to(Point2D { x: 4.0, y: 0.0 })
+            .line_to(Point2D { x: 4.0, y: 4.0 })
+            .close();
+        assert_eq!(
+            commands,
+            [
+                DrawCommand::FillRect {
+                    rect: rect(1.0, 2.0, 4.0, 6.0),
+                    brush: BrushSpec::Solid(Color::red()),
+                },
+                DrawCommand::StrokeRoundedRect {
+                    rect: RoundedRect2D {
+                        rect: rect(0.0, 0.0, 4.0, 4.0),
+                        radius_x: 1.0,
+                        radius_y: 1.0,
+                    },
+                    brush: BrushSpec::Solid(Color::blue()),
+                    stroke_width: 2.0,
+                    style: Some(stroke.clone()),
+                },
+                DrawCommand::FillEllipse {
+                    ellipse: Ellipse2D {
+                        center: Point2D { x: 5.0, y: 5.0 },
+                        radius_x: 2.0,
+                        radius_y: 2.0,
+                    },
+                    brush: BrushSpec::Solid(Color::lime().with_alpha(0.5)),
+                },
+                DrawCommand::DrawLine {
+                    p0: Point2D { x: 0.0, y: 0.0 },
+                    p1: Point2D { x: 10.0, y: 10.0 },
+                    stroke_width: 1.0,
+                    brush: BrushSpec::Solid(Color::black()),
+                    style: Some(stroke),
+                },
+                DrawCommand::FillPath {
+                    path: Rc::new(triangle),
+                    brush: BrushSpec::Solid(Color::black()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_groups_inherit_style_and_transforms() {
+        let commands = import(
+            r#"
+            <g color="red" fill="currentColor" opacity="0.5" transform="translate(1 2) scale(2)">
+                <rect width="1" height="1"/>
+                <g display="none"><rect width="1" height="1"/></g>
+                <defs><rect width="1" height="1"/></defs>
+            </g>
+            "#,
+        );
+
+        assert_eq!(
+            commands,
+            [
+                DrawCommand::PushTransform(
+                    Transform2D::scale(2.0, 2.0).then(Transform2D::translation(1.0, 2.0))
+                ),
+                DrawCommand::FillRect {
+                    rect: rect(0.0, 0.0, 1.0, 1.0),
+                    brush: BrushSpec::Solid(Color::red().with_alpha(0.5)),
+                },
+                DrawCommand::PopTransform,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_root_style_is_inherited() {
+        let drawing = SvgDrawing::parse(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24"
+                fill="none" stroke="currentColor" stroke-width="2" color="red">
+                <line x1="2" y1="2" x2="22" y2="22"/>
+                <rect x="4" y="4" width="16" height="16"/>
+            </svg>"#,
+        )
+        .unwrap();
+
+        let stroke = StrokeStyleProperties {
+            line_join: LineJoin::MiterOrBevel,
+            miter_limit: 4.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            drawing.list.commands(),
+            [
+                DrawCommand::DrawLine {
+                    p0: Point2D { x: 2.0, y: 2.0 },
+                    p1: Point2D { x: 22.0, y: 22.0 },
+                    stroke_width: 2.0,
+                    brush: BrushSpec::Solid(Color::red()),
+                    style: Some(stroke.clone()),
+                },
+                DrawCommand::StrokeRect {
+                    rect: rect(4.0, 4.0, 20.0, 20.0),
+                    brush: BrushSpec::Solid(Color::red()),
+                    stroke_width: 2.0,
+                    style: Some(stroke),
+                },
+            ]
+        );
+
+        let hidden =
+            r#"<svg width="1" height="1" display="none"><rect width="1" height="1"/></svg>"#;
+        assert_eq!(SvgDrawing::parse(hidden).unwrap().list.commands(), []);
+    }
+
+    #[test]
+    fn test_invalid_values_are_ignored() {
+        let commands = import(
+            r#"
+            <rect x="1em" width="2" height="2" fill="hsl(0 100% 50%)" stroke="red"
+                stroke-width="1em" style="stroke-linejoin: miter-clip" transform="spin(1)"/>
+            <circle r="1em"/>
+            <polygon points="0,0 a"/>
+            "#,
+        );
+
+        assert_eq!(
+            commands,
+            [
+                DrawCommand::FillRect {
+                    rect: rect(0.0, 0.0, 2.0, 2.0),
+                    brush: BrushSpec::Solid(Color::black()),
+                },
+                DrawCommand::StrokeRect {
+                    rect: rect(0.0, 0.0, 2.0, 2.0),
+                    brush: BrushSpec::Solid(Color::red()),
+                    stroke_width: 1.0,
+                    style: Some(StrokeStyleProperties {
+                        line_join: LineJoin::MiterOrBevel,
+                        miter_limit: 4.0,
+                        ..Default::default()
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_view_box_fits_size() {
+        let drawing = SvgDrawing::parse(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20" viewBox="0 0 10 10"/>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            drawing.list.commands(),
+            [DrawCommand::PushTransform(
+                Transform2D::scale(2.0, 2.0).then(Transform2D::translation(10.0, 0.0))
+            )]
+        );
+
+        let drawing = SvgDrawing::parse(r#"<svg viewBox="-5 -5 10 10" width="100%"/>"#).unwrap();
+        assert_eq!(
+            drawing.size,
+            Size2D {
+                width: 10.0,
+                height: 10.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#fff"), Some(Color::white()));
+        assert_eq!(parse_color("#FF000080"), Some(Color::new_rgba(0xFF000080)));
+        assert_eq!(
+            parse_color("rgb(255 0 0 / 50%)"),
+            Some(Color::red().with_alpha(0.5))
+        );
+        assert_eq!(
+            parse_color("rgba(0,0,255,0.25)"),
+            Some(Color::blue().with_alpha(0.25))
+        );
+        assert_eq!(parse_color("SteelBlue"), Some(Color::steel_blue()));
+        assert_eq!(parse_color("rgb(1, 2)"), None);
+        assert_eq!(parse_color("#12"), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            SvgDrawing::parse("<html/>"),
+            Err(SvgImportError::NotSvg(name)) if name == "html"
+        ));
+        assert!(matches!(
+            SvgDrawing::parse("<svg/>"),
+            Err(SvgImportError::MissingSize)
+        ));
+        assert!(matches!(
+            SvgDrawing::parse("<svg"),
+            Err(SvgImportError::Xml(_))
+        ));
+
+        match SvgDrawing::parse(r#"<svg viewBox="0 0 0 1"/>"#) {
+            Err(SvgImportError::InvalidAttribute {
+                element,
+                name,
+                value,
+            }) => assert_eq!((&*element, &*name, &*value), ("svg", "viewBox", "0 0 0 1")),
+            _ => panic!("expected an invalid attribute"),
+        }
+    }
+}
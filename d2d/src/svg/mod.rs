@@ -0,0 +1,14 @@
+//! Conversion between recorded drawing and SVG: exporting draw lists as SVG
+//! documents, so that frames can be inspected in a browser and vector output
+//! compared without Direct2D, and importing SVG path data and simple documents
+//! such as icons.
+
+mod export;
+#[cfg(feature = "svg")]
+mod import;
+mod path_data;
+
+#[cfg(feature = "svg")]
+pub use self::import::*;
+pub(crate) use self::path_data::parse_path_data;
+pub use self::{export::*, path_data::PathDataError};
@@ -0,0 +1,443 @@
+//! Parsing SVG path data, the mini-language of the `d` attribute of `<path>`.
+
+use crate::path::{ArcSegment, ArcSize, FillMode, Path, SweepDirection};
+use ::win_geom::d2::{Point2D, Size2D};
+
+/// An error in SVG path data, as returned by [`Path::from_svg`].
+#[derive(::thiserror::Error, Clone, Debug, PartialEq, Eq)]
+#[error("invalid path data at byte {position}: {reason}")]
+pub struct PathDataError {
+    /// The byte index into the path data at which the error was found.
+    pub position: usize,
+    /// What was wrong.
+    pub reason: &'static str,
+}
+
+/// Parses SVG path data into a [`Path`] with the [`Winding`](FillMode::Winding)
+/// fill mode, which is SVG's default fill rule.
+pub(crate) fn parse_path_data(data: &str) -> Result<Path, PathDataError> {
+    let mut parser = Parser {
+        data,
+        position: 0,
+        path: Path::new().with_fill_mode(FillMode::Winding),
+        last_ctrl: None,
+    };
+    parser.parse()?;
+    Ok(parser.path)
+}
+
+/// Parses a list of numbers separated by whitespace or commas, with the same
+/// syntax as the numbers of path data, e.g. of a `points` attribute.
+#[cfg(feature = "svg")]
+pub(crate) fn parse_number_list(data: &str) -> Result<Vec<f32>, PathDataError> {
+    let mut parser = Parser {
+        data,
+        position: 0,
+        path: Path::new(),
+        last_ctrl: None,
+    };
+    parser.skip_separators();
+    let mut numbers = Vec::new();
+    while parser.peek().is_some() {
+        numbers.push(parser.number()?);
+    }
+    Ok(numbers)
+}
+
+/// The state of a parse: the position in the data and the path so far.
+struct Parser<'a> {
+    data: &'a str,
+    /// The byte index of the next character to be parsed.
+    position: usize,
+    path: Path,
+    /// The last control point of the previous segment and whether it was
+    /// cubic, from which smooth curves reflect their first control point.
+    last_ctrl: Option<(Point2D<f32>, Curve)>,
+}
+
+/// The kind of Bézier curve whose control point is reflected by a smooth
+/// curve command.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Curve {
+    Quad,
+    Cubic,
+}
+
+impl Parser<'_> {
+    fn parse(&mut self) -> Result<(), PathDataError> {
+        self.skip_separators();
+        let mut previous: Option<u8> = None;
+        while let Some(c) = self.peek() {
+            let start = self.position;
+            let command = if c.is_ascii_alphabetic() {
+                self.position += 1;
+                c
+            } else {
+                // Further arguments repeat the previous command, except that
+                // those of a move are lines.
+                match previous {
+                    Some(b'M') => b'L',
+                    Some(b'm') => b'l',
+                    Some(b'Z' | b'z') | None => return Err(self.error("expected a command")),
+                    Some(command) => command,
+                }
+            };
+            if previous.is_none() && !matches!(command, b'M' | b'm') {
+                return Err(PathDataError {
+                    position: start,
+                    reason: "path data must begin with a move",
+                });
+            }
+
+            self.command(command)?;
+            previous = Some(command);
+            self.skip_separators();
+        }
+        Ok(())
+    }
+
+    /// Parses the arguments of `command` and adds its segment.
+    fn command(&mut self, command: u8) -> Result<(), PathDataError> {
+        let current = self.path.current_point().unwrap_or_default();
+        let origin = if command.is_ascii_lowercase() {
+            current
+        } else {
+            Point2D::default()
+        };
+        let mut ctrl = None;
+
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                let to = self.point(origin)?;
+                self.path.move_to(to);
+            }
+            b'L' => {
+                let to = self.point(origin)?;
+                self.path.line_to(to);
+            }
+            b'H' => {
+                let x = origin.x + self.number()?;
+                self.path.line_to(Point2D { x, y: current.y });
+            }
+            b'V' => {
+                let y = origin.y + self.number()?;
+                self.path.line_to(Point2D { x: current.x, y });
+            }
+            b'C' => {
+                let ctrl1 = self.point(origin)?;
+                let ctrl2 = self.point(origin)?;
+                let to = self.point(origin)?;
+                self.path.cubic_to(ctrl1, ctrl2, to);
+                ctrl = Some((ctrl2, Curve::Cubic));
+            }
+            b'S' => {
+                let ctrl1 = self.reflected_ctrl(current, Curve::Cubic);
+                let ctrl2 = self.point(origin)?;
+                let to = self.point(origin)?;
+                self.path.cubic_to(ctrl1, ctrl2, to);
+                ctrl = Some((ctrl2, Curve::Cubic));
+            }
+            b'Q' => {
+                let quad_ctrl = self.point(origin)?;
+                let to = self.point(origin)?;
+                self.path.quad_to(quad_ctrl, to);
+                ctrl = Some((quad_ctrl, Curve::Quad));
+            }
+            b'T' => {
+                let quad_ctrl = self.reflected_ctrl(current, Curve::Quad);
+                let to = self.point(origin)?;
+                self.path.quad_to(quad_ctrl, to);
+                ctrl = Some((quad_ctrl, Curve::Quad));
+            }
+            b'A' => {
+                let radius = Size2D {
+                    width: self.number()?.abs(),
+                    height: self.number()?.abs(),
+                };
+                let rotation = self.number()?.to_radians();
+                let large = self.flag()?;
+                let sweep = self.flag()?;
+                let to = self.point(origin)?;
+                // As SVG requires, an arc to its own start is omitted and one
+                // with a zero radius is a straight line.
+                if to != current {
+                    if radius.width == 0.0 || radius.height == 0.0 {
+                        self.path.line_to(to);
+                    } else {
+                        self.path.arc_to(ArcSegment {
+                            to,
+                            radius,
+                            rotation,
+                            sweep: if sweep {
+                                SweepDirection::Clockwise
+                            } else {
+                                SweepDirection::CounterClockwise
+                            },
+                            arc_size: if large {
+                                ArcSize::Large
+                            } else {
+                                ArcSize::Small
+                            },
+                        });
+                    }
+                }
+            }
+            b'Z' => {
+                self.path.close();
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.error("unknown command"));
+            }
+        }
+
+        self.last_ctrl = ctrl;
+        Ok(())
+    }
+
+    /// The first control point of a smooth curve: the reflection of the
+    /// previous curve's last control point if it was of the same kind, and
+    /// otherwise the current point.
+    fn reflected_ctrl(&self, current: Point2D<f32>, curve: Curve) -> Point2D<f32> {
+        match self.last_ctrl {
+            Some((ctrl, kind)) if kind == curve => Point2D {
+                x: 2.0 * current.x - ctrl.x,
+                y: 2.0 * current.y - ctrl.y,
+            },
+            _ => current,
+        }
+    }
+
+    /// Parses a coordinate pair, offset by `origin`.
+    fn point(&mut self, origin: Point2D<f32>) -> Result<Point2D<f32>, PathDataError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(Point2D {
+            x: origin.x + x,
+            y: origin.y + y,
+        })
+    }
+
+    /// Parses a number, e.g. `-1.5e3`, and any separators after it.
+    fn number(&mut self) -> Result<f32, PathDataError> {
+        let bytes = self.data.as_bytes();
+        let start = self.position;
+        let mut end = start;
+        let digits = |end: &mut usize| {
+            let from = *end;
+            while bytes.get(*end).is_some_and(u8::is_ascii_digit) {
+                *end += 1;
+            }
+            *end > from
+        };
+
+        if matches!(bytes.get(end), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let mut has_digits = digits(&mut end);
+        if bytes.get(end) == Some(&b'.') {
+            end += 1;
+            has_digits |= digits(&mut end);
+        }
+        if !has_digits {
+            return Err(self.error("expected a number"));
+        }
+        // An exponent, unless the "e" is not followed by one.
+        if matches!(bytes.get(end), Some(b'e' | b'E')) {
+            let mut exponent = end + 1;
+            if matches!(bytes.get(exponent), Some(b'+' | b'-')) {
+                exponent += 1;
+            }
+            if digits(&mut exponent) {
+                end = exponent;
+            }
+        }
+
+        let number = self.data[start..end]
+            .parse()
+            .map_err(|_| self.error("expected a number"))?;
+        self.position = end;
+        self.skip_separators();
+        Ok(number)
+    }
+
+    /// Parses an arc flag, which need not be separated from what follows it.
+    fn flag(&mut self) -> Result<bool, PathDataError> {
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error("expected a flag")),
+        };
+        self.position += 1;
+        self.skip_separators();
+        Ok(flag)
+    }
+
+    /// Skips whitespace and at most one comma.
+    fn skip_separators(&mut self) {
+        let mut comma = false;
+        while let Some(c) = self.peek() {
+            match c {
+                b' ' | b'\t' | b'\n' | b'\r' | b'\x0C' => {}
+                b',' if !comma => comma = true,
+                _ => break,
+            }
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.as_bytes().get(self.position).copied()
+    }
+
+    fn error(&self, reason: &'static str) -> PathDataError {
+        PathDataError {
+            position: self.position,
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::path::PathSegment;
+    use ::pretty_assertions::assert_eq;
+
+    fn p(x: f32, y: f32) -> Point2D<f32> {
+        Point2D { x, y }
+    }
+
+    fn segments(data: &str) -> Vec<PathSegment> {
+        parse_path_data(data).unwrap().segments().to_vec()
+    }
+
+    #[test]
+    fn test_lines_absolute_and_relative() {
+        assert_eq!(
+            segments("M10,10 l5 0 H20 v-5 V0 h-20 L0-5 20-10z m1 1"),
+            [
+                PathSegment::MoveTo(p(10.0, 10.0)),
+                PathSegment::LineTo(p(15.0, 10.0)),
+                PathSegment::LineTo(p(20.0, 10.0)),
+                PathSegment::LineTo(p(20.0, 5.0)),
+                PathSegment::LineTo(p(20.0, 0.0)),
+                PathSegment::LineTo(p(0.0, 0.0)),
+                PathSegment::LineTo(p(0.0, -5.0)),
+                PathSegment::LineTo(p(20.0, -10.0)),
+                PathSegment::Close,
+                // Relative to the start of the closed figure.
+                PathSegment::MoveTo(p(11.0, 11.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_moves_repeat_as_lines() {
+        assert_eq!(
+            segments("m1 1 2 2 3 3"),
+            [
+                PathSegment::MoveTo(p(1.0, 1.0)),
+                PathSegment::LineTo(p(3.0, 3.0)),
+                PathSegment::LineTo(p(6.0, 6.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_smooth_curves_reflect_control_points() {
+        assert_eq!(
+            segments("M0 0 C0 10 10 10 10 0 s10 -10 10 0 Q25 5 30 0 T40 0 S50 5 50 0"),
+            [
+                PathSegment::MoveTo(p(0.0, 0.0)),
+                PathSegment::CubicTo {
+                    ctrl1: p(0.0, 10.0),
+                    ctrl2: p(10.0, 10.0),
+                    to: p(10.0, 0.0)
+                },
+                PathSegment::CubicTo {
+                    ctrl1: p(10.0, -10.0),
+                    ctrl2: p(20.0, -10.0),
+                    to: p(20.0, 0.0)
+                },
+                PathSegment::QuadTo {
+                    ctrl: p(25.0, 5.0),
+                    to: p(30.0, 0.0)
+                },
+                PathSegment::QuadTo {
+                    ctrl: p(35.0, -5.0),
+                    to: p(40.0, 0.0)
+                },
+                // The previous curve was quadratic, so nothing is reflected.
+                PathSegment::CubicTo {
+                    ctrl1: p(40.0, 0.0),
+                    ctrl2: p(50.0, 5.0),
+                    to: p(50.0, 0.0)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arcs() {
+        assert_eq!(
+            segments("M0 0a5 5 30 1010 0 A0 5 0 0 0 20 0 a1 1 0 0 0 0 0"),
+            [
+                PathSegment::MoveTo(p(0.0, 0.0)),
+                PathSegment::ArcTo(ArcSegment {
+                    to: p(10.0, 0.0),
+                    radius: Size2D {
+                        width: 5.0,
+                        height: 5.0
+                    },
+                    rotation: 30_f32.to_radians(),
+                    sweep: SweepDirection::CounterClockwise,
+                    arc_size: ArcSize::Large,
+                }),
+                // A zero radius makes a line, and an arc to the current point
+                // is omitted.
+                PathSegment::LineTo(p(20.0, 0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_numbers() {
+        assert_eq!(
+            segments("M.5.5-1e1-1E-1"),
+            [
+                PathSegment::MoveTo(p(0.5, 0.5)),
+                PathSegment::LineTo(p(-10.0, -0.1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |data| parse_path_data(data).unwrap_err();
+
+        assert_eq!(
+            error("L1 1"),
+            PathDataError {
+                position: 0,
+                reason: "path data must begin with a move"
+            }
+        );
+        assert_eq!(error("M1 1 L2").reason, "expected a number");
+        assert_eq!(error("M1 1 X2").position, 5);
+        assert_eq!(error("M0 0 A1 1 0 2 0 1 1").reason, "expected a flag");
+        assert_eq!(error("M0 0 Z 1").reason, "expected a command");
+        assert!(parse_path_data("").unwrap().is_empty());
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_number_lists() {
+        assert_eq!(
+            parse_number_list(" 1,2 3-4.5.5 ").unwrap(),
+            [1.0, 2.0, 3.0, -4.5, 0.5]
+        );
+        assert_eq!(parse_number_list("1,,2").unwrap_err().position, 2);
+    }
+}
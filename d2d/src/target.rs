@@ -1,12 +1,41 @@
-use crate::{brushes::SolidColorBrush, color::Color, context::Context, factory::D2DFactory};
+use crate::{
+    bitmaps::{Bitmap, BitmapTarget},
+    brushes::{
+        BitmapBrush, BitmapBrushProperties, LinearGradientBrush, RadialGradientBrush,
+        SolidColorBrush,
+    },
+    cache::ResourceCache,
+    capture::{copy_frame, read_bitmap, Capture},
+    color::Color,
+    context::Context,
+    draw_list::{make_brush, BrushSpec},
+    errors::{Error, Result},
+    factory::D2DFactory,
+    gradient::Gradient,
+    imaging::Image,
+    options::RenderTargetOptions,
+    resources::DeviceResource,
+    snap::PixelSnap,
+    stats::{FrameRecorder, ResourceCounters},
+    stroke::{StrokeStyle, StrokeStyleProperties},
+};
 use ::std::rc::Rc;
-use ::win32::invoke::check_res;
-use ::win_geom::d2::Size2D;
+use ::win32::{invoke::check_res, window::DPI};
+use ::win_geom::d2::{Ellipse2D, Point2D, Size2D};
 use ::windows::{
     Foundation::Numerics::Matrix3x2,
     Win32::{
         Foundation::{D2DERR_RECREATE_TARGET, HWND},
-        Graphics::Direct2D::{ID2D1HwndRenderTarget, D2D1_BRUSH_PROPERTIES},
+        Graphics::{
+            Direct2D::{
+                Common::{D2D1_ALPHA_MODE_PREMULTIPLIED, D2D1_PIXEL_FORMAT},
+                ID2D1Bitmap, ID2D1Brush, ID2D1GradientStopCollection, ID2D1HwndRenderTarget,
+                ID2D1RenderTarget, D2D1_BITMAP_BRUSH_PROPERTIES, D2D1_BITMAP_PROPERTIES,
+                D2D1_BRUSH_PROPERTIES, D2D1_COMPATIBLE_RENDER_TARGET_OPTIONS_NONE,
+                D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES, D2D1_RADIAL_GRADIENT_BRUSH_PROPERTIES,
+            },
+            Dxgi::Common::DXGI_FORMAT_B8G8R8A8_UNORM,
+        },
     },
 };
 
@@ -37,46 +66,81 @@ use ::windows::{
 /// let factory = D2DFactory::new().unwrap();
 /// let mut render_target = factory.make_render_target(hwnd, size);
 ///
-/// let mut brush = render_target.make_solid_color_brush(Color::red());
+/// let mut brush = render_target.make_solid_color_brush(Color::red())?;
 /// let stroke_width = 1.0;
 ///
-/// let mut ctx = render_target.begin_draw();
+/// let mut ctx = render_target.begin_draw()?;
 /// ctx.clear(Color::blue());
 /// let rect = Rect2D::from_size_and_origin(
 ///     Size2D { width: 5.0, height: 5.0 },
 ///     Point2D { x: 10.0, y: 10.0 },
 /// );
-/// ctx.stroke_rect(rect, &mut brush, 1.0);
-/// ctx.end_draw();
+/// ctx.stroke_rect(rect, &mut brush, 1.0, None);
+/// ctx.end_draw()?;
+/// # Ok::<(), ::d2d::Error>(())
 /// ```
 ///
+/// # Errors
+///
+/// Drawing and resource creation report failures rather than panicking, so
+/// that a transient driver issue costs at most a frame. If the device is lost,
+/// the render target and its resources are re-created transparently on next
+/// use. If the render target is ever [poisoned](Error::Poisoned), it can be
+/// restored with [`recover`](Self::recover).
+///
 /// [`begin_draw`]: Self::begin_draw
 /// [`end_draw`]: Context::end_draw
 pub struct RenderTarget {
+    /// The components from which the device render target is (re-)created.
+    inner: Inner,
     /// State pattern object helps manage the two states we might find ourselves
     /// in:
     ///
     /// * Target created and device specific resources usable
     /// * Target requires re-creation due to hardware device loss or error.
-    state: State,
+    state: State<ID2D1HwndRenderTarget>,
     /// The generation of the [`RenderTarget`]. Used to stamp any newly created
     /// device resources with the generation of the render target that created
     /// them.
     generation: usize,
+    /// Simulated device losses, for testing recovery.
+    faults: FaultInjector,
+    /// Running totals of the device resources created.
+    counters: ResourceCounters,
+    /// Brushes, stroke styles and bitmaps drawn with by descriptor.
+    cache: ResourceCache,
+    /// A copy of the most recently presented frame on the device, and the
+    /// generation in which it was copied, for [`capture`](Self::capture).
+    last_frame: Option<(ID2D1Bitmap, usize)>,
+    /// Whether the next presented frame should be copied into `last_frame`.
+    capture_requested: bool,
 }
 
 impl RenderTarget {
     /// Crate-internal constructor, called by the [`Factory`](super::Factory).
-    pub(crate) fn new(factory: &Rc<D2DFactory>, hwnd: HWND, size: Size2D<i32>) -> Self {
+    pub(crate) fn new(
+        factory: &Rc<D2DFactory>,
+        hwnd: HWND,
+        size: Size2D<i32>,
+        options: RenderTargetOptions,
+    ) -> Self {
         Self {
-            state: State::RequiresRecreation {
-                inner: Inner {
-                    factory: factory.clone(),
-                    hwnd,
+            inner: Inner {
+                factory: factory.clone(),
+                hwnd,
+                viewport: Viewport {
                     size,
+                    dpi: DPI::detect(hwnd),
                 },
+                options,
             },
+            state: State::RequiresRecreation,
             generation: 0,
+            faults: FaultInjector::default(),
+            counters: ResourceCounters::default(),
+            cache: ResourceCache::default(),
+            last_frame: None,
+            capture_requested: false,
         }
     }
 
@@ -88,34 +152,275 @@ impl RenderTarget {
     /// called. Drawing can _only_ be achieved via a [Context]. A new [Context]
     /// should be created for each frame.
     ///
+    /// # Errors
+    ///
+    /// Fails if the device render target could not be (re-)created, if a
+    /// previous [Context] was never ended, or if the render target is
+    /// [poisoned](Error::Poisoned).
+    ///
     /// [`begin_draw`]: Self::begin_draw
     /// [`end_draw`]: Context::end_draw
-    pub fn begin_draw(&mut self) -> Context<'_> {
-        self.state.begin_draw();
-        let device_target = self.state.device_target();
+    pub fn begin_draw(&mut self) -> Result<Context<'_>> {
+        let frame = FrameRecorder::start(self.counters, "window");
+        let Self {
+            inner,
+            state,
+            counters,
+            ..
+        } = self;
+        let device_target = state.begin_draw(|| inner.create(&mut counters.targets_created))?;
+        let device_target = Rc::new(ID2D1RenderTarget::from(device_target.as_ref().clone()));
+
+        unsafe {
+            device_target.BeginDraw();
+        }
+
+        Ok(Context::new(device_target, self, Surface::Window, frame))
+    }
+
+    /// Make a new drawing [Context] for drawing into the offscreen `target`
+    /// rather than the window.
+    ///
+    /// The target is re-created first if the device was lost since it was
+    /// last drawn. Drawing is otherwise exactly as for [`begin_draw`], and
+    /// resources created by this render target may be used.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the target could not be re-created, or if this render target
+    /// is drawing or [poisoned](Error::Poisoned).
+    ///
+    /// [`begin_draw`]: Self::begin_draw
+    pub fn begin_draw_to<'t>(&'t mut self, target: &'t mut BitmapTarget) -> Result<Context<'t>> {
+        self.state.check_idle()?;
+        let frame = FrameRecorder::start(self.counters, "bitmap");
+        target.recreate_if_needed(self)?;
+        let device_target = Rc::new(target.device_target());
 
         unsafe {
             device_target.BeginDraw();
         }
 
-        Context::new(device_target, self)
+        Ok(Context::new(
+            device_target,
+            self,
+            Surface::Bitmap(target),
+            frame,
+        ))
     }
 
     /// Ends drawing operations on the render target causing the changes to
     /// become visible and the render target to become ready for the next
     /// [`Self::begin_draw`] call.
-    pub(crate) fn end_draw(&mut self, device_target: Rc<ID2D1HwndRenderTarget>) {
-        let must_recreate =
-            match check_res(|| unsafe { device_target.EndDraw(None, None) }, "EndDraw") {
-                Err(e) if e.code() == Some(D2DERR_RECREATE_TARGET) => true,
-                Err(e) => panic!("Unexpected error in Direct2D EndDraw(): {e}"),
-                Ok(_) => false,
-            };
+    pub(crate) fn end_draw(&mut self, device_target: Rc<ID2D1RenderTarget>) -> Result<()> {
+        if ::std::mem::take(&mut self.capture_requested) {
+            let previous = self.last_frame.take().map(|(frame, _)| frame);
+            match copy_frame(&device_target, previous) {
+                Ok(frame) => self.last_frame = Some((frame, self.generation)),
+                Err(e) => ::tracing::warn!(error = %e, "Failed to copy frame for capture"),
+            }
+        }
+
+        let injected = self.faults.next_frame();
+        if injected {
+            ::tracing::debug!("Simulating Direct2D device loss");
+        }
+        let flushed = flush(&device_target).map(|lost| lost || injected);
+        let must_recreate = matches!(flushed, Ok(true));
 
         if must_recreate {
             self.generation += 1;
         }
         self.state.end_draw(must_recreate);
+        flushed.map(|_| ())
+    }
+
+    /// Ends drawing operations on an offscreen target started by
+    /// [`Self::begin_draw_to`]. A device lost while drawing offscreen is lost
+    /// to this render target too, and so it must be re-created.
+    pub(crate) fn end_draw_to(
+        &mut self,
+        target: &mut BitmapTarget,
+        device_target: Rc<ID2D1RenderTarget>,
+    ) -> Result<()> {
+        if flush(&device_target)? {
+            self.generation += 1;
+            self.state.invalidate();
+        } else {
+            target.set_drawn();
+        }
+        Ok(())
+    }
+
+    /// The size of the render target in device-independent pixels (DIPs), in
+    /// which all drawing is expressed.
+    pub fn size(&self) -> Size2D<f32> {
+        self.inner.viewport.size.cast()
+    }
+
+    /// The size of the render target in device pixels, i.e. its
+    /// [`size`](Self::size) scaled by its [`dpi`](Self::dpi).
+    pub fn pixel_size(&self) -> Size2D<u32> {
+        self.inner.viewport.pixel_size()
+    }
+
+    /// The DPI of the render target, which maps device-independent pixels to
+    /// device pixels. This is initially the DPI of the window.
+    pub fn dpi(&self) -> DPI {
+        self.inner.viewport.dpi
+    }
+
+    /// Asks for a copy of the next frame drawn with
+    /// [`begin_draw`](Self::begin_draw) to be kept on the device as it is
+    /// presented, for [`capture`](Self::capture). Frames are otherwise not
+    /// copied, as that costs a copy of the whole window each frame.
+    pub fn request_capture(&mut self) {
+        self.capture_requested = true;
+    }
+
+    /// Copies the frame kept after the most recent
+    /// [`request_capture`](Self::request_capture) into an [`Image`], e.g. to
+    /// save as a PNG with [`Image::encode_png`]. The capture shows exactly
+    /// what was drawn to the window in that frame.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::NothingToCapture`] if no requested frame has been
+    /// presented since the render target was made or its device was lost, or
+    /// if the frame could not be read back from the device.
+    pub fn capture(&self) -> Result<Image> {
+        match (self.state.target(), &self.last_frame) {
+            (Some(target), Some((frame, generation))) if *generation == self.generation => {
+                read_bitmap(&ID2D1RenderTarget::from(target.as_ref().clone()), frame)
+            }
+            _ => Err(Error::NothingToCapture),
+        }
+    }
+
+    /// The options with which the render target was made.
+    pub fn options(&self) -> &RenderTargetOptions {
+        &self.inner.options
+    }
+
+    /// Snaps coordinates to the device pixels of the render target at its
+    /// current [`dpi`](Self::dpi), e.g. when recording drawing ahead of time.
+    /// Drawing with a [`Context`] can instead be snapped automatically with
+    /// [`Context::set_snap_mode`].
+    pub fn pixel_snap(&self) -> PixelSnap {
+        PixelSnap::new(self.dpi())
+    }
+
+    /// Resizes the render target to fill a window client area of `size`
+    /// device-independent pixels, e.g. after the window was resized.
+    ///
+    /// The device render target is resized in place, keeping its device
+    /// resources. If it has yet to be (re-)created, the new size is simply
+    /// used when it is.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the device render target could not be resized, in which case
+    /// it will instead be re-created at the new size on next use.
+    pub fn resize(&mut self, size: Size2D<i32>) -> Result<()> {
+        self.inner.viewport.size = size;
+        self.apply_size()
+    }
+
+    /// Changes the DPI of the render target, e.g. after the window moved to a
+    /// monitor with a different scale factor. The size in device-independent
+    /// pixels is unchanged, and so the size in device pixels is rescaled.
+    ///
+    /// As with [`resize`](Self::resize), the device render target is updated
+    /// in place if it exists. Offscreen [`BitmapTarget`]s keep the DPI they
+    /// were created with.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the device render target could not be resized, in which case
+    /// it will instead be re-created at the new DPI on next use.
+    pub fn set_dpi(&mut self, dpi: DPI) -> Result<()> {
+        self.inner.viewport.dpi = dpi;
+        if let Some(target) = self.state.target() {
+            unsafe {
+                target.SetDpi(dpi.into(), dpi.into());
+            }
+        }
+        self.apply_size()
+    }
+
+    /// Resizes any device render target to the current pixel size, falling
+    /// back to re-creating it if that fails.
+    fn apply_size(&mut self) -> Result<()> {
+        let pixel_size = self.inner.viewport.pixel_size().into();
+        let resized = self.state.resize(|target| {
+            Ok(check_res(
+                || unsafe { target.Resize(&pixel_size as _) },
+                "Resize",
+            )?)
+        });
+
+        if resized.is_err() {
+            self.generation += 1;
+        }
+        resized
+    }
+
+    /// Whether the render target was [poisoned](Error::Poisoned) and must be
+    /// restored with [`recover`](Self::recover) before it can be used again.
+    pub fn is_poisoned(&self) -> bool {
+        matches!(self.state, State::Poisoned)
+    }
+
+    /// Discards the device render target so that it is re-created on next use,
+    /// along with all device resources created from it.
+    ///
+    /// This restores a [poisoned](Error::Poisoned) render target, or one whose
+    /// previous [Context] was dropped without [`end_draw`] and so reports
+    /// [`Error::DrawInProgress`]. It may also be used to retry from scratch
+    /// after repeated failures.
+    ///
+    /// [`end_draw`]: Context::end_draw
+    pub fn recover(&mut self) {
+        ::tracing::warn!("Recovering Direct2D render target");
+        self.generation += 1;
+        self.state.reset();
+    }
+
+    /// Simulates the loss of the device at the end of future frames, as if
+    /// `EndDraw` had reported `D2DERR_RECREATE_TARGET`. The frame is still
+    /// presented, but the render target and all of its device resources are
+    /// then re-created exactly as after a real device loss.
+    ///
+    /// This is a testing and debugging aid for exercising an application's
+    /// recovery from device loss, which is otherwise rare and hard to provoke.
+    /// Only frames drawn with [`begin_draw`](Self::begin_draw) are counted.
+    pub fn set_device_loss(&mut self, loss: DeviceLoss) {
+        self.faults = FaultInjector::new(loss);
+    }
+
+    /// Counts of the device render targets and resources which have been
+    /// re-created, e.g. following device loss.
+    pub fn recreation_stats(&self) -> RecreationStats {
+        RecreationStats {
+            targets: self.counters.targets_created.saturating_sub(1),
+            resources: self.counters.resources_recreated,
+        }
+    }
+
+    /// Running totals of the device resources created, for frame statistics.
+    pub(crate) fn counters(&self) -> ResourceCounters {
+        self.counters
+    }
+
+    /// Records that a device resource was created.
+    pub(crate) fn count_created_resource(&mut self) {
+        self.counters.resources_created += 1;
+    }
+
+    /// Records that a device resource was re-created after its generation
+    /// expired.
+    pub(crate) fn count_recreated_resource(&mut self) {
+        self.counters.resources_recreated += 1;
     }
 
     /// The generation of the [`RenderTarget`]. Any device resources created
@@ -131,27 +436,398 @@ impl RenderTarget {
     /// As with all device-specific resources, the brush should be cached and
     /// re-used for subsequent drawing operations to avoid the overhead or
     /// repeatedly creating resources.
-    pub fn make_solid_color_brush(&mut self, color: Color) -> SolidColorBrush {
+    pub fn make_solid_color_brush(&mut self, color: Color) -> Result<SolidColorBrush> {
         let props = D2D1_BRUSH_PROPERTIES {
             opacity: 1.0,
             transform: Matrix3x2::identity(),
         };
+        let device_target = self.device_target()?;
         let device_brush = check_res(
             || unsafe {
-                self.state
-                    .device_target()
-                    .CreateSolidColorBrush(&color.into() as _, Some(&props as _))
+                device_target.CreateSolidColorBrush(&color.into() as _, Some(&props as _))
             },
             "CreateSolidColorBrush",
+        )?;
+
+        self.counters.resources_created += 1;
+        Ok(SolidColorBrush::new(color, device_brush, self.generation()))
+    }
+
+    /// Constructs a new linear gradient brush which blends the stops of
+    /// `gradient` along the axis from `start` to `end`.
+    ///
+    /// As with all device-specific resources, the brush should be cached and
+    /// re-used for subsequent drawing operations to avoid the overhead or
+    /// repeatedly creating resources.
+    pub fn make_linear_gradient_brush(
+        &mut self,
+        gradient: &Gradient,
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+    ) -> Result<LinearGradientBrush> {
+        let stops = self.make_gradient_stop_collection(gradient)?;
+        let gradient_props = D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES {
+            startPoint: start.into(),
+            endPoint: end.into(),
+        };
+        let props = D2D1_BRUSH_PROPERTIES {
+            opacity: 1.0,
+            transform: Matrix3x2::identity(),
+        };
+        let device_target = self.device_target()?;
+        let device_brush = check_res(
+            || unsafe {
+                device_target.CreateLinearGradientBrush(
+                    &gradient_props as _,
+                    Some(&props as _),
+                    &stops,
+                )
+            },
+            "CreateLinearGradientBrush",
+        )?;
+
+        self.counters.resources_created += 1;
+        Ok(LinearGradientBrush::new(
+            gradient.clone(),
+            start,
+            end,
+            device_brush,
+            self.generation(),
+        ))
+    }
+
+    /// Constructs a new radial gradient brush which blends the stops of
+    /// `gradient` outwards from the center of `ellipse` to its perimeter. The
+    /// gradient origin may be shifted away from the center by `origin_offset`.
+    ///
+    /// As with all device-specific resources, the brush should be cached and
+    /// re-used for subsequent drawing operations to avoid the overhead or
+    /// repeatedly creating resources.
+    pub fn make_radial_gradient_brush(
+        &mut self,
+        gradient: &Gradient,
+        ellipse: Ellipse2D<f32>,
+        origin_offset: Point2D<f32>,
+    ) -> Result<RadialGradientBrush> {
+        let stops = self.make_gradient_stop_collection(gradient)?;
+        let gradient_props = D2D1_RADIAL_GRADIENT_BRUSH_PROPERTIES {
+            center: ellipse.center.into(),
+            gradientOriginOffset: origin_offset.into(),
+            radiusX: ellipse.radius_x,
+            radiusY: ellipse.radius_y,
+        };
+        let props = D2D1_BRUSH_PROPERTIES {
+            opacity: 1.0,
+            transform: Matrix3x2::identity(),
+        };
+        let device_target = self.device_target()?;
+        let device_brush = check_res(
+            || unsafe {
+                device_target.CreateRadialGradientBrush(
+                    &gradient_props as _,
+                    Some(&props as _),
+                    &stops,
+                )
+            },
+            "CreateRadialGradientBrush",
+        )?;
+
+        self.counters.resources_created += 1;
+        Ok(RadialGradientBrush::new(
+            gradient.clone(),
+            ellipse,
+            origin_offset,
+            device_brush,
+            self.generation(),
+        ))
+    }
+
+    /// Uploads `image` to the device as a new bitmap.
+    ///
+    /// The bitmap is created at 96 DPI, so one image pixel maps to one device
+    /// independent pixel when drawn at its natural size.
+    ///
+    /// As with all device-specific resources, the bitmap should be cached and
+    /// re-used for subsequent drawing operations to avoid the overhead or
+    /// repeatedly creating resources.
+    pub fn make_bitmap(&mut self, image: impl Into<Rc<Image>>) -> Result<Bitmap> {
+        let image = image.into();
+        let props = D2D1_BITMAP_PROPERTIES {
+            pixelFormat: D2D1_PIXEL_FORMAT {
+                format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+            },
+            dpiX: 96.0,
+            dpiY: 96.0,
+        };
+        let device_target = self.device_target()?;
+        let device_bitmap = check_res(
+            || unsafe {
+                device_target.CreateBitmap(
+                    image.size().into(),
+                    Some(image.pixels().as_ptr() as _),
+                    image.stride(),
+                    &props as _,
+                )
+            },
+            "CreateBitmap",
+        )?;
+        self.counters.resources_created += 1;
+
+        Ok(Bitmap::new(image, device_bitmap, self.generation()))
+    }
+
+    /// Constructs a new offscreen [`BitmapTarget`] of `size` device independent
+    /// pixels, at the same DPI as this render target.
+    ///
+    /// As with all device-specific resources, the target should be cached and
+    /// re-used for subsequent drawing operations to avoid the overhead or
+    /// repeatedly creating resources.
+    pub fn make_bitmap_target(&mut self, size: Size2D<f32>) -> Result<BitmapTarget> {
+        let parent = self.device_target()?;
+        let device_target = check_res(
+            || unsafe {
+                parent.CreateCompatibleRenderTarget(
+                    Some(&size.into() as _),
+                    None,
+                    None,
+                    D2D1_COMPATIBLE_RENDER_TARGET_OPTIONS_NONE,
+                )
+            },
+            "CreateCompatibleRenderTarget",
+        )?;
+
+        self.counters.resources_created += 1;
+        Ok(BitmapTarget::new(size, device_target, self.generation()))
+    }
+
+    /// Constructs a new brush which paints with `bitmap`, tiled and positioned
+    /// according to `props`.
+    ///
+    /// As with all device-specific resources, the brush should be cached and
+    /// re-used for subsequent drawing operations to avoid the overhead or
+    /// repeatedly creating resources.
+    pub fn make_bitmap_brush(
+        &mut self,
+        bitmap: &mut Bitmap,
+        props: BitmapBrushProperties,
+    ) -> Result<BitmapBrush> {
+        bitmap.recreate_if_needed(self)?;
+
+        let bitmap_props = D2D1_BITMAP_BRUSH_PROPERTIES {
+            extendModeX: props.extend_mode_x.into(),
+            extendModeY: props.extend_mode_y.into(),
+            interpolationMode: props.interpolation.into(),
+        };
+        let brush_props = D2D1_BRUSH_PROPERTIES {
+            opacity: props.opacity,
+            transform: Matrix3x2::from(props.transform),
+        };
+        let device_target = self.device_target()?;
+        let device_brush = check_res(
+            || unsafe {
+                device_target.CreateBitmapBrush(
+                    bitmap.device_bitmap(),
+                    Some(&bitmap_props as _),
+                    Some(&brush_props as _),
+                )
+            },
+            "CreateBitmapBrush",
+        )?;
+
+        self.counters.resources_created += 1;
+        Ok(BitmapBrush::new(
+            bitmap.clone(),
+            props,
+            device_brush,
+            self.generation(),
+        ))
+    }
+
+    /// The cache of resources drawn with by descriptor, such as the brushes of
+    /// plain [`Color`]s.
+    pub fn cache(&self) -> &ResourceCache {
+        &self.cache
+    }
+
+    /// The cache of resources drawn with by descriptor, e.g. to change its
+    /// capacity.
+    pub fn cache_mut(&mut self) -> &mut ResourceCache {
+        &mut self.cache
+    }
+
+    /// The device brush described by `spec`, fetched from the cache.
+    pub(crate) fn cached_brush(&mut self, spec: &BrushSpec) -> Result<ID2D1Brush> {
+        // The entry is taken out of the cache while it is (re-)created with us.
+        let (spec, mut brush) = match self.cache.take_brush(spec) {
+            Some(entry) => entry,
+            None => (spec.clone(), make_brush(self, spec)?),
+        };
+        let recreated = self.recreate_cached(&mut *brush);
+        let device_brush = brush.device_brush().clone();
+        self.cache.insert_brush(spec, brush);
+
+        recreated.map(|()| device_brush)
+    }
+
+    /// The device bitmap of `image`, fetched from the cache.
+    pub(crate) fn cached_bitmap(&mut self, image: &Rc<Image>) -> Result<ID2D1Bitmap> {
+        let (image, mut bitmap) = match self.cache.take_bitmap(image) {
+            Some(entry) => entry,
+            None => (image.clone(), self.make_bitmap(image.clone())?),
+        };
+        let recreated = self.recreate_cached(&mut bitmap);
+        let device_bitmap = bitmap.device_bitmap().clone();
+        self.cache.insert_bitmap(image, bitmap);
+
+        recreated.map(|()| device_bitmap)
+    }
+
+    /// Re-creates a cached `resource` if the device was lost since it was
+    /// created, counting the re-creation.
+    fn recreate_cached<R: DeviceResource + ?Sized>(&mut self, resource: &mut R) -> Result<()> {
+        if resource.generation() != self.generation {
+            resource.recreate_if_needed(self)?;
+            self.cache.count_recreation();
+        }
+        Ok(())
+    }
+
+    /// The stroke style described by `props`, fetched from the cache.
+    pub(crate) fn cached_stroke_style(
+        &mut self,
+        props: &StrokeStyleProperties,
+    ) -> Result<StrokeStyle> {
+        self.cache.stroke_style(&self.inner.factory, props)
+    }
+
+    /// The factory which created this render target, and from which
+    /// factory-level resources such as stroke styles and geometries usable
+    /// with it must be created.
+    pub(crate) fn factory(&self) -> Rc<D2DFactory> {
+        self.inner.factory.clone()
+    }
+
+    /// Constructs the device-specific gradient stop collection shared by all
+    /// gradient brushes.
+    fn make_gradient_stop_collection(
+        &mut self,
+        gradient: &Gradient,
+    ) -> Result<ID2D1GradientStopCollection> {
+        let stops = gradient.device_stops();
+        let device_target = self.device_target()?;
+        check_res(
+            || unsafe {
+                device_target.CreateGradientStopCollection(
+                    &stops,
+                    gradient.gamma().into(),
+                    gradient.extend_mode().into(),
+                )
+            },
+            "CreateGradientStopCollection",
         )
-        .expect("failed to create solid color brush");
+        .map_err(Into::into)
+    }
+
+    /// The device render target, (re-)created if needed.
+    fn device_target(&mut self) -> Result<Rc<ID2D1HwndRenderTarget>> {
+        let Self {
+            inner,
+            state,
+            counters,
+            ..
+        } = self;
+        state.device_target(|| inner.create(&mut counters.targets_created))
+    }
+}
+
+/// Flushes the drawing commands batched on `device_target` since `BeginDraw`,
+/// returning whether the device was lost and must be re-created.
+fn flush(device_target: &ID2D1RenderTarget) -> Result<bool> {
+    let (mut tag1, mut tag2) = (0, 0);
+    match check_res(
+        || unsafe { device_target.EndDraw(Some(&mut tag1), Some(&mut tag2)) },
+        "EndDraw",
+    ) {
+        Err(e) if e.code() == Some(D2DERR_RECREATE_TARGET) => Ok(true),
+        Err(source) => Err(Error::Draw { tag1, tag2, source }),
+        Ok(_) => Ok(false),
+    }
+}
 
-        SolidColorBrush::new(color, device_brush, self.generation())
+impl Capture for RenderTarget {
+    type Error = Error;
+
+    fn capture(&self) -> Result<Image> {
+        RenderTarget::capture(self)
     }
 }
 
-/// Inner components which are common to all states of our state pattern render
-/// target.
+/// The surface into which a [`Context`] draws.
+pub(crate) enum Surface<'t> {
+    /// The window of the [`RenderTarget`], presented when drawing ends.
+    Window,
+    /// An offscreen target sharing the device of the [`RenderTarget`].
+    Bitmap(&'t mut BitmapTarget),
+}
+
+/// When a [`RenderTarget`] should simulate the loss of its device. See
+/// [`RenderTarget::set_device_loss`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DeviceLoss {
+    /// Never simulate device loss.
+    #[default]
+    Never,
+    /// Simulate device loss at the end of the next frame only.
+    NextFrame,
+    /// Simulate device loss at the end of every nth frame, counting from when
+    /// this was set. An interval of zero never loses the device.
+    EveryNthFrame(u32),
+}
+
+/// Counts of the device render targets and resources re-created by a
+/// [`RenderTarget`], as returned by [`RenderTarget::recreation_stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct RecreationStats {
+    /// Device render targets created after the first, following device loss
+    /// or [`RenderTarget::recover`].
+    pub targets: usize,
+    /// Brushes, bitmaps and other device resources re-created because the
+    /// render target was re-created since they were.
+    pub resources: usize,
+}
+
+/// Decides which frames simulate device loss.
+#[derive(Debug, Default)]
+struct FaultInjector {
+    loss: DeviceLoss,
+    /// Frames ended since `loss` was set.
+    frames: u32,
+}
+
+impl FaultInjector {
+    fn new(loss: DeviceLoss) -> Self {
+        Self { loss, frames: 0 }
+    }
+
+    /// Advances to the end of the next frame, returning whether it should
+    /// simulate device loss.
+    fn next_frame(&mut self) -> bool {
+        self.frames = self.frames.wrapping_add(1);
+        match self.loss {
+            DeviceLoss::Never => false,
+            DeviceLoss::NextFrame => {
+                self.loss = DeviceLoss::Never;
+                true
+            }
+            DeviceLoss::EveryNthFrame(0) => false,
+            DeviceLoss::EveryNthFrame(n) => self.frames.is_multiple_of(n),
+        }
+    }
+}
+
+/// Inner components from which the device render target is (re-)created,
+/// common to all states of our state pattern render target.
 struct Inner {
     /// The factory which created this [`RenderTarget`]. A reference is kept
     /// so that the [`RenderTarget`] can be automatically re-created from
@@ -165,95 +841,400 @@ struct Inner {
     // together safely.
     hwnd: HWND,
 
-    /// Size of both the window and the render target.
+    /// The size and DPI of the render target.
+    viewport: Viewport,
+
+    /// The options with which the device render target is created.
+    options: RenderTargetOptions,
+}
+
+impl Inner {
+    /// (Re-)creates the device render target, counting it in `created`.
+    fn create(&self, created: &mut usize) -> Result<ID2D1HwndRenderTarget> {
+        let target = self.factory.make_device_render_target(
+            self.hwnd,
+            self.viewport.pixel_size(),
+            self.viewport.dpi,
+            &self.options,
+        )?;
+        *created += 1;
+        Ok(target)
+    }
+}
+
+/// The size and DPI at which the device render target is (re-)created.
+#[derive(Clone, Copy, Debug)]
+struct Viewport {
+    /// Size of both the window and the render target, in device-independent
+    /// pixels.
     size: Size2D<i32>,
+    /// DPI of the render target, initially that of the window.
+    dpi: DPI,
+}
+
+impl Viewport {
+    /// The size of the render target in device pixels.
+    fn pixel_size(&self) -> Size2D<u32> {
+        self.dpi.scale_size(self.size).cast()
+    }
 }
 
 /// The internal state of our render target, encapsulated as a state pattern.
-enum State {
+///
+/// Generic over the device render target `T` so that the transitions can be
+/// tested without a device.
+enum State<T> {
     /// Device-specific resources have been recreated and are usable.
-    Created {
-        inner: Inner,
-        // TODO: abstract HWND or DXGISurfaceTarget behind common trait
-        target: Rc<ID2D1HwndRenderTarget>,
-    },
+    Created { target: Rc<T> },
     /// The target is currently in a `BeginDraw` call.
-    Drawing {
-        inner: Inner,
-        target: Rc<ID2D1HwndRenderTarget>,
-    },
+    Drawing { target: Rc<T> },
     /// Device-specific resources require (re-)creation. This is true for the
     /// first interaction and following any `D2DERR_RECREATE_TARGET` errors
     /// received due to device errors.
-    RequiresRecreation { inner: Inner },
-    /// Poisoned state. An error occurred mid-transition and this type is no
-    /// longer usable.
+    RequiresRecreation,
+    /// Poisoned state. A panic occurred mid-transition and this type is no
+    /// longer usable until [`RenderTarget::recover`] is called.
     Poisoned,
 }
 
-impl State {
-    fn device_target(&mut self) -> Rc<ID2D1HwndRenderTarget> {
-        *self = ::std::mem::replace(self, State::Poisoned).recreate_if_needed();
+impl<T> State<T> {
+    /// Returns the device render target, (re-)creating it with `create` if
+    /// needed.
+    fn device_target(&mut self, create: impl FnOnce() -> Result<T>) -> Result<Rc<T>> {
+        self.recreate_if_needed(create)?;
         match self {
-            Self::Poisoned => panic!("Render target state poisoned"),
-            Self::Created { target, .. } | Self::Drawing { target, .. } => target.clone(),
-            _ => unreachable!("Render target guaranteed to be created"),
+            Self::Created { target } | Self::Drawing { target } => Ok(target.clone()),
+            Self::Poisoned => Err(Error::Poisoned),
+            Self::RequiresRecreation => unreachable!("Render target guaranteed to be created"),
         }
     }
 
-    /// Re-creates the render target if needed. Always returns a valid render
-    /// target.
-    fn recreate_if_needed(self) -> Self {
+    /// Re-creates the render target with `create` if needed. If re-creation
+    /// fails, the state is left as [`Self::RequiresRecreation`] so that it may
+    /// be retried.
+    fn recreate_if_needed(&mut self, create: impl FnOnce() -> Result<T>) -> Result<()> {
         match self {
-            Self::Poisoned => panic!("Render target state poisoned"),
-            Self::RequiresRecreation { inner } => {
-                let target = inner
-                    .factory
-                    .make_device_render_target(inner.hwnd, inner.size)
-                    .expect("Failed to create device resources for Direct2D render target");
-
-                Self::Created {
-                    inner,
-                    target: Rc::new(target),
-                }
+            Self::Poisoned => Err(Error::Poisoned),
+            Self::RequiresRecreation => {
+                *self = Self::Created {
+                    target: Rc::new(create()?),
+                };
+                Ok(())
             }
-            _ => self,
+            Self::Created { .. } | Self::Drawing { .. } => Ok(()),
         }
     }
 
-    /// Transitions to the drawing state and returns (or recreates) the device
-    /// render target.
-    ///
-    /// # Panics
-    ///
-    /// Panics if called while already in the [`Self::Drawing`] state or if
-    /// the state was [`Self::Poisoned`].
-    fn begin_draw(&mut self) {
-        *self = match ::std::mem::replace(self, State::Poisoned).recreate_if_needed() {
-            Self::Poisoned => panic!("Render target state poisoned"),
-            Self::Drawing { .. } => panic!("Render target should not be re-created mid-draw"),
-            Self::Created { target, inner } => Self::Drawing { inner, target },
-            _ => unreachable!("Render target guaranteed to be created"),
+    /// The device render target, if it has been created.
+    fn target(&self) -> Option<&Rc<T>> {
+        match self {
+            Self::Created { target } | Self::Drawing { target } => Some(target),
+            Self::RequiresRecreation | Self::Poisoned => None,
+        }
+    }
+
+    /// Fails unless the state is usable and not already drawing.
+    fn check_idle(&self) -> Result<()> {
+        match self {
+            Self::Poisoned => Err(Error::Poisoned),
+            Self::Drawing { .. } => Err(Error::DrawInProgress),
+            Self::Created { .. } | Self::RequiresRecreation => Ok(()),
         }
     }
 
-    /// Ends a drawing cycle, transitioning from either [`Self::Drawing`] to
-    /// [`Self::RequiresRecreation`] depending on the value of `must_recreate`.
+    /// Transitions to the drawing state and returns (or recreates with
+    /// `create`) the device render target.
+    fn begin_draw(&mut self, create: impl FnOnce() -> Result<T>) -> Result<Rc<T>> {
+        self.check_idle()?;
+        let target = self.device_target(create)?;
+        *self = Self::Drawing {
+            target: target.clone(),
+        };
+        Ok(target)
+    }
+
+    /// Ends a drawing cycle, transitioning from [`Self::Drawing`] to either
+    /// [`Self::Created`] or [`Self::RequiresRecreation`] depending on the
+    /// value of `must_recreate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, poisoning the state, if not in the [`Self::Drawing`] state,
+    /// which a [`Context`] guarantees.
     fn end_draw(&mut self, must_recreate: bool) {
-        *self = match ::std::mem::replace(self, State::Poisoned).recreate_if_needed() {
-            Self::Poisoned => panic!("Render target state poisoned"),
-            Self::Created { .. } => {
-                panic!("Render target cannot transition from Drawing to Created")
-            }
-            Self::Drawing { inner, target } => {
+        *self = match ::std::mem::replace(self, State::Poisoned) {
+            Self::Drawing { target } => {
                 if must_recreate {
                     ::tracing::warn!("Direct2D requires device resource re-creation");
-                    Self::RequiresRecreation { inner }
+                    Self::RequiresRecreation
                 } else {
-                    Self::Created { inner, target }
+                    Self::Created { target }
                 }
             }
-            _ => unreachable!("Render target guaranteed to be created"),
+            _ => panic!("Render target can only end drawing from the Drawing state"),
         }
     }
+
+    /// Resizes the device render target with `resize` if it has been created.
+    /// Otherwise the new size is left to be used when the target is created.
+    /// If resizing fails, the target is discarded to be re-created instead.
+    fn resize(&mut self, resize: impl FnOnce(&T) -> Result<()>) -> Result<()> {
+        let resized = match self.target() {
+            Some(target) => resize(target),
+            None => return Ok(()),
+        };
+        if resized.is_err() {
+            self.invalidate();
+        }
+        resized
+    }
+
+    /// Transitions to [`Self::RequiresRecreation`] after the device was lost
+    /// while drawing outside of a [`Self::Drawing`] cycle, e.g. offscreen.
+    fn invalidate(&mut self) {
+        ::tracing::warn!("Direct2D requires device resource re-creation");
+        *self = Self::RequiresRecreation;
+    }
+
+    /// Discards the device render target from any state, including
+    /// [`Self::Poisoned`], so that it is re-created on next use.
+    fn reset(&mut self) {
+        *self = Self::RequiresRecreation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+    use ::std::panic::{catch_unwind, AssertUnwindSafe};
+
+    /// A stand-in for a device render target, numbered by creation order.
+    #[derive(Debug, PartialEq)]
+    struct FakeTarget(usize);
+
+    /// Creates fake targets, counting how many have been created.
+    fn creator(count: &mut usize) -> impl FnOnce() -> Result<FakeTarget> + '_ {
+        move || {
+            *count += 1;
+            Ok(FakeTarget(*count))
+        }
+    }
+
+    /// Unwraps `result` without formatting any error, which needs Windows.
+    fn ok<T>(result: Result<T>) -> T {
+        match result {
+            Ok(value) => value,
+            Err(_) => panic!("unexpected error"),
+        }
+    }
+
+    fn failure() -> Result<FakeTarget> {
+        Err(::win32::errors::Error::Unexpected {
+            function: "CreateHwndRenderTarget",
+            context: ::windows::core::Error::OK,
+        }
+        .into())
+    }
+
+    #[test]
+    fn test_targets_are_created_lazily_and_reused() {
+        let mut count = 0;
+        let mut state = State::RequiresRecreation;
+        assert!(state.target().is_none());
+
+        let first = ok(state.device_target(creator(&mut count)));
+        let second = ok(state.device_target(creator(&mut count)));
+
+        assert_eq!(count, 1);
+        assert!(Rc::ptr_eq(&first, &second));
+        assert!(matches!(state, State::Created { .. }));
+        assert!(state
+            .target()
+            .is_some_and(|target| Rc::ptr_eq(target, &first)));
+    }
+
+    #[test]
+    fn test_drawing_cycles_keep_the_target() {
+        let mut count = 0;
+        let mut state = State::RequiresRecreation;
+
+        ok(state.begin_draw(creator(&mut count)));
+        assert!(matches!(state, State::Drawing { .. }));
+        state.end_draw(false);
+        assert!(matches!(state, State::Created { .. }));
+        ok(state.begin_draw(creator(&mut count)));
+        state.end_draw(false);
+
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_device_loss_recreates_the_target_on_next_use() {
+        let mut count = 0;
+        let mut state = State::RequiresRecreation;
+
+        ok(state.begin_draw(creator(&mut count)));
+        state.end_draw(true);
+        assert!(matches!(state, State::RequiresRecreation));
+
+        let target = ok(state.begin_draw(creator(&mut count)));
+        assert_eq!(*target, FakeTarget(2));
+
+        state.end_draw(false);
+        state.invalidate();
+        assert!(matches!(state, State::RequiresRecreation));
+    }
+
+    #[test]
+    fn test_failed_creation_can_be_retried() {
+        let mut count = 0;
+        let mut state = State::RequiresRecreation;
+
+        assert!(matches!(state.begin_draw(failure), Err(Error::Win32(_))));
+        assert!(matches!(state, State::RequiresRecreation));
+
+        ok(state.begin_draw(creator(&mut count)));
+        assert!(matches!(state, State::Drawing { .. }));
+    }
+
+    #[test]
+    fn test_drawing_cannot_begin_twice() {
+        let mut count = 0;
+        let mut state = State::RequiresRecreation;
+
+        ok(state.begin_draw(creator(&mut count)));
+
+        assert!(matches!(
+            state.begin_draw(creator(&mut count)),
+            Err(Error::DrawInProgress)
+        ));
+        assert!(matches!(state.check_idle(), Err(Error::DrawInProgress)));
+        // Resources may still be created mid-draw.
+        assert!(state.device_target(creator(&mut count)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_transitions_poison_until_reset() {
+        let mut count = 0;
+        let mut state = State::RequiresRecreation;
+        ok(state.device_target(creator(&mut count)));
+
+        let ended = catch_unwind(AssertUnwindSafe(|| state.end_draw(false)));
+        assert!(ended.is_err());
+        assert!(matches!(state, State::Poisoned));
+        assert!(matches!(
+            state.begin_draw(creator(&mut count)),
+            Err(Error::Poisoned)
+        ));
+        assert!(matches!(
+            state.device_target(creator(&mut count)),
+            Err(Error::Poisoned)
+        ));
+
+        state.reset();
+        let target = ok(state.begin_draw(creator(&mut count)));
+        assert_eq!(*target, FakeTarget(2));
+    }
+
+    fn viewport(width: i32, height: i32, dpi: f32) -> Viewport {
+        Viewport {
+            size: Size2D { width, height },
+            dpi: DPI::from(dpi),
+        }
+    }
+
+    #[test]
+    fn test_pixel_size_is_scaled_by_dpi() {
+        let size = |width, height| Size2D { width, height };
+
+        assert_eq!(viewport(100, 50, 96.0).pixel_size(), size(100, 50));
+        assert_eq!(viewport(100, 50, 144.0).pixel_size(), size(150, 75));
+        // Partially covered device pixels are included.
+        assert_eq!(viewport(3, 1, 120.0).pixel_size(), size(4, 2));
+        assert_eq!(f32::from(viewport(3, 1, 120.0).dpi), 120.0);
+    }
+
+    #[test]
+    fn test_resizes_are_deferred_until_the_target_is_created() {
+        let mut viewport = viewport(100, 50, 96.0);
+        let mut state = State::RequiresRecreation;
+        let mut resized = false;
+
+        viewport.size = Size2D {
+            width: 200,
+            height: 100,
+        };
+        ok(state.resize(|_| {
+            resized = true;
+            Ok(())
+        }));
+        viewport.dpi = DPI::from(192.0);
+        ok(state.resize(|_| {
+            resized = true;
+            Ok(())
+        }));
+        assert!(!resized);
+
+        let target = ok(state.begin_draw(|| Ok(viewport.pixel_size())));
+        assert_eq!(
+            *target,
+            Size2D {
+                width: 400,
+                height: 200
+            }
+        );
+    }
+
+    #[test]
+    fn test_resizes_keep_a_created_target() {
+        let mut count = 0;
+        let mut state = State::RequiresRecreation;
+        let first = ok(state.device_target(creator(&mut count)));
+
+        let mut resized = None;
+        ok(state.resize(|target| {
+            resized = Some(target.0);
+            Ok(())
+        }));
+
+        assert_eq!(resized, Some(1));
+        assert!(state
+            .target()
+            .is_some_and(|target| Rc::ptr_eq(target, &first)));
+    }
+
+    #[test]
+    fn test_failed_resizes_recreate_the_target() {
+        let mut count = 0;
+        let mut state = State::RequiresRecreation;
+        ok(state.device_target(creator(&mut count)));
+
+        assert!(state.resize(|_| failure().map(|_| ())).is_err());
+        assert!(matches!(state, State::RequiresRecreation));
+
+        let target = ok(state.begin_draw(creator(&mut count)));
+        assert_eq!(*target, FakeTarget(2));
+    }
+
+    #[test]
+    fn test_fault_injection_loses_the_next_frame_once() {
+        let mut faults = FaultInjector::new(DeviceLoss::NextFrame);
+
+        let frames: Vec<_> = (0..3).map(|_| faults.next_frame()).collect();
+
+        assert_eq!(frames, [true, false, false]);
+    }
+
+    #[test]
+    fn test_fault_injection_loses_every_nth_frame() {
+        let mut faults = FaultInjector::new(DeviceLoss::EveryNthFrame(3));
+        let frames: Vec<_> = (0..7).map(|_| faults.next_frame()).collect();
+        assert_eq!(frames, [false, false, true, false, false, true, false]);
+
+        let mut never = FaultInjector::new(DeviceLoss::EveryNthFrame(0));
+        assert!(!(0..5).any(|_| never.next_frame()));
+        assert!(!FaultInjector::default().next_frame());
+    }
 }
@@ -0,0 +1,246 @@
+//! Text formats, which describe the font and paragraph layout of text.
+
+use ::windows::Win32::Graphics::DirectWrite::{
+    IDWriteTextFormat, DWRITE_FONT_STYLE, DWRITE_FONT_STYLE_ITALIC, DWRITE_FONT_STYLE_NORMAL,
+    DWRITE_FONT_STYLE_OBLIQUE, DWRITE_FONT_WEIGHT, DWRITE_PARAGRAPH_ALIGNMENT,
+    DWRITE_PARAGRAPH_ALIGNMENT_CENTER, DWRITE_PARAGRAPH_ALIGNMENT_FAR,
+    DWRITE_PARAGRAPH_ALIGNMENT_NEAR, DWRITE_TEXT_ALIGNMENT, DWRITE_TEXT_ALIGNMENT_CENTER,
+    DWRITE_TEXT_ALIGNMENT_JUSTIFIED, DWRITE_TEXT_ALIGNMENT_LEADING, DWRITE_TEXT_ALIGNMENT_TRAILING,
+    DWRITE_WORD_WRAPPING, DWRITE_WORD_WRAPPING_CHARACTER, DWRITE_WORD_WRAPPING_EMERGENCY_BREAK,
+    DWRITE_WORD_WRAPPING_NO_WRAP, DWRITE_WORD_WRAPPING_WHOLE_WORD, DWRITE_WORD_WRAPPING_WRAP,
+};
+
+/// The thickness of a font's strokes, from 1 (thinnest) to 999 (thickest).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct FontWeight(pub u16);
+
+impl FontWeight {
+    /// Thin (100).
+    pub const THIN: Self = Self(100);
+    /// Extra light (200).
+    pub const EXTRA_LIGHT: Self = Self(200);
+    /// Light (300).
+    pub const LIGHT: Self = Self(300);
+    /// Normal, or regular (400).
+    pub const NORMAL: Self = Self(400);
+    /// Medium (500).
+    pub const MEDIUM: Self = Self(500);
+    /// Semi-bold (600).
+    pub const SEMI_BOLD: Self = Self(600);
+    /// Bold (700).
+    pub const BOLD: Self = Self(700);
+    /// Extra bold (800).
+    pub const EXTRA_BOLD: Self = Self(800);
+    /// Black, or heavy (900).
+    pub const BLACK: Self = Self(900);
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+impl From<FontWeight> for DWRITE_FONT_WEIGHT {
+    fn from(weight: FontWeight) -> Self {
+        DWRITE_FONT_WEIGHT(weight.0.into())
+    }
+}
+
+/// The slant of a font.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum FontStyle {
+    /// Upright characters.
+    #[default]
+    Normal,
+    /// Characters slanted artificially from the upright face.
+    Oblique,
+    /// Characters of the font's italic face.
+    Italic,
+}
+
+impl From<FontStyle> for DWRITE_FONT_STYLE {
+    fn from(style: FontStyle) -> Self {
+        match style {
+            FontStyle::Normal => DWRITE_FONT_STYLE_NORMAL,
+            FontStyle::Oblique => DWRITE_FONT_STYLE_OBLIQUE,
+            FontStyle::Italic => DWRITE_FONT_STYLE_ITALIC,
+        }
+    }
+}
+
+/// The horizontal alignment of each line of text within the layout box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum TextAlignment {
+    /// Aligned to the leading edge, which is the left for left-to-right text.
+    #[default]
+    Leading,
+    /// Aligned to the trailing edge, which is the right for left-to-right text.
+    Trailing,
+    /// Centered.
+    Center,
+    /// Stretched to fill the width of the layout box, except for the last line
+    /// of each paragraph.
+    Justified,
+}
+
+impl From<TextAlignment> for DWRITE_TEXT_ALIGNMENT {
+    fn from(alignment: TextAlignment) -> Self {
+        match alignment {
+            TextAlignment::Leading => DWRITE_TEXT_ALIGNMENT_LEADING,
+            TextAlignment::Trailing => DWRITE_TEXT_ALIGNMENT_TRAILING,
+            TextAlignment::Center => DWRITE_TEXT_ALIGNMENT_CENTER,
+            TextAlignment::Justified => DWRITE_TEXT_ALIGNMENT_JUSTIFIED,
+        }
+    }
+}
+
+/// The vertical alignment of the text within the layout box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum ParagraphAlignment {
+    /// Aligned to the top of the layout box.
+    #[default]
+    Near,
+    /// Aligned to the bottom of the layout box.
+    Far,
+    /// Centered vertically.
+    Center,
+}
+
+impl From<ParagraphAlignment> for DWRITE_PARAGRAPH_ALIGNMENT {
+    fn from(alignment: ParagraphAlignment) -> Self {
+        match alignment {
+            ParagraphAlignment::Near => DWRITE_PARAGRAPH_ALIGNMENT_NEAR,
+            ParagraphAlignment::Far => DWRITE_PARAGRAPH_ALIGNMENT_FAR,
+            ParagraphAlignment::Center => DWRITE_PARAGRAPH_ALIGNMENT_CENTER,
+        }
+    }
+}
+
+/// How text is broken into lines which overflow the layout box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum WordWrapping {
+    /// Lines are broken between words, and words too long for a line are
+    /// broken between characters.
+    #[default]
+    Wrap,
+    /// Lines are only broken at explicit line breaks, even if they overflow.
+    NoWrap,
+    /// Lines are broken between words, and words too long for a line are left
+    /// to overflow.
+    WholeWord,
+    /// Lines are broken between any two characters.
+    Character,
+    /// As [`Wrap`](Self::Wrap), but words too long for a line are only broken
+    /// where there is no other option.
+    EmergencyBreak,
+}
+
+impl From<WordWrapping> for DWRITE_WORD_WRAPPING {
+    fn from(wrapping: WordWrapping) -> Self {
+        match wrapping {
+            WordWrapping::Wrap => DWRITE_WORD_WRAPPING_WRAP,
+            WordWrapping::NoWrap => DWRITE_WORD_WRAPPING_NO_WRAP,
+            WordWrapping::WholeWord => DWRITE_WORD_WRAPPING_WHOLE_WORD,
+            WordWrapping::Character => DWRITE_WORD_WRAPPING_CHARACTER,
+            WordWrapping::EmergencyBreak => DWRITE_WORD_WRAPPING_EMERGENCY_BREAK,
+        }
+    }
+}
+
+/// The full description of a [`TextFormat`].
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{FontWeight, TextAlignment, TextFormatProperties};
+///
+/// // A centered, bold heading.
+/// let props = TextFormatProperties {
+///     font_size: 24.0,
+///     weight: FontWeight::BOLD,
+///     alignment: TextAlignment::Center,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct TextFormatProperties {
+    /// The name of the font family, e.g. `"Segoe UI"`.
+    pub font_family: String,
+    /// The size of the font in device-independent pixels. At 96 DPI a 12pt
+    /// font is 16 pixels.
+    pub font_size: f32,
+    /// The thickness of the font's strokes.
+    pub weight: FontWeight,
+    /// The slant of the font.
+    pub style: FontStyle,
+    /// The horizontal alignment of each line.
+    pub alignment: TextAlignment,
+    /// The vertical alignment of the text.
+    pub paragraph_alignment: ParagraphAlignment,
+    /// How lines which overflow the layout box are broken.
+    pub wrapping: WordWrapping,
+    /// The locale used to shape and break the text, e.g. `"en-us"`.
+    pub locale: String,
+}
+
+impl Default for TextFormatProperties {
+    fn default() -> Self {
+        Self {
+            font_family: "Segoe UI".to_owned(),
+            font_size: 14.0,
+            weight: FontWeight::default(),
+            style: FontStyle::default(),
+            alignment: TextAlignment::default(),
+            paragraph_alignment: ParagraphAlignment::default(),
+            wrapping: WordWrapping::default(),
+            locale: "en-us".to_owned(),
+        }
+    }
+}
+
+/// Describes the font and paragraph layout with which text is drawn by
+/// [`Context::draw_text`](crate::Context::draw_text), or from which a
+/// [`TextLayout`](crate::TextLayout) is made.
+///
+/// Text formats are factory-level resources made by a
+/// [`DWriteFactory`](crate::DWriteFactory). They are immutable, cheap to clone
+/// and never need to be re-created after device loss.
+#[derive(Clone, Debug)]
+pub struct TextFormat {
+    /// The properties with which the format was created.
+    props: TextFormatProperties,
+    /// The underlying DirectWrite text format.
+    inner: IDWriteTextFormat,
+}
+
+impl TextFormat {
+    /// A crate-private constructor. Only a
+    /// [`DWriteFactory`](crate::DWriteFactory) should be able to create
+    /// text formats.
+    pub(crate) fn new(props: TextFormatProperties, inner: IDWriteTextFormat) -> Self {
+        Self { props, inner }
+    }
+
+    /// The properties with which the format was created.
+    pub fn properties(&self) -> &TextFormatProperties {
+        &self.props
+    }
+
+    /// Accesses the underlying DirectWrite text format.
+    pub(crate) fn device_format(&self) -> &IDWriteTextFormat {
+        &self.inner
+    }
+}
+
+/// Encodes `s` as a nul-terminated wide string, as DirectWrite expects of
+/// font family and locale names.
+pub(crate) fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(Some(0)).collect()
+}
@@ -0,0 +1,335 @@
+//! Text laid out in a box, which can be measured, hit-tested and styled by
+//! range.
+
+use super::{
+    format::{wide, FontStyle, FontWeight},
+    metrics::{check_text_range, CaretPosition, HitTest, TextMeasure, TextMetrics, TextRangeError},
+};
+use crate::errors::{Error, Result};
+use ::std::ops::Range;
+use ::win32::invoke::check_res;
+use ::win_geom::d2::{Point2D, Rect2D, Size2D};
+use ::windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::BOOL,
+        Graphics::DirectWrite::{IDWriteTextLayout, DWRITE_HIT_TEST_METRICS, DWRITE_TEXT_RANGE},
+    },
+};
+
+/// Text laid out by DirectWrite within a maximum size, which can be drawn with
+/// [`Context::draw_text_layout`](crate::Context::draw_text_layout).
+///
+/// A layout is made by a [`DWriteFactory`](crate::DWriteFactory) from a
+/// [`TextFormat`](crate::TextFormat), whose font applies to all of the text
+/// until ranges of it are restyled, e.g. with [`set_weight`](Self::set_weight).
+/// Its size, hit tests and caret positions are found through the
+/// [`TextMeasure`] trait. Ranges and indices are byte indices into the text,
+/// and fail with [`Error::TextRange`] unless they are ordered, within the text
+/// and on character boundaries.
+///
+/// Layouts are factory-level resources and never need to be re-created after
+/// device loss. Laying out text is comparatively slow, so a layout should be
+/// kept for as long as its text is unchanged.
+///
+/// # Example
+///
+/// ```no_run
+/// use ::d2d::{DWriteFactory, FontWeight, TextFormatProperties, TextMeasure};
+/// use ::win_geom::d2::Size2D;
+///
+/// let factory = DWriteFactory::new()?;
+/// let format = factory.make_text_format(TextFormatProperties::default())?;
+/// let mut layout = factory.make_text_layout(
+///     "Press Start",
+///     &format,
+///     Size2D { width: 200.0, height: 50.0 },
+/// )?;
+/// layout.set_weight(6..11, FontWeight::BOLD)?;
+///
+/// let width = layout.metrics()?.bounds.width();
+/// # Ok::<(), ::d2d::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct TextLayout {
+    /// The text which was laid out.
+    text: String,
+    /// The underlying DirectWrite text layout.
+    inner: IDWriteTextLayout,
+}
+
+impl TextLayout {
+    /// A crate-private constructor. Only a
+    /// [`DWriteFactory`](crate::DWriteFactory) should be able to create
+    /// text layouts.
+    pub(crate) fn new(text: String, inner: IDWriteTextLayout) -> Self {
+        Self { text, inner }
+    }
+
+    /// The size of the box in which the text is laid out.
+    pub fn max_size(&self) -> Size2D<f32> {
+        Size2D {
+            width: unsafe { self.inner.GetMaxWidth() },
+            height: unsafe { self.inner.GetMaxHeight() },
+        }
+    }
+
+    /// Lays out the text again within a box of `size`, e.g. after its
+    /// container is resized.
+    pub fn set_max_size(&mut self, size: Size2D<f32>) -> Result<()> {
+        check_res(
+            || unsafe { self.inner.SetMaxWidth(size.width) },
+            "SetMaxWidth",
+        )?;
+        Ok(check_res(
+            || unsafe { self.inner.SetMaxHeight(size.height) },
+            "SetMaxHeight",
+        )?)
+    }
+
+    /// Sets the font family of the text in `range`.
+    pub fn set_font_family(&mut self, range: Range<usize>, font_family: &str) -> Result<()> {
+        let range = utf16_range(&self.text, range)?;
+        let font_family = wide(font_family);
+        Ok(check_res(
+            || unsafe {
+                self.inner
+                    .SetFontFamilyName(PCWSTR::from_raw(font_family.as_ptr()), range)
+            },
+            "SetFontFamilyName",
+        )?)
+    }
+
+    /// Sets the size of the font of the text in `range`, in device-independent
+    /// pixels.
+    pub fn set_font_size(&mut self, range: Range<usize>, font_size: f32) -> Result<()> {
+        let range = utf16_range(&self.text, range)?;
+        Ok(check_res(
+            || unsafe { self.inner.SetFontSize(font_size, range) },
+            "SetFontSize",
+        )?)
+    }
+
+    /// Sets the weight of the font of the text in `range`.
+    pub fn set_weight(&mut self, range: Range<usize>, weight: FontWeight) -> Result<()> {
+        let range = utf16_range(&self.text, range)?;
+        Ok(check_res(
+            || unsafe { self.inner.SetFontWeight(weight.into(), range) },
+            "SetFontWeight",
+        )?)
+    }
+
+    /// Sets the slant of the font of the text in `range`.
+    pub fn set_style(&mut self, range: Range<usize>, style: FontStyle) -> Result<()> {
+        let range = utf16_range(&self.text, range)?;
+        Ok(check_res(
+            || unsafe { self.inner.SetFontStyle(style.into(), range) },
+            "SetFontStyle",
+        )?)
+    }
+
+    /// Sets whether the text in `range` is underlined.
+    pub fn set_underline(&mut self, range: Range<usize>, underline: bool) -> Result<()> {
+        let range = utf16_range(&self.text, range)?;
+        Ok(check_res(
+            || unsafe { self.inner.SetUnderline(underline, range) },
+            "SetUnderline",
+        )?)
+    }
+
+    /// Sets whether the text in `range` is struck through.
+    pub fn set_strikethrough(&mut self, range: Range<usize>, strikethrough: bool) -> Result<()> {
+        let range = utf16_range(&self.text, range)?;
+        Ok(check_res(
+            || unsafe { self.inner.SetStrikethrough(strikethrough, range) },
+            "SetStrikethrough",
+        )?)
+    }
+
+    /// Accesses the underlying DirectWrite text layout.
+    pub(crate) fn device_layout(&self) -> &IDWriteTextLayout {
+        &self.inner
+    }
+}
+
+impl TextMeasure for TextLayout {
+    type Error = Error;
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn metrics(&self) -> Result<TextMetrics> {
+        let metrics = check_res(|| unsafe { self.inner.GetMetrics() }, "GetMetrics")?;
+        Ok(TextMetrics {
+            bounds: Rect2D::from_size_and_origin(
+                Size2D {
+                    width: metrics.width,
+                    height: metrics.height,
+                },
+                Point2D {
+                    x: metrics.left,
+                    y: metrics.top,
+                },
+            ),
+            width_including_trailing_whitespace: metrics.widthIncludingTrailingWhitespace,
+            line_count: metrics.lineCount as usize,
+        })
+    }
+
+    fn hit_test_point(&self, point: Point2D<f32>) -> Result<HitTest> {
+        let (mut is_trailing, mut is_inside) = (BOOL::default(), BOOL::default());
+        let mut hit = DWRITE_HIT_TEST_METRICS::default();
+        check_res(
+            || unsafe {
+                self.inner.HitTestPoint(
+                    point.x,
+                    point.y,
+                    &mut is_trailing,
+                    &mut is_inside,
+                    &mut hit,
+                )
+            },
+            "HitTestPoint",
+        )?;
+
+        let index = byte_index(&self.text, hit.textPosition);
+        let caret_index = if is_trailing.as_bool() {
+            byte_index(&self.text, hit.textPosition + hit.length)
+        } else {
+            index
+        };
+        Ok(HitTest {
+            index,
+            caret_index,
+            is_inside: is_inside.as_bool(),
+        })
+    }
+
+    fn caret_position(&self, index: usize) -> Result<CaretPosition> {
+        let (mut x, mut y) = (0.0, 0.0);
+        let mut hit = DWRITE_HIT_TEST_METRICS::default();
+        check_text_range(&self.text, index..index)?;
+        let position = utf16_index(&self.text, index);
+        check_res(
+            || unsafe {
+                self.inner
+                    .HitTestTextPosition(position, false, &mut x, &mut y, &mut hit)
+            },
+            "HitTestTextPosition",
+        )?;
+
+        Ok(CaretPosition {
+            top: Point2D { x, y: hit.top },
+            height: hit.height,
+        })
+    }
+
+    fn range_bounds(&self, range: Range<usize>) -> Result<Vec<Rect2D<f32>>> {
+        let range = utf16_range(&self.text, range)?;
+        let hit_test = |hits: Option<&mut [DWRITE_HIT_TEST_METRICS]>, count: &mut u32| unsafe {
+            self.inner
+                .HitTestTextRange(range.startPosition, range.length, 0.0, 0.0, hits, count)
+        };
+
+        // The first call, with no room for any results, fails but reports
+        // how many there are.
+        let mut count = 0;
+        let _ = hit_test(None, &mut count);
+        let mut hits = vec![DWRITE_HIT_TEST_METRICS::default(); count as usize];
+        check_res(|| hit_test(Some(&mut hits), &mut count), "HitTestTextRange")?;
+
+        Ok(hits
+            .iter()
+            .take(count as usize)
+            .map(|hit| {
+                Rect2D::from_size_and_origin(
+                    Size2D {
+                        width: hit.width,
+                        height: hit.height,
+                    },
+                    Point2D {
+                        x: hit.left,
+                        y: hit.top,
+                    },
+                )
+            })
+            .collect())
+    }
+}
+
+/// Converts a byte range of `text` into the UTF-16 range DirectWrite expects.
+fn utf16_range(
+    text: &str,
+    range: Range<usize>,
+) -> ::std::result::Result<DWRITE_TEXT_RANGE, TextRangeError> {
+    check_text_range(text, range.clone())?;
+    let start = utf16_index(text, range.start);
+    Ok(DWRITE_TEXT_RANGE {
+        startPosition: start,
+        length: utf16_index(text, range.end) - start,
+    })
+}
+
+/// The index in UTF-16 code units of the character at byte `index` of `text`.
+///
+/// # Panics
+///
+/// Panics if `index` is not on a character boundary.
+fn utf16_index(text: &str, index: usize) -> u32 {
+    text[..index].encode_utf16().count() as u32
+}
+
+/// The byte index of the character at `index` in UTF-16 code units of `text`,
+/// or the length of the text if `index` is past its end.
+fn byte_index(text: &str, index: u32) -> usize {
+    let mut utf16 = 0;
+    for (byte, c) in text.char_indices() {
+        if utf16 >= index as usize {
+            return byte;
+        }
+        utf16 += c.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_indices_round_trip_through_utf16() {
+        // "é" is two bytes but one UTF-16 unit, "🦀" four bytes but two units.
+        let text = "aé🦀b";
+
+        let indices = [0, 1, 3, 7, 8];
+        let utf16 = indices.map(|i| utf16_index(text, i));
+
+        assert_eq!(utf16, [0, 1, 2, 4, 5]);
+        assert_eq!(utf16.map(|i| byte_index(text, i)), indices);
+        assert_eq!(byte_index(text, 100), text.len());
+    }
+
+    #[test]
+    fn test_utf16_ranges_are_validated() {
+        let text = "aé🦀b";
+        let range = |range: Range<usize>| {
+            utf16_range(text, range).map(|range| (range.startPosition, range.length))
+        };
+
+        assert_eq!(range(1..7), Ok((1, 3)));
+        assert_eq!(range(8..8), Ok((5, 0)));
+        assert_eq!(range(0..9), Err(TextRangeError { range: 0..9 }));
+        assert_eq!(range(9..9), Err(TextRangeError { range: 9..9 }));
+        // Within "é", and reversed.
+        assert_eq!(range(0..2), Err(TextRangeError { range: 0..2 }));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 3..1;
+        assert_eq!(
+            range(reversed.clone()),
+            Err(TextRangeError { range: reversed })
+        );
+    }
+}
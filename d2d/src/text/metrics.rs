@@ -0,0 +1,374 @@
+//! Measuring and hit-testing laid out text, behind a trait so that code which
+//! positions text can be tested without DirectWrite.
+
+use ::std::ops::Range;
+use ::win_geom::d2::{Point2D, Rect2D, Size2D};
+
+/// The size of laid out text, as measured by [`TextMeasure::metrics`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextMetrics {
+    /// The box enclosing the text, excluding trailing whitespace, relative to
+    /// the layout's origin. Its left and top are offset from the origin by the
+    /// text's alignment.
+    pub bounds: Rect2D<f32>,
+    /// The width of the widest line including its trailing whitespace, e.g.
+    /// to place a caret after a trailing space.
+    pub width_including_trailing_whitespace: f32,
+    /// The number of lines, which is at least one even for empty text.
+    pub line_count: usize,
+}
+
+/// The character under a point, as found by [`TextMeasure::hit_test_point`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HitTest {
+    /// The byte index of the character nearest the point.
+    pub index: usize,
+    /// The byte index at which to place a caret for the point, which is after
+    /// the character if the point is over its trailing half.
+    pub caret_index: usize,
+    /// Whether the point is over the text, rather than outside it and snapped
+    /// to the nearest character.
+    pub is_inside: bool,
+}
+
+/// Where to draw a caret, as found by [`TextMeasure::caret_position`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CaretPosition {
+    /// The top of the caret, relative to the layout's origin.
+    pub top: Point2D<f32>,
+    /// The height of the caret, which is that of its line.
+    pub height: f32,
+}
+
+/// A byte range of a text which is reversed, extends past the end of the text
+/// or splits a character, as rejected by [`TextMeasure`] and
+/// [`TextLayout`](crate::TextLayout). An index is reported as an empty range.
+#[derive(::thiserror::Error, Clone, Debug, PartialEq, Eq)]
+#[error("text range {}..{} is not ordered on character boundaries of the text", range.start, range.end)]
+pub struct TextRangeError {
+    /// The rejected range.
+    pub range: Range<usize>,
+}
+
+/// Checks that `range` is ordered and lies on character boundaries of `text`.
+pub(crate) fn check_text_range(text: &str, range: Range<usize>) -> Result<(), TextRangeError> {
+    if range.start <= range.end
+        && text.is_char_boundary(range.start)
+        && text.is_char_boundary(range.end)
+    {
+        Ok(())
+    } else {
+        Err(TextRangeError { range })
+    }
+}
+
+/// Metrics and hit-testing of text laid out in a box.
+///
+/// Implemented by the DirectWrite [`TextLayout`](crate::TextLayout) and by
+/// [`MonospaceLayout`], a simple stand-in, so that UI code which measures text
+/// or places carets and selections can be tested on any platform. All
+/// positions are relative to the layout's origin and all indices are byte
+/// indices into [`text`](Self::text).
+pub trait TextMeasure {
+    /// The error returned if measuring fails.
+    type Error;
+
+    /// The text which was laid out.
+    fn text(&self) -> &str;
+
+    /// Measures the laid out text.
+    fn metrics(&self) -> Result<TextMetrics, Self::Error>;
+
+    /// Finds the character under `point`, or nearest to it if the point is
+    /// outside the text.
+    fn hit_test_point(&self, point: Point2D<f32>) -> Result<HitTest, Self::Error>;
+
+    /// Finds where to draw a caret placed before the character at byte `index`,
+    /// or after the last character if `index` is the length of the text.
+    ///
+    /// # Errors
+    ///
+    /// Fails with a [`TextRangeError`] if `index` is past the end of the text
+    /// or not on a character boundary.
+    fn caret_position(&self, index: usize) -> Result<CaretPosition, Self::Error>;
+
+    /// The boxes covering the characters in `range`, one per line spanned,
+    /// e.g. to highlight a selection.
+    ///
+    /// # Errors
+    ///
+    /// Fails with a [`TextRangeError`] if `range` is reversed, extends past
+    /// the end of the text or does not start and end on character boundaries.
+    fn range_bounds(&self, range: Range<usize>) -> Result<Vec<Rect2D<f32>>, Self::Error>;
+}
+
+/// A [`TextMeasure`] which lays out text as if in a monospace font, for
+/// testing UI code without DirectWrite.
+///
+/// Every character is `advance` wide and every line `line_height` tall. Lines
+/// are only broken at `'\n'` and are aligned to the left.
+///
+/// # Example
+///
+/// ```
+/// use ::d2d::{MonospaceLayout, TextMeasure};
+/// use ::win_geom::d2::Point2D;
+///
+/// let layout = MonospaceLayout::new("Hello\nworld", 8.0, 16.0);
+///
+/// let hit = layout.hit_test_point(Point2D { x: 13.0, y: 20.0 }).unwrap();
+/// assert_eq!(hit.index, "Hello\nw".len());
+/// assert_eq!(hit.caret_index, "Hello\nwo".len());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonospaceLayout {
+    text: String,
+    advance: f32,
+    line_height: f32,
+}
+
+impl MonospaceLayout {
+    /// Lays out `text` with characters `advance` wide on lines `line_height`
+    /// tall.
+    pub fn new(text: impl Into<String>, advance: f32, line_height: f32) -> Self {
+        Self {
+            text: text.into(),
+            advance,
+            line_height,
+        }
+    }
+
+    /// Each line with the byte index at which it starts, excluding the
+    /// `'\n'` which ends it.
+    fn lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        let mut start = 0;
+        self.text.split('\n').map(move |line| {
+            let line_start = start;
+            start += line.len() + 1;
+            (line_start, line)
+        })
+    }
+
+    /// The width of `count` characters.
+    fn width(&self, count: usize) -> f32 {
+        count as f32 * self.advance
+    }
+
+    /// The box covering the characters in the byte range `span` of the line at
+    /// `row`, which starts at byte `line_start`.
+    fn span(&self, row: usize, line_start: usize, line: &str, span: Range<usize>) -> Rect2D<f32> {
+        let column = |index: usize| line[..index - line_start].chars().count();
+        let top = row as f32 * self.line_height;
+        Rect2D::from_size_and_origin(
+            Size2D {
+                width: self.width(column(span.end) - column(span.start)),
+                height: self.line_height,
+            },
+            Point2D {
+                x: self.width(column(span.start)),
+                y: top,
+            },
+        )
+    }
+}
+
+impl TextMeasure for MonospaceLayout {
+    type Error = TextRangeError;
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn metrics(&self) -> Result<TextMetrics, Self::Error> {
+        let (mut width, mut width_including_trailing_whitespace) = (0.0_f32, 0.0_f32);
+        let mut line_count = 0;
+        for (_, line) in self.lines() {
+            width = width.max(self.width(line.trim_end().chars().count()));
+            width_including_trailing_whitespace =
+                width_including_trailing_whitespace.max(self.width(line.chars().count()));
+            line_count += 1;
+        }
+
+        Ok(TextMetrics {
+            bounds: Rect2D {
+                left: 0.0,
+                top: 0.0,
+                right: width,
+                bottom: line_count as f32 * self.line_height,
+            },
+            width_including_trailing_whitespace,
+            line_count,
+        })
+    }
+
+    fn hit_test_point(&self, point: Point2D<f32>) -> Result<HitTest, Self::Error> {
+        let line_count = self.lines().count();
+        let row = (point.y / self.line_height).floor().max(0.0) as usize;
+        let (line_start, line) = self
+            .lines()
+            .nth(row.min(line_count - 1))
+            .expect("text has at least one line");
+
+        let Some(last) = line.chars().count().checked_sub(1) else {
+            return Ok(HitTest {
+                index: line_start,
+                caret_index: line_start,
+                is_inside: false,
+            });
+        };
+        let column = (point.x / self.advance).floor().max(0.0) as usize;
+        let (offset, c) = line
+            .char_indices()
+            .nth(column.min(last))
+            .expect("column is within the line");
+        let index = line_start + offset;
+        let is_trailing = point.x >= self.width(column.min(last)) + self.advance / 2.0;
+
+        Ok(HitTest {
+            index,
+            caret_index: if is_trailing {
+                index + c.len_utf8()
+            } else {
+                index
+            },
+            is_inside: row < line_count && point.x >= 0.0 && point.y >= 0.0 && column <= last,
+        })
+    }
+
+    fn caret_position(&self, index: usize) -> Result<CaretPosition, Self::Error> {
+        check_text_range(&self.text, index..index)?;
+        let (row, (line_start, line)) = self
+            .lines()
+            .enumerate()
+            .find(|(_, (start, line))| index <= start + line.len())
+            .expect("index is within the text");
+        let rect = self.span(row, line_start, line, line_start..index);
+
+        Ok(CaretPosition {
+            top: Point2D {
+                x: rect.right,
+                y: rect.top,
+            },
+            height: self.line_height,
+        })
+    }
+
+    fn range_bounds(&self, range: Range<usize>) -> Result<Vec<Rect2D<f32>>, Self::Error> {
+        check_text_range(&self.text, range.clone())?;
+        Ok(self
+            .lines()
+            .enumerate()
+            .filter_map(|(row, (line_start, line))| {
+                let start = range.start.max(line_start);
+                let end = range.end.min(line_start + line.len());
+                (start < end).then(|| self.span(row, line_start, line, start..end))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+
+    fn layout() -> MonospaceLayout {
+        MonospaceLayout::new("ab \ncdé", 10.0, 20.0)
+    }
+
+    fn hit(x: f32, y: f32) -> HitTest {
+        layout().hit_test_point(Point2D { x, y }).unwrap()
+    }
+
+    #[test]
+    fn test_metrics_exclude_trailing_whitespace() {
+        let metrics = layout().metrics().unwrap();
+
+        assert_eq!(metrics.bounds.right, 30.0);
+        assert_eq!(metrics.bounds.bottom, 40.0);
+        assert_eq!(metrics.width_including_trailing_whitespace, 30.0);
+        assert_eq!(metrics.line_count, 2);
+
+        let metrics = MonospaceLayout::new("", 10.0, 20.0).metrics().unwrap();
+        assert_eq!(metrics.line_count, 1);
+    }
+
+    #[test]
+    fn test_hit_test_point() {
+        // The leading and trailing halves of "b".
+        assert_eq!(
+            hit(12.0, 5.0),
+            HitTest {
+                index: 1,
+                caret_index: 1,
+                is_inside: true
+            }
+        );
+        assert_eq!(hit(18.0, 5.0).caret_index, 2);
+
+        // Past the end of the second line snaps to its last character.
+        assert_eq!(
+            hit(100.0, 25.0),
+            HitTest {
+                index: "ab \ncd".len(),
+                caret_index: "ab \ncdé".len(),
+                is_inside: false
+            }
+        );
+        // Above the text snaps to the first line.
+        assert_eq!(hit(1.0, -5.0).index, 0);
+        assert!(!hit(1.0, -5.0).is_inside);
+    }
+
+    #[test]
+    fn test_caret_position() {
+        let layout = layout();
+
+        let caret = layout.caret_position(2).unwrap();
+        assert_eq!(caret.top, Point2D { x: 20.0, y: 0.0 });
+
+        let caret = layout.caret_position("ab \ncdé".len()).unwrap();
+        assert_eq!(caret.top, Point2D { x: 30.0, y: 20.0 });
+        assert_eq!(caret.height, 20.0);
+    }
+
+    #[test]
+    fn test_range_bounds_span_lines() {
+        let bounds = layout().range_bounds(1.."ab \nc".len()).unwrap();
+
+        assert_eq!(
+            bounds,
+            [
+                Rect2D {
+                    left: 10.0,
+                    top: 0.0,
+                    right: 30.0,
+                    bottom: 20.0
+                },
+                Rect2D {
+                    left: 0.0,
+                    top: 20.0,
+                    right: 10.0,
+                    bottom: 40.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_indices_are_rejected() {
+        let layout = layout();
+        let len = "ab \ncdé".len();
+        let error = |range: Range<usize>| TextRangeError { range };
+
+        assert_eq!(layout.caret_position(len + 1), Err(error(len + 1..len + 1)));
+        // Within "é", which is two bytes long.
+        assert_eq!(layout.caret_position(len - 1), Err(error(len - 1..len - 1)));
+        assert_eq!(layout.range_bounds(0..len + 1), Err(error(0..len + 1)));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 2..1;
+        assert_eq!(layout.range_bounds(reversed.clone()), Err(error(reversed)));
+        assert_eq!(layout.range_bounds(len..len), Ok(Vec::new()));
+    }
+}
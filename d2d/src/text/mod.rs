@@ -0,0 +1,18 @@
+//! Text drawn and measured with DirectWrite.
+//!
+//! A [`TextFormat`] describes a font and how paragraphs are laid out, and is
+//! enough to draw a string with
+//! [`Context::draw_text`](crate::Context::draw_text). A [`TextLayout`]
+//! additionally keeps the laid out text so that it can be measured, hit-tested
+//! and styled by range. Both are made by a
+//! [`DWriteFactory`](crate::DWriteFactory).
+//!
+//! Measurement is behind the [`TextMeasure`] trait so that UI code can be
+//! tested with a [`MonospaceLayout`] on platforms without DirectWrite.
+
+mod format;
+mod layout;
+mod metrics;
+
+pub(crate) use self::format::wide;
+pub use self::{format::*, layout::*, metrics::*};
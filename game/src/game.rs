@@ -1,47 +1,38 @@
-use crate::resources::FERRIS_ICON;
+use crate::{resources::FERRIS_ICON, scene};
 
-use ::d2d::{brushes::SolidColorBrush, Color, D2DFactory, RenderTarget};
-use ::std::rc::Rc;
-use ::tracing::info;
+use ::d2d::{D2DFactory, DrawList, RenderTarget, SnapMode};
+use ::std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use ::tracing::{error, info};
 use ::win32::{
     errors::Result,
+    input::keyboard::KeyCode,
     window::{Theme, Window},
 };
-use ::win_geom::d2::{Ellipse2D, Point2D, Rect2D, RoundedRect2D, Size2D};
+use ::win_geom::d2::Size2D;
 use ::windows::Win32::UI::WindowsAndMessaging::{
     DispatchMessageW, GetMessageW, PostQuitMessage, TranslateMessage, MSG,
 };
 
-struct DeviceResources {
-    rect_stroke_brush: SolidColorBrush,
-    rect_fill_brush: SolidColorBrush,
-    ellipse_fill_brush: SolidColorBrush,
-
-    red_brush: SolidColorBrush,
-    green_brush: SolidColorBrush,
-    blue_brush: SolidColorBrush,
-}
-
-impl DeviceResources {
-    fn make(render_target: &mut RenderTarget) -> Self {
-        Self {
-            rect_stroke_brush: render_target.make_solid_color_brush(Color::dark_slate_gray()),
-            rect_fill_brush: render_target.make_solid_color_brush(Color::cornflower_blue()),
-            ellipse_fill_brush: render_target.make_solid_color_brush(Color::crimson()),
-
-            red_brush: render_target.make_solid_color_brush(Color::red()),
-            green_brush: render_target.make_solid_color_brush(Color::green()),
-            blue_brush: render_target.make_solid_color_brush(Color::blue()),
-        }
-    }
-}
-
 pub struct Game {
     main_window: Window,
 
     _factory: Rc<D2DFactory>,
     render_target: RenderTarget,
-    resources: DeviceResources,
+    /// The scene recorded for the most recent frame, re-used between frames
+    /// to avoid re-allocating.
+    scene: DrawList,
+
+    /// Whether the capture hotkey was held at the last update, so that each
+    /// press captures a single frame.
+    is_capture_key_down: bool,
+
+    /// Whether a capture was requested, to be saved once the next frame has
+    /// been drawn.
+    is_capture_pending: bool,
 
     /// Dirty flag for changes that require rendering. If not dirty, we can skip
     /// rendering.
@@ -75,21 +66,49 @@ impl Game {
         ::tracing::debug!("Window DPI: {dpi}", dpi = main_window.dpi());
 
         let factory = D2DFactory::new().expect("Failed to create Direct2D factory");
-        let mut render_target = factory.make_render_target(main_window.hwnd(), size);
-        let resources = DeviceResources::make(&mut render_target);
+        let render_target = factory.make_render_target(main_window.hwnd(), size);
 
         Self {
             main_window,
             _factory: factory,
             render_target,
-            resources,
+            scene: DrawList::new(),
+            is_capture_key_down: false,
+            is_capture_pending: false,
             is_render_dirty: true, // Immediately dirty to ensure first draw
             is_shutting_down: false,
         }
     }
 
     fn update(&mut self) {
-        // TODO...
+        let is_capture_key_down = self.main_window.keyboard().is_key_pressed(KeyCode::F12);
+        if is_capture_key_down && !self.is_capture_key_down {
+            // Frames are only kept on request, so draw one to capture.
+            self.is_capture_pending = true;
+            self.is_render_dirty = true;
+        }
+        self.is_capture_key_down = is_capture_key_down;
+    }
+
+    /// Saves the frame on screen as a PNG in the working directory, for
+    /// attaching to bug reports.
+    fn capture_frame(&self) {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis());
+        let path = PathBuf::from(format!("capture-{millis}.png"));
+
+        match self.save_capture(&path) {
+            Ok(()) => info!(path = %path.display(), "captured frame"),
+            Err(e) => error!(error = %e, "failed to capture frame"),
+        }
+    }
+
+    /// Captures the frame on screen and writes it to `path` as a PNG.
+    fn save_capture(&self, path: &Path) -> ::std::result::Result<(), Box<dyn ::std::error::Error>> {
+        let png = self.render_target.capture()?.encode_png()?;
+        ::std::fs::write(path, png)?;
+        Ok(())
     }
 
     fn draw(&mut self) {
@@ -97,85 +116,36 @@ impl Game {
             return;
         }
 
-        let mut ctx = self.render_target.begin_draw();
-        ctx.clear(Color::white());
-
-        let u_dim = self.main_window.size();
-        let f_dim = u_dim.cast::<f32>();
-
-        // Draw light grey grid with 10px squares
-        let stroke_width = 0.5;
-        for (i, x) in (0..u_dim.width).step_by(8).map(|u| u as f32).enumerate() {
-            let brush = match i % 3 {
-                0 => &mut self.resources.red_brush,
-                1 => &mut self.resources.green_brush,
-                2 => &mut self.resources.blue_brush,
-                _ => unreachable!(),
-            };
-
-            ctx.draw_line(
-                Point2D { x, y: 0.0 },
-                Point2D { x, y: f_dim.height },
-                stroke_width,
-                brush,
-            );
+        self.scene.reset();
+        scene::draw(&mut self.scene, self.main_window.size());
+
+        if self.is_capture_pending {
+            self.render_target.request_capture();
         }
-        for (i, y) in (0..u_dim.height).step_by(8).map(|u| u as f32).enumerate() {
-            let brush = match i % 3 {
-                0 => &mut self.resources.red_brush,
-                1 => &mut self.resources.green_brush,
-                2 => &mut self.resources.blue_brush,
-                _ => unreachable!(),
-            };
-            ctx.draw_line(
-                Point2D { x: 0.0, y },
-                Point2D { x: f_dim.width, y },
-                stroke_width,
-                brush,
-            );
+        match self.draw_scene() {
+            Ok(()) => {
+                self.is_render_dirty = false;
+                if self.is_capture_pending {
+                    self.is_capture_pending = false;
+                    self.capture_frame();
+                }
+            }
+            Err(e) => {
+                // Leave the frame dirty so that it is drawn again next time.
+                error!(error = %e, "failed to draw scene");
+                if self.render_target.is_poisoned() {
+                    self.render_target.recover();
+                }
+            }
         }
+    }
 
-        // Draw two rectangles, one inner filled gray and one outer stroked blue
-        ctx.fill_rounded_rect(
-            RoundedRect2D {
-                rect: Rect2D {
-                    left: (u_dim.width / 2 - 56) as _,
-                    right: (u_dim.width / 2 + 56) as _,
-                    top: (u_dim.height / 2 - 56) as _,
-                    bottom: (u_dim.height / 2 + 56) as _,
-                },
-                radius_x: 8.0,
-                radius_y: 8.0,
-            },
-            &mut self.resources.rect_fill_brush,
-        );
-        let stroke_width = 1.0;
-        ctx.stroke_rect(
-            Rect2D {
-                left: (u_dim.width / 2 - 104) as _,
-                right: (u_dim.width / 2 + 104) as _,
-                top: (u_dim.height / 2 - 104) as _,
-                bottom: (u_dim.height / 2 + 104) as _,
-            },
-            &mut self.resources.rect_stroke_brush,
-            stroke_width,
-        );
-
-        // Draw an ellipse in the center
-        ctx.fill_ellipse(
-            Ellipse2D {
-                center: Point2D {
-                    x: (u_dim.width / 2) as _,
-                    y: (u_dim.height / 2) as _,
-                },
-                radius_x: 16.0,
-                radius_y: 16.0,
-            },
-            &mut self.resources.ellipse_fill_brush,
-        );
-
-        ctx.end_draw();
-        self.is_render_dirty = false;
+    /// Replays the recorded scene onto the main window.
+    fn draw_scene(&mut self) -> ::d2d::Result<()> {
+        let mut ctx = self.render_target.begin_draw()?;
+        ctx.set_snap_mode(SnapMode::Positions);
+        let replayed = self.scene.replay(&mut ctx);
+        ctx.end_draw().and(replayed)
     }
 
     pub fn run(&mut self) -> Result<()> {
@@ -5,6 +5,7 @@
 
 mod game;
 mod resources;
+mod scene;
 mod trace;
 
 use crate::game::Game;
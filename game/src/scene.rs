@@ -0,0 +1,95 @@
+//! The game's scene, recorded into a [`DrawList`] so that it can be replayed
+//! onto the window's render target or rendered off-screen in tests.
+
+use ::d2d::{Color, DrawList};
+use ::win_geom::d2::{Ellipse2D, Point2D, Rect2D, RoundedRect2D, Size2D};
+
+/// Records the scene for a window client area of the given size.
+pub fn draw(list: &mut DrawList, u_dim: Size2D<i32>) {
+    list.clear(Color::white());
+
+    let f_dim = u_dim.cast::<f32>();
+    let grid_colors = [Color::red(), Color::green(), Color::blue()];
+
+    // Draw a grid with 8px squares, cycling through red, green and blue
+    let stroke_width = 0.5;
+    for (i, x) in (0..u_dim.width).step_by(8).map(|u| u as f32).enumerate() {
+        list.draw_line(
+            Point2D { x, y: 0.0 },
+            Point2D { x, y: f_dim.height },
+            stroke_width,
+            grid_colors[i % 3],
+            None,
+        );
+    }
+    for (i, y) in (0..u_dim.height).step_by(8).map(|u| u as f32).enumerate() {
+        list.draw_line(
+            Point2D { x: 0.0, y },
+            Point2D { x: f_dim.width, y },
+            stroke_width,
+            grid_colors[i % 3],
+            None,
+        );
+    }
+
+    // Draw two rectangles, one inner filled gray and one outer stroked blue
+    list.fill_rounded_rect(
+        RoundedRect2D {
+            rect: Rect2D {
+                left: (u_dim.width / 2 - 56) as _,
+                right: (u_dim.width / 2 + 56) as _,
+                top: (u_dim.height / 2 - 56) as _,
+                bottom: (u_dim.height / 2 + 56) as _,
+            },
+            radius_x: 8.0,
+            radius_y: 8.0,
+        },
+        Color::cornflower_blue(),
+    );
+    let stroke_width = 1.0;
+    list.stroke_rect(
+        Rect2D {
+            left: (u_dim.width / 2 - 104) as _,
+            right: (u_dim.width / 2 + 104) as _,
+            top: (u_dim.height / 2 - 104) as _,
+            bottom: (u_dim.height / 2 + 104) as _,
+        },
+        Color::dark_slate_gray(),
+        stroke_width,
+        None,
+    );
+
+    // Draw an ellipse in the center
+    list.fill_ellipse(
+        Ellipse2D {
+            center: Point2D {
+                x: (u_dim.width / 2) as _,
+                y: (u_dim.height / 2) as _,
+            },
+            radius_x: 16.0,
+            radius_y: 16.0,
+        },
+        Color::crimson(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::d2d::snapshot::Snapshots;
+
+    #[test]
+    fn test_scene_matches_snapshot() {
+        let size = Size2D {
+            width: 720,
+            height: 640,
+        };
+
+        Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots")).assert(
+            "scene",
+            size.cast(),
+            |list| draw(list, size),
+        );
+    }
+}
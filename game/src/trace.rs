@@ -8,6 +8,10 @@ use ::tracing_subscriber::{fmt, prelude::*, EnvFilter};
 ///
 /// Run the program with `RUST_LOG=trace cargo run` to capture `TRACE`-level
 /// events and upwards.
+///
+/// Run with `RUST_LOG=d2d=debug` to profile rendering. Each frame is drawn
+/// within a `frame` span and ends with a `Frame drawn` event carrying its
+/// [`FrameStats`](::d2d::FrameStats), such as draw calls and `EndDraw` time.
 pub(crate) fn configure() {
     ::tracing_subscriber::registry()
         .with(fmt::layer())
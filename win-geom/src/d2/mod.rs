@@ -12,13 +12,17 @@
 use ::num_traits::{AsPrimitive, Num};
 use ::std::{fmt::Debug, ops::Add};
 
+mod transform;
+
 #[cfg(feature = "d2d")]
 pub use d2d::*;
+pub use transform::*;
 #[cfg(feature = "win32")]
 pub use win32::*;
 
 /// 2D point representation, compatible with any numeric representation.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 #[repr(C)]
 pub struct Point2D<T>
 where
@@ -98,8 +102,10 @@ where
 /// # Conversions
 ///
 /// If _feature_ `"d2d"` is enabled, then a [`Size2D<u32>`] can be directly
-/// converted into a Direct2D `D2D_SIZE_U` struct.
+/// converted into a Direct2D `D2D_SIZE_U` struct, and a [`Size2D<f32>`] into a
+/// Direct2D `D2D_SIZE_F` struct.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 #[repr(C)]
 pub struct Size2D<T>
 where
@@ -204,11 +210,12 @@ where
 /// # Conversions
 ///
 /// If _feature_ `"d2d"` is enabled, then a [`Rect2D<f32>`] can be directly
-/// converted into a Direct2D `D2D_RECT_F` struct.
+/// converted into (and from) a Direct2D `D2D_RECT_F` struct.
 ///
 /// If _feature_ `"win32"` is enabled, then a [`Rect2D<u32>`] can be directly
 /// converted into a Win32 `RECT` struct.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 #[repr(C)]
 pub struct Rect2D<T>
 where
@@ -375,6 +382,7 @@ where
 /// [`radius_x`]: self.radius_x
 /// [`radius_y`]: self.radius_y
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 #[repr(C)]
 pub struct RoundedRect2D<T>
 where
@@ -523,6 +531,7 @@ where
 /// If _feature_ `"d2d"` is enabled, then a [`Ellipse2D<f32>`] can be
 /// directly converted into a Direct2D `D2D1_ELLIPSE` struct.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 #[repr(C)]
 pub struct Ellipse2D<T>
 where
@@ -659,9 +668,12 @@ mod win32 {
 #[cfg(feature = "d2d")]
 mod d2d {
     use super::*;
-    use ::windows::Win32::Graphics::Direct2D::{
-        Common::{D2D_POINT_2F, D2D_RECT_F, D2D_SIZE_U},
-        D2D1_ELLIPSE, D2D1_ROUNDED_RECT,
+    use ::windows::{
+        Foundation::Numerics::Matrix3x2,
+        Win32::Graphics::Direct2D::{
+            Common::{D2D_POINT_2F, D2D_RECT_F, D2D_SIZE_F, D2D_SIZE_U},
+            D2D1_ELLIPSE, D2D1_ROUNDED_RECT,
+        },
     };
 
     impl From<Point2D<f32>> for D2D_POINT_2F {
@@ -682,6 +694,24 @@ mod d2d {
         }
     }
 
+    impl From<Size2D<f32>> for D2D_SIZE_F {
+        fn from(val: Size2D<f32>) -> Self {
+            // SAFETY: our `Size2D` is modelled on the same memory layout as the
+            // Direct2D `D2D_SIZE_F` and we restrict this conversion
+            // implementation to sizes with `f32` representations.
+            unsafe { ::std::mem::transmute(val) }
+        }
+    }
+
+    impl From<D2D_RECT_F> for Rect2D<f32> {
+        fn from(val: D2D_RECT_F) -> Self {
+            // SAFETY: our `Rect2D` is modelled on the same memory layout as the
+            // Direct2D `D2D_RECT_F` and we restrict this conversion
+            // implementation to rectangles with `f32` representations.
+            unsafe { ::std::mem::transmute(val) }
+        }
+    }
+
     impl From<Rect2D<f32>> for D2D_RECT_F {
         fn from(val: Rect2D<f32>) -> Self {
             // SAFETY: our `Rect2D` is modelled on the same memory layout as the
@@ -709,4 +739,12 @@ mod d2d {
             unsafe { ::std::mem::transmute(val) }
         }
     }
+
+    impl From<Transform2D> for Matrix3x2 {
+        fn from(val: Transform2D) -> Self {
+            // SAFETY: our `Transform2D` is modelled on the same memory layout
+            // as the `Matrix3x2` numerics type taken by Direct2D.
+            unsafe { ::std::mem::transmute(val) }
+        }
+    }
 }
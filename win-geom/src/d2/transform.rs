@@ -0,0 +1,296 @@
+//! 2D affine transformations.
+
+use super::Point2D;
+use ::std::ops::Mul;
+
+/// A 2D affine transformation, represented as a 3x2 matrix in the row-vector
+/// convention used by Direct2D:
+///
+/// ```text
+/// | m11 m12 0 |
+/// | m21 m22 0 |
+/// | dx  dy  1 |
+/// ```
+///
+/// A point `(x, y)` is transformed to
+/// `(x * m11 + y * m21 + dx, x * m12 + y * m22 + dy)`.
+///
+/// Transforms are composed with [`then`](Self::then) (or `*`), where `a * b`
+/// applies `a` first and `b` second.
+///
+/// # Conversions
+///
+/// [`Transform2D`] shares the memory layout of the `Matrix3x2` numerics type
+/// taken by Direct2D, into which it converts with the `d2d` feature.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[repr(C)]
+pub struct Transform2D {
+    /// Horizontal scale (and rotation) factor.
+    pub m11: f32,
+    /// Vertical shear (and rotation) factor.
+    pub m12: f32,
+    /// Horizontal shear (and rotation) factor.
+    pub m21: f32,
+    /// Vertical scale (and rotation) factor.
+    pub m22: f32,
+    /// Horizontal translation.
+    pub dx: f32,
+    /// Vertical translation.
+    pub dy: f32,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform2D {
+    /// The identity transform, which leaves all points unchanged.
+    pub const fn identity() -> Self {
+        Self {
+            m11: 1.0,
+            m12: 0.0,
+            m21: 0.0,
+            m22: 1.0,
+            dx: 0.0,
+            dy: 0.0,
+        }
+    }
+
+    /// A transform which moves points by `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ::win_geom::d2::{Point2D, Transform2D};
+    ///
+    /// let t = Transform2D::translation(10.0, -5.0);
+    /// assert_eq!(
+    ///     t.transform_point(Point2D { x: 1.0, y: 1.0 }),
+    ///     Point2D { x: 11.0, y: -4.0 },
+    /// );
+    /// ```
+    pub const fn translation(x: f32, y: f32) -> Self {
+        Self {
+            dx: x,
+            dy: y,
+            ..Self::identity()
+        }
+    }
+
+    /// A transform which scales points by `(sx, sy)` about the origin.
+    pub const fn scale(sx: f32, sy: f32) -> Self {
+        Self {
+            m11: sx,
+            m22: sy,
+            ..Self::identity()
+        }
+    }
+
+    /// A transform which scales points by `(sx, sy)` about `center`.
+    pub fn scale_about(sx: f32, sy: f32, center: Point2D<f32>) -> Self {
+        Self::translation(-center.x, -center.y)
+            .then(Self::scale(sx, sy))
+            .then(Self::translation(center.x, center.y))
+    }
+
+    /// A transform which rotates points clockwise (in the y-down coordinate
+    /// space of a render target) by `radians` about the origin.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ::win_geom::d2::{Point2D, Transform2D};
+    ///
+    /// let t = Transform2D::rotation(::std::f32::consts::FRAC_PI_2);
+    /// let p = t.transform_point(Point2D { x: 1.0, y: 0.0 });
+    ///
+    /// assert!((p.x - 0.0).abs() < 1e-6);
+    /// assert!((p.y - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn rotation(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self {
+            m11: cos,
+            m12: sin,
+            m21: -sin,
+            m22: cos,
+            dx: 0.0,
+            dy: 0.0,
+        }
+    }
+
+    /// A transform which rotates points clockwise by `radians` about
+    /// `center`.
+    pub fn rotation_about(radians: f32, center: Point2D<f32>) -> Self {
+        Self::translation(-center.x, -center.y)
+            .then(Self::rotation(radians))
+            .then(Self::translation(center.x, center.y))
+    }
+
+    /// Composes two transforms, returning a transform which applies `self`
+    /// first and `next` second.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ::win_geom::d2::{Point2D, Transform2D};
+    ///
+    /// let t = Transform2D::scale(2.0, 2.0).then(Transform2D::translation(1.0, 0.0));
+    /// assert_eq!(
+    ///     t.transform_point(Point2D { x: 1.0, y: 1.0 }),
+    ///     Point2D { x: 3.0, y: 2.0 },
+    /// );
+    /// ```
+    pub fn then(self, next: Transform2D) -> Self {
+        Self {
+            m11: self.m11 * next.m11 + self.m12 * next.m21,
+            m12: self.m11 * next.m12 + self.m12 * next.m22,
+            m21: self.m21 * next.m11 + self.m22 * next.m21,
+            m22: self.m21 * next.m12 + self.m22 * next.m22,
+            dx: self.dx * next.m11 + self.dy * next.m21 + next.dx,
+            dy: self.dx * next.m12 + self.dy * next.m22 + next.dy,
+        }
+    }
+
+    /// Applies the transform to a point.
+    pub fn transform_point(&self, p: Point2D<f32>) -> Point2D<f32> {
+        Point2D {
+            x: p.x * self.m11 + p.y * self.m21 + self.dx,
+            y: p.x * self.m12 + p.y * self.m22 + self.dy,
+        }
+    }
+
+    /// The determinant of the transform's linear part. A transform with a zero
+    /// determinant collapses space and cannot be inverted.
+    pub fn determinant(&self) -> f32 {
+        self.m11 * self.m22 - self.m12 * self.m21
+    }
+
+    /// Whether the transform is exactly the identity.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// The inverse of the transform, if one exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ::win_geom::d2::{Point2D, Transform2D};
+    ///
+    /// let t = Transform2D::translation(5.0, 5.0).then(Transform2D::scale(2.0, 4.0));
+    /// let p = Point2D { x: 3.0, y: 7.0 };
+    ///
+    /// assert_eq!(t.invert().unwrap().transform_point(t.transform_point(p)), p);
+    /// assert_eq!(Transform2D::scale(0.0, 1.0).invert(), None);
+    /// ```
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let m11 = self.m22 / det;
+        let m12 = -self.m12 / det;
+        let m21 = -self.m21 / det;
+        let m22 = self.m11 / det;
+        Some(Self {
+            m11,
+            m12,
+            m21,
+            m22,
+            dx: -(self.dx * m11 + self.dy * m21),
+            dy: -(self.dx * m12 + self.dy * m22),
+        })
+    }
+}
+
+impl Mul for Transform2D {
+    type Output = Transform2D;
+
+    /// Composes two transforms, applying `self` first and `rhs` second.
+    fn mul(self, rhs: Transform2D) -> Self::Output {
+        self.then(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::pretty_assertions::assert_eq;
+    use ::std::f32::consts::FRAC_PI_2;
+
+    fn assert_near(actual: Point2D<f32>, expected: Point2D<f32>) {
+        assert!(
+            (actual.x - expected.x).abs() < 1e-5 && (actual.y - expected.y).abs() < 1e-5,
+            "{actual:?} is not near {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_then_applies_self_first() {
+        let scale = Transform2D::scale(2.0, 3.0);
+        let translate = Transform2D::translation(1.0, 1.0);
+        let p = Point2D { x: 1.0, y: 1.0 };
+
+        assert_eq!(
+            scale.then(translate).transform_point(p),
+            Point2D { x: 3.0, y: 4.0 }
+        );
+        assert_eq!(
+            translate.then(scale).transform_point(p),
+            Point2D { x: 4.0, y: 6.0 }
+        );
+        assert_eq!(scale * translate, scale.then(translate));
+        assert_eq!(Transform2D::identity().then(scale), scale);
+    }
+
+    #[test]
+    fn test_invert() {
+        let t = Transform2D::rotation(0.3)
+            .then(Transform2D::scale(2.0, 0.5))
+            .then(Transform2D::translation(-4.0, 7.0));
+        let p = Point2D { x: 3.0, y: -2.0 };
+
+        let inverse = t.invert().unwrap();
+        assert_near(inverse.transform_point(t.transform_point(p)), p);
+        assert_near(
+            t.then(inverse).transform_point(p),
+            Transform2D::identity().transform_point(p),
+        );
+    }
+
+    #[test]
+    fn test_singular_transforms_have_no_inverse() {
+        assert_eq!(Transform2D::scale(0.0, 1.0).invert(), None);
+        // Rows which are multiples of each other collapse space onto a line.
+        let collapsed = Transform2D {
+            m11: 1.0,
+            m12: 2.0,
+            m21: 2.0,
+            m22: 4.0,
+            dx: 5.0,
+            dy: 5.0,
+        };
+        assert_eq!(collapsed.determinant(), 0.0);
+        assert_eq!(collapsed.invert(), None);
+        assert_eq!(Transform2D::scale(f32::INFINITY, 1.0).invert(), None);
+    }
+
+    #[test]
+    fn test_rotation_about_keeps_center_fixed() {
+        let center = Point2D { x: 10.0, y: 5.0 };
+        let t = Transform2D::rotation_about(FRAC_PI_2, center);
+
+        assert_near(t.transform_point(center), center);
+        // A quarter turn clockwise in y-down space moves right to down.
+        assert_near(
+            t.transform_point(Point2D { x: 11.0, y: 5.0 }),
+            Point2D { x: 10.0, y: 6.0 },
+        );
+    }
+}
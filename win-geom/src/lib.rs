@@ -8,6 +8,11 @@
 //!
 //! If _feature_ `"win32"` is enabled, then some primitives can be directly
 //! converted into a Win32 structures.
+//!
+//! # Serialization
+//!
+//! If _feature_ `"serde"` is enabled, then all primitives implement `serde`'s
+//! `Serialize` and `Deserialize` traits.
 
 #![deny(rust_2018_idioms)]
 #![warn(missing_docs)]
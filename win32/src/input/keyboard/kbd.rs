@@ -3,7 +3,6 @@
 use ::bitvec::prelude::*;
 use ::std::{char::REPLACEMENT_CHARACTER, collections::VecDeque};
 use ::tracing::trace;
-use ::widestring::WideChar;
 
 use super::{KeyCode, KeystrokeFlags};
 
@@ -52,7 +51,9 @@ pub(crate) enum KeyEvent {
         flags: KeystrokeFlags,
     },
     Input {
-        wchar: WideChar,
+        /// A UTF-16 code unit. `WM_CHAR` always delivers UTF-16, regardless of
+        /// the platform's native wide character width.
+        wchar: u16,
         flags: KeystrokeFlags,
     },
 }
@@ -72,7 +73,7 @@ pub struct Keyboard {
     /// receipt of the following low surrogate. Once the low surrogate arrives,
     /// the pair can be converted into a character and appended to
     /// `input_queue`.
-    pending_surrogate: Option<WideChar>,
+    pending_surrogate: Option<u16>,
 }
 
 impl Keyboard {